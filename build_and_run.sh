@@ -1,26 +1,14 @@
 #!/bin/bash
 set -e
 
-echo "Building common API..."
+echo "Building plugin API..."
+cargo build -p deepnest-plugin-api
 
-pushd common
-cargo build
-popd
+echo "Building deepnest-plugin-svg..."
+cargo build -p deepnest-plugin-svg
 
-echo "Building plugin_svg..."
+echo "Building deepnest-server..."
+cargo build -p deepnest-server
 
-pushd plugin_svg
-cargo build --target-dir ../target_svg
-popd
-
-echo "Building plugin_math..."
-
-pushd plugin_math
-cargo build --target-dir ../target_math
-popd
-
-echo "Building main..."
-
-pushd main
-cargo run
-popd
\ No newline at end of file
+echo "Formats of the plugins in target/debug:"
+cargo run -p deepnest-server -- plugins target/debug
@@ -1,3 +1,65 @@
-fn main() {
-    println!("Hello, world!");
+//! Command line host for format plugins.
+//!
+//! ```text
+//! deepnest-server plugins <plugin-dir>
+//! deepnest-server import <plugin-dir> <file>
+//! ```
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use deepnest_plugin_api::PluginRegistry;
+
+const USAGE: &str = "usage:
+  deepnest-server plugins <plugin-dir>        list the formats of the plugins in <plugin-dir>
+  deepnest-server import <plugin-dir> <file>  print the parts of <file> as JSON";
+
+fn main() -> ExitCode {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let result = match args
+    .iter()
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .as_slice()
+  {
+    ["plugins", dir] => list_plugins(Path::new(dir)),
+    ["import", dir, file] => import(Path::new(dir), Path::new(file)),
+    _ => Err(USAGE.to_string()),
+  };
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(message) => {
+      eprintln!("{}", message);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn load_plugins(dir: &Path) -> Result<PluginRegistry, String> {
+  let mut registry = PluginRegistry::new();
+  let failed = registry.load_dir(dir).map_err(|err| err.to_string())?;
+  for (path, err) in failed {
+    // A broken plugin does not keep the others from loading.
+    eprintln!("skipping {}: {}", path.display(), err);
+  }
+  Ok(registry)
+}
+
+fn list_plugins(dir: &Path) -> Result<(), String> {
+  for plugin in load_plugins(dir)?.plugins() {
+    println!("{}\t{}", plugin.format_id(), plugin.extensions().join(","));
+  }
+  Ok(())
+}
+
+fn import(dir: &Path, file: &Path) -> Result<(), String> {
+  let registry = load_plugins(dir)?;
+  let plugin = registry
+    .for_path(file)
+    .ok_or_else(|| format!("no plugin for {}", file.display()))?;
+  let data = std::fs::read(file).map_err(|err| format!("{}: {}", file.display(), err))?;
+  let parts = plugin.import(&data).map_err(|err| err.to_string())?;
+  let json = serde_json::to_string_pretty(&parts).map_err(|err| err.to_string())?;
+  println!("{}", json);
+  Ok(())
 }
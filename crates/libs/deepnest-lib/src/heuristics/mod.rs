@@ -0,0 +1,169 @@
+//! Hooks for user-supplied placement heuristics.
+//!
+//! A placement loop asks a [`PlacementHeuristic`] to score the candidate positions of
+//! a part with [`best_placement`], and optionally to choose the order in which parts
+//! are placed with [`order_parts`]. [`crate::nfp::place_on_nfp`] chooses the position
+//! of a part on its no-fit polygons with a heuristic. With the `wasm` feature,
+//! heuristics can be loaded from WebAssembly modules, see [`WasmHeuristic`].
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmHeuristic, WasmLimits};
+
+/// A candidate position of a part.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// index of the part in the list being nested
+    pub part: usize,
+    /// index of the sheet
+    pub sheet: usize,
+    pub x: f64,
+    pub y: f64,
+    /// rotation in degrees
+    pub rotation: f64,
+    /// width of the bounding box of the rotated part
+    pub width: f64,
+    /// height of the bounding box of the rotated part
+    pub height: f64,
+}
+
+/// What a heuristic knows about a part when ordering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PartInfo {
+    pub area: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeuristicError {
+    /// The module is invalid or lacks a required export.
+    Load(String),
+    /// The heuristic failed, e.g. a WebAssembly trap.
+    Trap(String),
+    /// The heuristic used up its fuel.
+    OutOfFuel,
+    /// The heuristic ran longer than its time limit.
+    Timeout,
+    /// The heuristic returned a value the engine cannot use.
+    InvalidResult(String),
+}
+
+impl fmt::Display for HeuristicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeuristicError::Load(message) => write!(f, "Failed to load heuristic: {}", message),
+            HeuristicError::Trap(message) => write!(f, "Heuristic failed: {}", message),
+            HeuristicError::OutOfFuel => write!(f, "Heuristic ran out of fuel"),
+            HeuristicError::Timeout => write!(f, "Heuristic exceeded its time limit"),
+            HeuristicError::InvalidResult(message) => {
+                write!(f, "Heuristic returned an invalid result: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeuristicError {}
+
+pub trait PlacementHeuristic {
+    /// Score of a candidate placement, lower is better as for the fitness of a nest.
+    fn score(&mut self, placement: &Placement) -> Result<f64, HeuristicError>;
+
+    /// The order in which `parts` are placed, as indices into `parts`, or `None` to
+    /// keep the order of the engine.
+    fn order(&mut self, _parts: &[PartInfo]) -> Result<Option<Vec<usize>>, HeuristicError> {
+        Ok(None)
+    }
+}
+
+/// Index of the candidate with the lowest score, the first one on ties.
+pub fn best_placement(
+    heuristic: &mut dyn PlacementHeuristic,
+    candidates: &[Placement],
+) -> Result<Option<usize>, HeuristicError> {
+    let mut best: Option<(usize, f64)> = None;
+    for (index, candidate) in candidates.iter().enumerate() {
+        let score = heuristic.score(candidate)?;
+        if score.is_nan() {
+            return Err(HeuristicError::InvalidResult(
+                "the score is NaN".to_string(),
+            ));
+        }
+        if best.is_none_or(|(_, best_score)| score < best_score) {
+            best = Some((index, score));
+        }
+    }
+    Ok(best.map(|(index, _)| index))
+}
+
+/// The order of `parts` chosen by the heuristic, checked to be a permutation.
+/// Without an order hook the parts keep their order.
+pub fn order_parts(
+    heuristic: &mut dyn PlacementHeuristic,
+    parts: &[PartInfo],
+) -> Result<Vec<usize>, HeuristicError> {
+    let Some(order) = heuristic.order(parts)? else {
+        return Ok((0..parts.len()).collect());
+    };
+    let mut seen = vec![false; parts.len()];
+    let is_permutation = order.len() == parts.len()
+        && order
+            .iter()
+            .all(|&index| index < parts.len() && !std::mem::replace(&mut seen[index], true));
+    if !is_permutation {
+        return Err(HeuristicError::InvalidResult(format!(
+            "{:?} is not an order of {} parts",
+            order,
+            parts.len()
+        )));
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prefers placements close to the origin and places the largest parts first.
+    struct NearOrigin;
+
+    impl PlacementHeuristic for NearOrigin {
+        fn score(&mut self, placement: &Placement) -> Result<f64, HeuristicError> {
+            Ok(placement.x.hypot(placement.y))
+        }
+
+        fn order(&mut self, parts: &[PartInfo]) -> Result<Option<Vec<usize>>, HeuristicError> {
+            let mut order: Vec<usize> = (0..parts.len()).collect();
+            order.sort_by(|&a, &b| parts[b].area.total_cmp(&parts[a].area));
+            Ok(Some(order))
+        }
+    }
+
+    #[test]
+    fn test_best_placement_and_order() {
+        let at = |x: f64, y: f64| Placement {
+            part: 0,
+            sheet: 0,
+            x,
+            y,
+            rotation: 0.0,
+            width: 10.0,
+            height: 10.0,
+        };
+        let candidates = [at(5.0, 5.0), at(1.0, 2.0), at(0.0, 3.0)];
+        assert_eq!(best_placement(&mut NearOrigin, &candidates), Ok(Some(1)));
+        assert_eq!(best_placement(&mut NearOrigin, &[]), Ok(None));
+
+        let part = |area: f64| PartInfo {
+            area,
+            width: 1.0,
+            height: area,
+        };
+        let parts = [part(1.0), part(3.0), part(2.0)];
+        assert_eq!(order_parts(&mut NearOrigin, &parts), Ok(vec![1, 2, 0]));
+    }
+}
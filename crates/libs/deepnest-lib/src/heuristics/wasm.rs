@@ -0,0 +1,400 @@
+//! Heuristics compiled to WebAssembly, run with wasmtime.
+//!
+//! A module exports
+//!
+//! - `score(part: i32, sheet: i32, x: f64, y: f64, rotation: f64, width: f64, height: f64) -> f64`
+//!   with the fields of a [`Placement`], and optionally
+//! - `order(ptr: i32, count: i32)` together with `memory` and `alloc(size: i32) -> i32`.
+//!   The host writes `count` records of four little-endian `f64` (index, area, width,
+//!   height) to the memory returned by `alloc`, and `order` sorts the records in place.
+//!
+//! Modules get no imports, so they cannot reach files or the network. Every call runs
+//! with a fresh amount of fuel and a time limit, and the memory of a module is capped,
+//! so a broken heuristic fails the call instead of hanging the nest. All heuristics
+//! share one engine and one thread that enforces the time limits.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use wasmtime::{
+    Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+    TypedFunc,
+};
+
+use super::{HeuristicError, PartInfo, Placement, PlacementHeuristic};
+
+/// Resolution of the time limit.
+const TICK: Duration = Duration::from_millis(1);
+
+/// Size of a part record passed to `order`.
+const RECORD_SIZE: usize = 4 * std::mem::size_of::<f64>();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WasmLimits {
+    /// fuel of a single call, roughly the number of executed instructions
+    pub fuel: u64,
+    /// time limit of a single call
+    pub timeout: Duration,
+    /// maximum size of the linear memory in bytes
+    pub max_memory: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        WasmLimits {
+            fuel: 10_000_000,
+            timeout: Duration::from_millis(100),
+            max_memory: 64 << 20,
+        }
+    }
+}
+
+type ScoreFn = TypedFunc<(i32, i32, f64, f64, f64, f64, f64), f64>;
+
+struct OrderHook {
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    order: TypedFunc<(i32, i32), ()>,
+}
+
+pub struct WasmHeuristic {
+    store: Store<StoreLimits>,
+    score: ScoreFn,
+    order: Option<OrderHook>,
+    limits: WasmLimits,
+    _ticker: Arc<Ticker>,
+}
+
+impl WasmHeuristic {
+    /// Instantiates a module in binary or text format.
+    pub fn new(module: &[u8], limits: WasmLimits) -> Result<WasmHeuristic, HeuristicError> {
+        let load_error = |err: wasmtime::Error| HeuristicError::Load(err.to_string());
+        let (engine, ticker) = shared_engine()?;
+        let module = Module::new(&engine, module).map_err(load_error)?;
+
+        let mut store = Store::new(
+            &engine,
+            StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory)
+                .build(),
+        );
+        store.limiter(|limits| limits);
+        // The start function of the module runs under the same limits as the calls.
+        prepare(&mut store, &limits)?;
+        let instance = Instance::new(&mut store, &module, &[]).map_err(|err| {
+            if err.downcast_ref::<Trap>().is_some() {
+                call_error(err)
+            } else {
+                load_error(err)
+            }
+        })?;
+
+        let score = instance
+            .get_typed_func(&mut store, "score")
+            .map_err(|err| HeuristicError::Load(format!("score: {}", err)))?;
+        let order = if instance.get_func(&mut store, "order").is_some() {
+            let export_error = |name: &str, err: wasmtime::Error| {
+                HeuristicError::Load(format!("{}: {}", name, err))
+            };
+            Some(OrderHook {
+                memory: instance.get_memory(&mut store, "memory").ok_or_else(|| {
+                    HeuristicError::Load("order requires an exported memory".to_string())
+                })?,
+                alloc: instance
+                    .get_typed_func(&mut store, "alloc")
+                    .map_err(|err| export_error("alloc", err))?,
+                order: instance
+                    .get_typed_func(&mut store, "order")
+                    .map_err(|err| export_error("order", err))?,
+            })
+        } else {
+            None
+        };
+
+        Ok(WasmHeuristic {
+            store,
+            score,
+            order,
+            limits,
+            _ticker: ticker,
+        })
+    }
+
+    pub fn from_file(path: &Path, limits: WasmLimits) -> Result<WasmHeuristic, HeuristicError> {
+        let module = std::fs::read(path)
+            .map_err(|err| HeuristicError::Load(format!("{}: {}", path.display(), err)))?;
+        WasmHeuristic::new(&module, limits)
+    }
+}
+
+impl PlacementHeuristic for WasmHeuristic {
+    fn score(&mut self, placement: &Placement) -> Result<f64, HeuristicError> {
+        prepare(&mut self.store, &self.limits)?;
+        self.score
+            .call(
+                &mut self.store,
+                (
+                    placement.part as i32,
+                    placement.sheet as i32,
+                    placement.x,
+                    placement.y,
+                    placement.rotation,
+                    placement.width,
+                    placement.height,
+                ),
+            )
+            .map_err(call_error)
+    }
+
+    fn order(&mut self, parts: &[PartInfo]) -> Result<Option<Vec<usize>>, HeuristicError> {
+        let Some(hook) = &self.order else {
+            return Ok(None);
+        };
+        let mut records = Vec::with_capacity(parts.len() * RECORD_SIZE);
+        for (index, part) in parts.iter().enumerate() {
+            for value in [index as f64, part.area, part.width, part.height] {
+                records.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        prepare(&mut self.store, &self.limits)?;
+        let ptr = hook
+            .alloc
+            .call(&mut self.store, records.len() as i32)
+            .map_err(call_error)?;
+        let invalid_memory =
+            |err| HeuristicError::InvalidResult(format!("alloc returned {}: {}", ptr, err));
+        hook.memory
+            .write(&mut self.store, ptr as u32 as usize, &records)
+            .map_err(invalid_memory)?;
+        hook.order
+            .call(&mut self.store, (ptr, parts.len() as i32))
+            .map_err(call_error)?;
+        hook.memory
+            .read(&self.store, ptr as u32 as usize, &mut records)
+            .map_err(invalid_memory)?;
+
+        let order = records
+            .chunks_exact(RECORD_SIZE)
+            .map(|record| {
+                let index = f64::from_le_bytes(record[..8].try_into().unwrap());
+                if index >= 0.0 && index.fract() == 0.0 && index < parts.len() as f64 {
+                    Ok(index as usize)
+                } else {
+                    Err(HeuristicError::InvalidResult(format!(
+                        "{} is not the index of a part",
+                        index
+                    )))
+                }
+            })
+            .collect::<Result<Vec<usize>, HeuristicError>>()?;
+        Ok(Some(order))
+    }
+}
+
+/// Refills the fuel and restarts the time limit before a call.
+fn prepare(store: &mut Store<StoreLimits>, limits: &WasmLimits) -> Result<(), HeuristicError> {
+    store
+        .set_fuel(limits.fuel)
+        .map_err(|err| HeuristicError::Trap(err.to_string()))?;
+    let ticks = limits.timeout.as_nanos().div_ceil(TICK.as_nanos()).max(1);
+    store.set_epoch_deadline(ticks.try_into().unwrap_or(u64::MAX));
+    Ok(())
+}
+
+fn call_error(err: wasmtime::Error) -> HeuristicError {
+    match err.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => HeuristicError::OutOfFuel,
+        Some(Trap::Interrupt) => HeuristicError::Timeout,
+        _ => HeuristicError::Trap(err.to_string()),
+    }
+}
+
+/// The engine of all heuristics, with its ticker. The ticker runs while a heuristic
+/// holds it.
+fn shared_engine() -> Result<(Engine, Arc<Ticker>), HeuristicError> {
+    static ENGINE: OnceLock<Result<Engine, String>> = OnceLock::new();
+    static TICKER: Mutex<Weak<Ticker>> = Mutex::new(Weak::new());
+    let engine = ENGINE
+        .get_or_init(|| {
+            let mut config = Config::new();
+            config.consume_fuel(true).epoch_interruption(true);
+            Engine::new(&config).map_err(|err| err.to_string())
+        })
+        .clone()
+        .map_err(HeuristicError::Load)?;
+    let mut ticker = TICKER.lock().unwrap_or_else(PoisonError::into_inner);
+    let running = match ticker.upgrade() {
+        Some(running) => running,
+        None => {
+            let started = Arc::new(Ticker::start(engine.clone()));
+            *ticker = Arc::downgrade(&started);
+            started
+        }
+    };
+    Ok((engine, running))
+}
+
+/// Advances the epoch of an engine every [`TICK`], which interrupts calls past their
+/// deadline.
+struct Ticker {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    fn start(engine: Engine) -> Ticker {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(TICK);
+                    engine.increment_epoch();
+                }
+            }
+        });
+        Ticker {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristics::{best_placement, order_parts};
+
+    /// Bottom-left: prefers low placements, then left ones. Loops forever for part 1.
+    const BOTTOM_LEFT: &str = r#"(module
+  (func (export "score")
+    (param $part i32) (param $sheet i32) (param $x f64) (param $y f64)
+    (param $rotation f64) (param $width f64) (param $height f64) (result f64)
+    (if (local.get $part) (then (loop $forever (br $forever))))
+    (f64.add (f64.mul (local.get $y) (f64.const 1000)) (local.get $x))))"#;
+
+    /// Reverses the order of the parts.
+    const REVERSE: &str = r#"(module
+  (memory (export "memory") 1)
+  (func (export "score") (param i32 i32 f64 f64 f64 f64 f64) (result f64)
+    (f64.const 0))
+  (func (export "alloc") (param i32) (result i32)
+    (i32.const 1024))
+  (func (export "order") (param $ptr i32) (param $count i32)
+    (local $lo i32) (local $hi i32) (local $index f64)
+    (local.set $lo (local.get $ptr))
+    (local.set $hi (i32.add (local.get $ptr)
+      (i32.mul (i32.sub (local.get $count) (i32.const 1)) (i32.const 32))))
+    (block $done (loop $swap
+      (br_if $done (i32.ge_s (local.get $lo) (local.get $hi)))
+      (local.set $index (f64.load (local.get $lo)))
+      (f64.store (local.get $lo) (f64.load (local.get $hi)))
+      (f64.store (local.get $hi) (local.get $index))
+      (local.set $lo (i32.add (local.get $lo) (i32.const 32)))
+      (local.set $hi (i32.sub (local.get $hi) (i32.const 32)))
+      (br $swap)))))"#;
+
+    fn placement(part: usize, x: f64, y: f64) -> Placement {
+        Placement {
+            part,
+            sheet: 0,
+            x,
+            y,
+            rotation: 0.0,
+            width: 10.0,
+            height: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_score_with_limits() {
+        let mut heuristic =
+            WasmHeuristic::new(BOTTOM_LEFT.as_bytes(), WasmLimits::default()).unwrap();
+        let candidates = [
+            placement(0, 0.0, 10.0),
+            placement(0, 30.0, 0.0),
+            placement(0, 20.0, 0.0),
+        ];
+        assert_eq!(best_placement(&mut heuristic, &candidates), Ok(Some(2)));
+        assert_eq!(order_parts(&mut heuristic, &[]), Ok(vec![]));
+
+        assert_eq!(
+            heuristic.score(&placement(1, 0.0, 0.0)),
+            Err(HeuristicError::OutOfFuel)
+        );
+        // A failed call leaves the heuristic usable.
+        assert_eq!(heuristic.score(&placement(0, 1.0, 2.0)), Ok(2001.0));
+
+        let limits = WasmLimits {
+            fuel: u64::MAX,
+            timeout: Duration::from_millis(20),
+            ..WasmLimits::default()
+        };
+        let mut heuristic = WasmHeuristic::new(BOTTOM_LEFT.as_bytes(), limits).unwrap();
+        assert_eq!(
+            heuristic.score(&placement(1, 0.0, 0.0)),
+            Err(HeuristicError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_order() {
+        let mut heuristic = WasmHeuristic::new(REVERSE.as_bytes(), WasmLimits::default()).unwrap();
+        let part = PartInfo {
+            area: 1.0,
+            width: 1.0,
+            height: 1.0,
+        };
+        assert_eq!(
+            order_parts(&mut heuristic, &[part; 4]),
+            Ok(vec![3, 2, 1, 0])
+        );
+        assert_eq!(order_parts(&mut heuristic, &[]), Ok(vec![]));
+
+        // Heuristics alive at the same time share the engine and its ticker.
+        let other = WasmHeuristic::new(REVERSE.as_bytes(), WasmLimits::default()).unwrap();
+        assert!(Engine::same(heuristic.store.engine(), other.store.engine()));
+        assert!(Arc::ptr_eq(&heuristic._ticker, &other._ticker));
+
+        let pointer_out_of_memory = REVERSE.replace("(i32.const 1024)", "(i32.const 65530)");
+        let mut heuristic =
+            WasmHeuristic::new(pointer_out_of_memory.as_bytes(), WasmLimits::default()).unwrap();
+        assert!(matches!(
+            heuristic.order(&[part; 2]),
+            Err(HeuristicError::InvalidResult(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_modules() {
+        let load = |module: &str| WasmHeuristic::new(module.as_bytes(), WasmLimits::default());
+        assert!(matches!(load("(module"), Err(HeuristicError::Load(_))));
+        assert!(matches!(load("(module)"), Err(HeuristicError::Load(_))));
+        // Imports are not provided.
+        let import = r#"(module (import "env" "now" (func)) (func (export "score")
+  (param i32 i32 f64 f64 f64 f64 f64) (result f64) (f64.const 0)))"#;
+        assert!(matches!(load(import), Err(HeuristicError::Load(_))));
+        // 128 MiB exceed the default memory limit.
+        let memory = r#"(module (memory 2048) (func (export "score")
+  (param i32 i32 f64 f64 f64 f64 f64) (result f64) (f64.const 0)))"#;
+        assert!(matches!(load(memory), Err(HeuristicError::Load(_))));
+        // The order hook needs the memory to exchange the parts.
+        let order = r#"(module (func (export "score")
+  (param i32 i32 f64 f64 f64 f64 f64) (result f64) (f64.const 0))
+  (func (export "order") (param i32 i32)))"#;
+        assert!(matches!(load(order), Err(HeuristicError::Load(_))));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod heuristics;
 pub mod nfp;
 #[cfg(feature = "traits")]
 pub use ::deepnest_types::traits;
@@ -2,6 +2,8 @@ use babushka::kernelf64::{Point2D, Polygon};
 use babushka::multi_polygon::MultiPolygon;
 use babushka::no_fit_polygon::ComputeNoFitPolygon as _;
 
+use crate::heuristics::{best_placement, HeuristicError, Placement, PlacementHeuristic};
+
 pub fn calculate_nfp(a: MultiPolygon<Polygon>, b: MultiPolygon<Polygon>) -> Vec<Vec<Point2D>> {
     let mut nfp_list = vec![];
     nfp_list.extend(
@@ -18,3 +20,73 @@ pub fn calculate_nfp(a: MultiPolygon<Polygon>, b: MultiPolygon<Polygon>) -> Vec<
     // }
     nfp_list
 }
+
+/// Chooses the position of a part among the vertices of its no-fit polygons `nfp`.
+/// `part` describes the part, its `x` and `y` are replaced by the chosen vertex.
+///
+/// With a `heuristic`, the vertex it scores lowest is chosen, see [`best_placement`].
+/// Without one, the leftmost vertex is chosen, the lowest one on ties. Returns `None`
+/// when the no-fit polygons have no vertices.
+pub fn place_on_nfp(
+    nfp: &[Vec<Point2D>],
+    part: Placement,
+    heuristic: Option<&mut dyn PlacementHeuristic>,
+) -> Result<Option<Placement>, HeuristicError> {
+    let candidates: Vec<Placement> = nfp
+        .iter()
+        .flatten()
+        .map(|vertex| Placement {
+            x: vertex.x,
+            y: vertex.y,
+            ..part
+        })
+        .collect();
+    let best = match heuristic {
+        Some(heuristic) => best_placement(heuristic, &candidates)?,
+        None => candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)))
+            .map(|(index, _)| index),
+    };
+    Ok(best.map(|index| candidates[index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prefers the placement farthest to the right.
+    struct Rightmost;
+
+    impl PlacementHeuristic for Rightmost {
+        fn score(&mut self, placement: &Placement) -> Result<f64, HeuristicError> {
+            Ok(-placement.x)
+        }
+    }
+
+    #[test]
+    fn test_place_on_nfp() {
+        let point = |x: f64, y: f64| Point2D { x, y };
+        let nfp = vec![
+            vec![point(5.0, 0.0), point(0.0, 4.0), point(0.0, 2.0)],
+            vec![point(8.0, 1.0)],
+        ];
+        let part = Placement {
+            part: 3,
+            sheet: 1,
+            x: 0.0,
+            y: 0.0,
+            rotation: 90.0,
+            width: 2.0,
+            height: 1.0,
+        };
+        let placed = place_on_nfp(&nfp, part, None).unwrap().unwrap();
+        assert_eq!((placed.x, placed.y), (0.0, 2.0));
+        assert_eq!((placed.part, placed.rotation), (3, 90.0));
+
+        let placed = place_on_nfp(&nfp, part, Some(&mut Rightmost)).unwrap().unwrap();
+        assert_eq!((placed.x, placed.y), (8.0, 1.0));
+        assert_eq!(place_on_nfp(&[], part, None), Ok(None));
+    }
+}
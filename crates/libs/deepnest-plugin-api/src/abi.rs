@@ -0,0 +1,227 @@
+//! C ABI between hosts and plugin libraries.
+//!
+//! A plugin library exports [`ENTRY_SYMBOL`], which returns a static
+//! [`PluginDescriptor`]. Data is passed as byte buffers: files as they are, parts and
+//! nests as JSON. Buffers returned by a plugin are allocated by the plugin and must
+//! be released with its `free` function, since host and plugin may use different
+//! allocators.
+
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{FormatPlugin, Nest, PluginError};
+
+/// Version of the descriptor layout and of the JSON schema of parts and nests.
+pub const ABI_VERSION: u32 = 1;
+
+/// Name of the function exported by plugin libraries.
+pub const ENTRY_SYMBOL: &str = "deepnest_plugin_entry";
+
+/// Signature of the function exported as [`ENTRY_SYMBOL`].
+pub type EntryFn = unsafe extern "C" fn() -> *const PluginDescriptor;
+
+/// A byte buffer allocated by a plugin.
+#[repr(C)]
+pub struct PluginBuffer {
+  pub ptr: *mut u8,
+  pub len: usize,
+  pub capacity: usize,
+}
+
+impl PluginBuffer {
+  pub fn from_vec(data: Vec<u8>) -> PluginBuffer {
+    let mut data = std::mem::ManuallyDrop::new(data);
+    PluginBuffer {
+      ptr: data.as_mut_ptr(),
+      len: data.len(),
+      capacity: data.capacity(),
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The buffer must come from [`PluginBuffer::from_vec`] and must not have been freed.
+  pub unsafe fn as_slice(&self) -> &[u8] {
+    if self.ptr.is_null() {
+      &[]
+    } else {
+      std::slice::from_raw_parts(self.ptr, self.len)
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The buffer must come from [`PluginBuffer::from_vec`] in the same library.
+  pub unsafe fn into_vec(self) -> Vec<u8> {
+    Vec::from_raw_parts(self.ptr, self.len, self.capacity)
+  }
+}
+
+/// Outcome of a plugin call: the output, or a JSON [`PluginError`] if `ok` is false.
+#[repr(C)]
+pub struct PluginResult {
+  pub ok: bool,
+  pub data: PluginBuffer,
+}
+
+/// Functions and metadata of a plugin. Only `abi_version` may be read before it
+/// has been checked against [`ABI_VERSION`].
+#[repr(C)]
+pub struct PluginDescriptor {
+  pub abi_version: u32,
+  /// nul-terminated format id
+  pub format_id: *const c_char,
+  /// nul-terminated, comma-separated file extensions
+  pub extensions: *const c_char,
+  /// reads the bytes of a file and returns the parts as JSON
+  pub import: unsafe extern "C" fn(data: *const u8, len: usize) -> PluginResult,
+  /// reads a JSON nest and returns the bytes of the file
+  pub export: unsafe extern "C" fn(data: *const u8, len: usize) -> PluginResult,
+  /// releases a buffer returned by `import` or `export`
+  pub free: unsafe extern "C" fn(buffer: PluginBuffer),
+}
+
+/// The descriptor of a plugin together with the strings it points to.
+pub struct PluginEntry {
+  descriptor: PluginDescriptor,
+  _format_id: CString,
+  _extensions: CString,
+}
+
+// The descriptor only points to the strings owned by the entry and to functions.
+unsafe impl Send for PluginEntry {}
+unsafe impl Sync for PluginEntry {}
+
+impl PluginEntry {
+  pub fn new(
+    plugin: &dyn FormatPlugin,
+    import: unsafe extern "C" fn(*const u8, usize) -> PluginResult,
+    export: unsafe extern "C" fn(*const u8, usize) -> PluginResult,
+  ) -> PluginEntry {
+    let format_id = CString::new(plugin.format_id()).unwrap_or_default();
+    let extensions = CString::new(plugin.extensions().join(",")).unwrap_or_default();
+    PluginEntry {
+      descriptor: PluginDescriptor {
+        abi_version: ABI_VERSION,
+        format_id: format_id.as_ptr(),
+        extensions: extensions.as_ptr(),
+        import,
+        export,
+        free: free_buffer,
+      },
+      _format_id: format_id,
+      _extensions: extensions,
+    }
+  }
+
+  pub fn descriptor(&self) -> *const PluginDescriptor {
+    &self.descriptor
+  }
+}
+
+/// Releases a buffer allocated by this library.
+///
+/// # Safety
+///
+/// The buffer must come from [`PluginBuffer::from_vec`] in this library.
+pub unsafe extern "C" fn free_buffer(buffer: PluginBuffer) {
+  drop(buffer.into_vec());
+}
+
+/// Implements `PluginDescriptor::import` with `plugin`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, or be null with a `len` of 0.
+pub unsafe fn call_import(plugin: &dyn FormatPlugin, data: *const u8, len: usize) -> PluginResult {
+  let data = input(data, len);
+  respond(catch_unwind(AssertUnwindSafe(|| {
+    plugin.import(data).and_then(|parts| to_json(&parts))
+  })))
+}
+
+/// Implements `PluginDescriptor::export` with `plugin`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, or be null with a `len` of 0.
+pub unsafe fn call_export(plugin: &dyn FormatPlugin, data: *const u8, len: usize) -> PluginResult {
+  let data = input(data, len);
+  respond(catch_unwind(AssertUnwindSafe(|| {
+    let nest: Nest = from_json(data)?;
+    plugin.export(&nest)
+  })))
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> &'a [u8] {
+  if data.is_null() {
+    &[]
+  } else {
+    std::slice::from_raw_parts(data, len)
+  }
+}
+
+fn respond(result: std::thread::Result<Result<Vec<u8>, PluginError>>) -> PluginResult {
+  let result = result.unwrap_or_else(|payload| {
+    let message = if let Some(s) = payload.downcast_ref::<String>() {
+      s.clone()
+    } else if let Some(s) = payload.downcast_ref::<&str>() {
+      s.to_string()
+    } else {
+      "unknown panic".to_string()
+    };
+    Err(PluginError::Internal(message))
+  });
+  match result {
+    Ok(data) => PluginResult {
+      ok: true,
+      data: PluginBuffer::from_vec(data),
+    },
+    Err(err) => PluginResult {
+      ok: false,
+      data: PluginBuffer::from_vec(serde_json::to_vec(&err).unwrap_or_default()),
+    },
+  }
+}
+
+pub(crate) fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, PluginError> {
+  serde_json::to_vec(value).map_err(|err| PluginError::Internal(err.to_string()))
+}
+
+pub(crate) fn from_json<T: DeserializeOwned>(data: &[u8]) -> Result<T, PluginError> {
+  serde_json::from_slice(data).map_err(|err| PluginError::Internal(err.to_string()))
+}
+
+/// Exports a [`FormatPlugin`] from a `cdylib` as [`ENTRY_SYMBOL`].
+///
+/// ```ignore
+/// deepnest_plugin_api::declare_plugin!(SvgPlugin::default());
+/// ```
+#[macro_export]
+macro_rules! declare_plugin {
+  ($constructor:expr) => {
+    #[no_mangle]
+    pub extern "C" fn deepnest_plugin_entry() -> *const $crate::abi::PluginDescriptor {
+      fn plugin() -> &'static dyn $crate::FormatPlugin {
+        static PLUGIN: ::std::sync::OnceLock<::std::boxed::Box<dyn $crate::FormatPlugin>> =
+          ::std::sync::OnceLock::new();
+        PLUGIN
+          .get_or_init(|| ::std::boxed::Box::new($constructor))
+          .as_ref()
+      }
+      unsafe extern "C" fn import(data: *const u8, len: usize) -> $crate::abi::PluginResult {
+        $crate::abi::call_import(plugin(), data, len)
+      }
+      unsafe extern "C" fn export(data: *const u8, len: usize) -> $crate::abi::PluginResult {
+        $crate::abi::call_export(plugin(), data, len)
+      }
+      static ENTRY: ::std::sync::OnceLock<$crate::abi::PluginEntry> = ::std::sync::OnceLock::new();
+      ENTRY
+        .get_or_init(|| $crate::abi::PluginEntry::new(plugin(), import, export))
+        .descriptor()
+    }
+  };
+}
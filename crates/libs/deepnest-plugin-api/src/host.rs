@@ -0,0 +1,317 @@
+//! Loading of plugin libraries.
+
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+
+use libloading::Library;
+use object::Object;
+
+use crate::abi::{
+  from_json, to_json, EntryFn, PluginDescriptor, PluginResult, ABI_VERSION, ENTRY_SYMBOL,
+};
+use crate::{FormatPlugin, Nest, Part, PluginError};
+
+type CallFn = unsafe extern "C" fn(*const u8, usize) -> PluginResult;
+
+/// A plugin behind the C ABI, usually loaded from a dynamic library.
+pub struct DynamicPlugin {
+  descriptor: *const PluginDescriptor,
+  format_id: String,
+  extensions: Vec<String>,
+  // Declared last, so the library is unloaded after everything pointing into it.
+  _library: Option<Library>,
+}
+
+// The descriptor is immutable, and `FormatPlugin` implementations are `Send + Sync`.
+unsafe impl Send for DynamicPlugin {}
+unsafe impl Sync for DynamicPlugin {}
+
+impl DynamicPlugin {
+  /// Loads the plugin exported by the dynamic library at `path`.
+  pub fn load(path: &Path) -> Result<DynamicPlugin, PluginError> {
+    // The messages of libloading already name the library.
+    let load_error = |err: libloading::Error| PluginError::Load(err.to_string());
+    // Loading runs the initializers of the library; plugins are trusted code.
+    let library = unsafe { Library::new(path) }.map_err(load_error)?;
+    let entry: EntryFn =
+      *unsafe { library.get::<EntryFn>(ENTRY_SYMBOL.as_bytes()) }.map_err(load_error)?;
+    unsafe { DynamicPlugin::new(entry, Some(library)) }
+  }
+
+  /// Wraps the entry function of a plugin linked into the host.
+  ///
+  /// # Safety
+  ///
+  /// `entry` must be generated by `declare_plugin!` or follow the same contract.
+  pub unsafe fn from_entry(entry: EntryFn) -> Result<DynamicPlugin, PluginError> {
+    DynamicPlugin::new(entry, None)
+  }
+
+  unsafe fn new(entry: EntryFn, library: Option<Library>) -> Result<DynamicPlugin, PluginError> {
+    let descriptor = entry();
+    if descriptor.is_null() {
+      return Err(PluginError::Load(
+        "the plugin returned no descriptor".to_string(),
+      ));
+    }
+    let found = (*descriptor).abi_version;
+    if found != ABI_VERSION {
+      return Err(PluginError::Abi {
+        expected: ABI_VERSION,
+        found,
+      });
+    }
+    let text = |ptr: *const std::ffi::c_char| {
+      if ptr.is_null() {
+        String::new()
+      } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+      }
+    };
+    let extensions = text((*descriptor).extensions)
+      .split(',')
+      .filter(|extension| !extension.is_empty())
+      .map(str::to_lowercase)
+      .collect();
+    Ok(DynamicPlugin {
+      descriptor,
+      format_id: text((*descriptor).format_id),
+      extensions,
+      _library: library,
+    })
+  }
+
+  fn call(&self, function: CallFn, input: &[u8]) -> Result<Vec<u8>, PluginError> {
+    let descriptor = unsafe { &*self.descriptor };
+    let result = unsafe { function(input.as_ptr(), input.len()) };
+    let data = unsafe { result.data.as_slice() }.to_vec();
+    unsafe { (descriptor.free)(result.data) };
+    if result.ok {
+      Ok(data)
+    } else {
+      Err(
+        from_json(&data)
+          .unwrap_or_else(|_| PluginError::Internal(String::from_utf8_lossy(&data).into_owned())),
+      )
+    }
+  }
+}
+
+impl FormatPlugin for DynamicPlugin {
+  fn format_id(&self) -> &str {
+    &self.format_id
+  }
+
+  fn extensions(&self) -> Vec<String> {
+    self.extensions.clone()
+  }
+
+  fn import(&self, data: &[u8]) -> Result<Vec<Part>, PluginError> {
+    let import = unsafe { (*self.descriptor).import };
+    from_json(&self.call(import, data)?)
+  }
+
+  fn export(&self, nest: &Nest) -> Result<Vec<u8>, PluginError> {
+    let export = unsafe { (*self.descriptor).export };
+    self.call(export, &to_json(nest)?)
+  }
+}
+
+/// Whether the library at `path` exports `ENTRY_SYMBOL`. Only the file is read, so the
+/// initializers of other libraries never run.
+fn exports_entry(path: &Path) -> bool {
+  let Ok(data) = std::fs::read(path) else {
+    return false;
+  };
+  let Ok(exports) = object::File::parse(&*data).and_then(|file| file.exports()) else {
+    return false;
+  };
+  exports.iter().any(|export| {
+    // Mach-O prefixes C symbols with an underscore.
+    let name = export.name();
+    name == ENTRY_SYMBOL.as_bytes() || name.strip_prefix(b"_") == Some(ENTRY_SYMBOL.as_bytes())
+  })
+}
+
+/// The formats known to a host.
+#[derive(Default)]
+pub struct PluginRegistry {
+  plugins: Vec<Box<dyn FormatPlugin>>,
+}
+
+impl PluginRegistry {
+  pub fn new() -> PluginRegistry {
+    PluginRegistry::default()
+  }
+
+  /// Adds a plugin, replacing the one with the same format id.
+  pub fn register(&mut self, plugin: Box<dyn FormatPlugin>) {
+    self
+      .plugins
+      .retain(|registered| registered.format_id() != plugin.format_id());
+    self.plugins.push(plugin);
+  }
+
+  /// Loads every plugin library in `dir`, skipping the dynamic libraries that do not
+  /// export `ENTRY_SYMBOL` without loading them. The plugins that could not be loaded
+  /// are returned with their errors, so one broken plugin does not hide the others.
+  pub fn load_dir(&mut self, dir: &Path) -> Result<Vec<(PathBuf, PluginError)>, PluginError> {
+    let entries = std::fs::read_dir(dir)
+      .map_err(|err| PluginError::Load(format!("{}: {}", dir.display(), err)))?;
+    let mut paths: Vec<PathBuf> = entries
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| {
+        path.is_file()
+          && path.extension().and_then(|ext| ext.to_str()) == Some(std::env::consts::DLL_EXTENSION)
+          && exports_entry(path)
+      })
+      .collect();
+    paths.sort();
+
+    let mut failed = vec![];
+    for path in paths {
+      match DynamicPlugin::load(&path) {
+        Ok(plugin) => self.register(Box::new(plugin)),
+        Err(err) => failed.push((path, err)),
+      }
+    }
+    Ok(failed)
+  }
+
+  pub fn plugins(&self) -> impl Iterator<Item = &dyn FormatPlugin> {
+    self.plugins.iter().map(|plugin| plugin.as_ref())
+  }
+
+  pub fn get(&self, format_id: &str) -> Option<&dyn FormatPlugin> {
+    self
+      .plugins()
+      .find(|plugin| plugin.format_id() == format_id)
+  }
+
+  /// The plugin for a file extension, with or without the leading dot.
+  pub fn for_extension(&self, extension: &str) -> Option<&dyn FormatPlugin> {
+    let extension = extension.trim_start_matches('.').to_lowercase();
+    self
+      .plugins()
+      .find(|plugin| plugin.extensions().contains(&extension))
+  }
+
+  pub fn for_path(&self, path: &Path) -> Option<&dyn FormatPlugin> {
+    self.for_extension(path.extension()?.to_str()?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Placement, Sheet};
+  use deepnest_types::types::{Point, Polygon};
+
+  /// Reads "x y" lines as the points of a single part and writes the placements.
+  struct PointsPlugin;
+
+  impl FormatPlugin for PointsPlugin {
+    fn format_id(&self) -> &str {
+      "points"
+    }
+
+    fn extensions(&self) -> Vec<String> {
+      vec!["pts".to_string(), "txt".to_string()]
+    }
+
+    fn import(&self, data: &[u8]) -> Result<Vec<Part>, PluginError> {
+      let text = std::str::from_utf8(data).map_err(|err| PluginError::Format(err.to_string()))?;
+      let points = text
+        .lines()
+        .map(|line| {
+          let mut numbers = line.split_whitespace().map(str::parse::<f64>);
+          match (numbers.next(), numbers.next()) {
+            (Some(Ok(x)), Some(Ok(y))) => Ok(Point { x, y }),
+            _ => Err(PluginError::Format(format!("invalid line \"{}\"", line))),
+          }
+        })
+        .collect::<Result<Vec<Point>, PluginError>>()?;
+      Ok(vec![Part {
+        id: None,
+        polygon: Polygon {
+          points,
+          children: None,
+          offsetx: None,
+          offsety: None,
+        },
+      }])
+    }
+
+    fn export(&self, nest: &Nest) -> Result<Vec<u8>, PluginError> {
+      if nest.sheets.is_empty() {
+        panic!("no sheets");
+      }
+      let placements = nest.sheets.iter().map(|sheet| sheet.placements.len());
+      Ok(format!("{:?}", placements.collect::<Vec<_>>()).into_bytes())
+    }
+  }
+
+  crate::declare_plugin!(PointsPlugin);
+
+  #[test]
+  fn test_plugin_round_trip_through_the_abi() {
+    let plugin = unsafe { DynamicPlugin::from_entry(deepnest_plugin_entry) }.unwrap();
+    let mut registry = PluginRegistry::new();
+    registry.register(Box::new(plugin));
+    let plugin = registry.for_path(Path::new("part.PTS")).unwrap();
+    assert_eq!(plugin.format_id(), "points");
+
+    let parts = plugin.import(b"0 0\n10 0\n10 5").unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].polygon.points[2], Point { x: 10.0, y: 5.0 });
+    // Errors keep their kind across the boundary.
+    assert!(matches!(
+      plugin.import(b"0 zero"),
+      Err(PluginError::Format(_))
+    ));
+
+    let mut nest = Nest {
+      parts,
+      sheets: vec![Sheet {
+        width: 100.0,
+        height: 50.0,
+        placements: vec![Placement {
+          part: 0,
+          x: 1.0,
+          y: 2.0,
+          rotation: 90.0,
+        }],
+      }],
+    };
+    assert_eq!(plugin.export(&nest).unwrap(), b"[1]");
+    // A panic in the plugin becomes an error instead of unwinding into the host.
+    nest.sheets.clear();
+    assert_eq!(
+      plugin.export(&nest),
+      Err(PluginError::Internal("no sheets".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_load_invalid_library() {
+    assert!(matches!(
+      DynamicPlugin::load(Path::new("/nonexistent/libplugin.so")),
+      Err(PluginError::Load(_))
+    ));
+    let mut registry = PluginRegistry::new();
+    assert!(registry.load_dir(Path::new("/nonexistent")).is_err());
+  }
+
+  #[test]
+  fn test_load_dir_skips_other_libraries() {
+    let dir = std::env::temp_dir().join(format!("deepnest-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let library = dir.join(format!("libother.{}", std::env::consts::DLL_EXTENSION));
+    std::fs::write(&library, b"not a plugin").unwrap();
+    let mut registry = PluginRegistry::new();
+    let failed = registry.load_dir(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(failed.unwrap(), vec![]);
+    assert_eq!(registry.plugins().count(), 0);
+  }
+}
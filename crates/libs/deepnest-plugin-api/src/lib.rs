@@ -0,0 +1,101 @@
+//! Plugin interface for import and export formats.
+//!
+//! A format plugin implements [`FormatPlugin`] and is compiled as a `cdylib` with
+//! [`declare_plugin!`], which exports the C ABI of the [`abi`] module. Hosts load such
+//! libraries with [`DynamicPlugin`] or collect them in a [`PluginRegistry`], so new
+//! formats can be added without rebuilding the host.
+//!
+//! Parts and nests cross the library boundary as JSON, so plugins and hosts built
+//! with different compilers stay compatible as long as they agree on
+//! [`abi::ABI_VERSION`].
+
+pub mod abi;
+mod host;
+
+use std::fmt;
+
+use deepnest_types::types::Polygon;
+use serde::{Deserialize, Serialize};
+
+pub use host::{DynamicPlugin, PluginRegistry};
+
+/// A part read from a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Part {
+  /// name or id of the part in the source file, if it has one
+  pub id: Option<String>,
+  /// outer contour with the holes as `children`
+  pub polygon: Polygon,
+}
+
+/// A part placed on a sheet: rotated by `rotation` degrees around its origin, then
+/// moved to (`x`, `y`), as `Polygon::rotate` followed by `Polygon::translate`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+  /// index into `Nest::parts`
+  pub part: usize,
+  pub x: f64,
+  pub y: f64,
+  pub rotation: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sheet {
+  pub width: f64,
+  pub height: f64,
+  pub placements: Vec<Placement>,
+}
+
+/// A nesting result to export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nest {
+  pub parts: Vec<Part>,
+  pub sheets: Vec<Sheet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PluginError {
+  /// The library could not be loaded or does not export a plugin.
+  Load(String),
+  /// The plugin was built for another ABI version.
+  Abi { expected: u32, found: u32 },
+  /// The input is not a valid file of the format.
+  Format(String),
+  /// The plugin cannot perform the operation, e.g. an import-only format.
+  Unsupported(String),
+  /// The plugin panicked or returned malformed data.
+  Internal(String),
+}
+
+impl fmt::Display for PluginError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PluginError::Load(message) => write!(f, "Failed to load plugin: {}", message),
+      PluginError::Abi { expected, found } => write!(
+        f,
+        "Plugin ABI version {} does not match the host version {}",
+        found, expected
+      ),
+      PluginError::Format(message) => write!(f, "Invalid input: {}", message),
+      PluginError::Unsupported(message) => write!(f, "Unsupported operation: {}", message),
+      PluginError::Internal(message) => write!(f, "Internal plugin error: {}", message),
+    }
+  }
+}
+
+impl std::error::Error for PluginError {}
+
+/// An import and export format.
+pub trait FormatPlugin: Send + Sync {
+  /// Unique id of the format, e.g. "svg".
+  fn format_id(&self) -> &str;
+
+  /// File extensions handled by the plugin, lowercase and without the dot.
+  fn extensions(&self) -> Vec<String>;
+
+  /// Reads the parts of a file.
+  fn import(&self, data: &[u8]) -> Result<Vec<Part>, PluginError>;
+
+  /// Writes a nesting result as a file.
+  fn export(&self, nest: &Nest) -> Result<Vec<u8>, PluginError>;
+}
@@ -0,0 +1,299 @@
+use crate::types::point::Point;
+use crate::types::polygon::{sin_cos_degrees, MirrorAxis};
+
+/// A segment of a `CurvePath`, starting at the end of the previous segment.
+///
+/// Curves are kept exact so placements can be applied to the original geometry;
+/// the linearized polygon is only needed for NFP and collision tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+  Line {
+    to: Point,
+  },
+  /// Elliptical arc with the parameters of the SVG `A` command.
+  Arc {
+    to: Point,
+    rx: f64,
+    ry: f64,
+    /// rotation of the x axis of the ellipse in degrees
+    x_axis_rotation: f64,
+    large_arc: bool,
+    /// counter-clockwise in math coordinates (y up), like `Arc::linearize`
+    sweep: bool,
+  },
+  Cubic {
+    control1: Point,
+    control2: Point,
+    to: Point,
+  },
+}
+
+impl Segment {
+  /// End point of the segment.
+  pub fn to(&self) -> Point {
+    match self {
+      Segment::Line { to } | Segment::Arc { to, .. } | Segment::Cubic { to, .. } => *to,
+    }
+  }
+
+  fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> Segment {
+    match *self {
+      Segment::Line { to } => Segment::Line { to: f(to) },
+      Segment::Arc {
+        to,
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+      } => Segment::Arc {
+        to: f(to),
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+      },
+      Segment::Cubic {
+        control1,
+        control2,
+        to,
+      } => Segment::Cubic {
+        control1: f(control1),
+        control2: f(control2),
+        to: f(to),
+      },
+    }
+  }
+}
+
+/// A path of line, arc and cubic segments.
+/// A closed path ends with an implicit line back to `start` unless its last segment ends there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurvePath {
+  pub start: Point,
+  pub segments: Vec<Segment>,
+  pub closed: bool,
+}
+
+/// A part with curved contours: the outer contour and its holes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurvePart {
+  pub outer: CurvePath,
+  pub holes: Vec<CurvePath>,
+}
+
+impl CurvePath {
+  /// A path of straight segments through `points`.
+  pub fn from_points(points: &[Point], closed: bool) -> CurvePath {
+    let start = points.first().copied().unwrap_or(Point { x: 0.0, y: 0.0 });
+    CurvePath {
+      start,
+      segments: points
+        .iter()
+        .skip(1)
+        .map(|to| Segment::Line { to: *to })
+        .collect(),
+      closed,
+    }
+  }
+
+  /// The start point followed by the end point of every segment.
+  pub fn vertices(&self) -> Vec<Point> {
+    std::iter::once(self.start)
+      .chain(self.segments.iter().map(Segment::to))
+      .collect()
+  }
+
+  /// Rotates the path by `degrees` around the origin.
+  /// Arcs keep their flags and turn their x axis with the path.
+  pub fn rotate(&self, degrees: f64) -> CurvePath {
+    let (sin, cos) = sin_cos_degrees(degrees);
+    let mut rotated = self.map_points(|p| Point {
+      x: p.x * cos - p.y * sin,
+      y: p.x * sin + p.y * cos,
+    });
+    for segment in rotated.segments.iter_mut() {
+      if let Segment::Arc {
+        x_axis_rotation, ..
+      } = segment
+      {
+        *x_axis_rotation = (*x_axis_rotation + degrees).rem_euclid(360.0);
+      }
+    }
+    rotated
+  }
+
+  /// Moves the path by (`dx`, `dy`).
+  pub fn translate(&self, dx: f64, dy: f64) -> CurvePath {
+    self.map_points(|p| Point {
+      x: p.x + dx,
+      y: p.y + dy,
+    })
+  }
+
+  /// Mirrors the path across the given axis through the origin.
+  /// Like `Polygon::mirror`, the path is reversed so its winding direction is preserved.
+  pub fn mirror(&self, axis: MirrorAxis) -> CurvePath {
+    let mut mirrored = self.map_points(|p| match axis {
+      MirrorAxis::Horizontal => Point { x: -p.x, y: p.y },
+      MirrorAxis::Vertical => Point { x: p.x, y: -p.y },
+    });
+    for segment in mirrored.segments.iter_mut() {
+      if let Segment::Arc {
+        x_axis_rotation,
+        sweep,
+        ..
+      } = segment
+      {
+        *x_axis_rotation = (-*x_axis_rotation).rem_euclid(360.0);
+        *sweep = !*sweep;
+      }
+    }
+    mirrored.reversed()
+  }
+
+  /// The same geometry traversed in the opposite direction.
+  pub fn reversed(&self) -> CurvePath {
+    let vertices = self.vertices();
+    let segments = self
+      .segments
+      .iter()
+      .enumerate()
+      .rev()
+      .map(|(i, segment)| {
+        let to = vertices[i];
+        match *segment {
+          Segment::Line { .. } => Segment::Line { to },
+          Segment::Arc {
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            ..
+          } => Segment::Arc {
+            to,
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep: !sweep,
+          },
+          Segment::Cubic {
+            control1, control2, ..
+          } => Segment::Cubic {
+            control1: control2,
+            control2: control1,
+            to,
+          },
+        }
+      })
+      .collect();
+    CurvePath {
+      start: vertices[vertices.len() - 1],
+      segments,
+      closed: self.closed,
+    }
+  }
+
+  fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> CurvePath {
+    CurvePath {
+      start: f(self.start),
+      segments: self.segments.iter().map(|s| s.map_points(&f)).collect(),
+      closed: self.closed,
+    }
+  }
+}
+
+impl CurvePart {
+  pub fn rotate(&self, degrees: f64) -> CurvePart {
+    self.map_paths(|path| path.rotate(degrees))
+  }
+
+  pub fn translate(&self, dx: f64, dy: f64) -> CurvePart {
+    self.map_paths(|path| path.translate(dx, dy))
+  }
+
+  pub fn mirror(&self, axis: MirrorAxis) -> CurvePart {
+    self.map_paths(|path| path.mirror(axis))
+  }
+
+  /// Applies a placement of the nesting result: rotation by `degrees` around the
+  /// part origin followed by a translation to (`x`, `y`), as for `Polygon`.
+  pub fn place(&self, degrees: f64, x: f64, y: f64) -> CurvePart {
+    self.rotate(degrees).translate(x, y)
+  }
+
+  fn map_paths<F: Fn(&CurvePath) -> CurvePath>(&self, f: F) -> CurvePart {
+    CurvePart {
+      outer: f(&self.outer),
+      holes: self.holes.iter().map(f).collect(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A 2x1 rectangle whose right side is a half circle.
+  fn slot() -> CurvePath {
+    CurvePath {
+      start: Point { x: 0.0, y: 0.0 },
+      segments: vec![
+        Segment::Line {
+          to: Point { x: 2.0, y: 0.0 },
+        },
+        Segment::Arc {
+          to: Point { x: 2.0, y: 1.0 },
+          rx: 0.5,
+          ry: 0.5,
+          x_axis_rotation: 0.0,
+          large_arc: false,
+          sweep: true,
+        },
+        Segment::Cubic {
+          control1: Point { x: 1.5, y: 1.5 },
+          control2: Point { x: 0.5, y: 1.5 },
+          to: Point { x: 0.0, y: 1.0 },
+        },
+      ],
+      closed: true,
+    }
+  }
+
+  #[test]
+  fn test_transforms_keep_curves() {
+    let rotated = slot().rotate(90.0).translate(10.0, 0.0);
+    assert_eq!(rotated.start, Point { x: 10.0, y: 0.0 });
+    assert_eq!(
+      rotated.segments[1],
+      Segment::Arc {
+        to: Point { x: 9.0, y: 2.0 },
+        rx: 0.5,
+        ry: 0.5,
+        x_axis_rotation: 90.0,
+        large_arc: false,
+        sweep: true,
+      }
+    );
+
+    // Mirroring flips the arc direction, and the reversal flips it back.
+    let mirrored = slot().mirror(MirrorAxis::Horizontal);
+    assert_eq!(mirrored.start, Point { x: 0.0, y: 1.0 });
+    assert!(matches!(
+      mirrored.segments[1],
+      Segment::Arc { to, sweep: true, .. } if to == Point { x: -2.0, y: 0.0 }
+    ));
+    assert!(matches!(
+      mirrored.segments[0],
+      Segment::Cubic { control1, .. } if control1 == Point { x: -0.5, y: 1.5 }
+    ));
+  }
+
+  #[test]
+  fn test_reversed_twice_is_identity() {
+    assert_eq!(slot().reversed().reversed(), slot());
+  }
+}
@@ -1,9 +1,13 @@
+pub mod curve;
 pub mod point;
 pub mod polygon;
 pub mod vector;
 pub mod rect;
+pub mod transform;
 
+pub use curve::*;
 pub use point::*;
 pub use polygon::*;
 pub use vector::*;
-pub use rect::*;
\ No newline at end of file
+pub use rect::*;
+pub use transform::*;
\ No newline at end of file
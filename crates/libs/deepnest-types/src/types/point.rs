@@ -2,6 +2,7 @@ use derive_more::{From, Into};
 
 /// Point
 #[cfg_attr(feature = "node", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, From, Into)]
 pub struct Point {
   pub x: f64,
@@ -4,6 +4,7 @@ use crate::types::point::Point;
 
 /// Polygon
 #[cfg_attr(feature = "node", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, From, Into)]
 pub struct Polygon {
   pub points: Vec<Point>,
@@ -29,3 +30,194 @@ pub fn polygon_area(polygon: Polygon) -> f64 {
   }
   0.5 * area
 }
+
+/// Axis used to mirror a polygon
+#[cfg_attr(feature = "node", napi)]
+#[cfg_attr(not(feature = "node"), derive(Clone, Copy))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MirrorAxis {
+  /// Mirror across the y axis (x becomes -x)
+  Horizontal,
+  /// Mirror across the x axis (y becomes -y)
+  Vertical,
+}
+
+impl Polygon {
+  /// Rotates the outer contour and all holes by `degrees` around the local origin.
+  /// The offsets are kept, so the polygon rotates around its placement position.
+  pub fn rotate(&self, degrees: f64) -> Polygon {
+    let (sin, cos) = sin_cos_degrees(degrees);
+    self.map_points(|p| Point {
+      x: p.x * cos - p.y * sin,
+      y: p.x * sin + p.y * cos,
+    })
+  }
+
+  /// Mirrors the outer contour and all holes across the given axis through the local origin.
+  /// The point order of every ring is reversed so the winding direction is preserved.
+  pub fn mirror(&self, axis: MirrorAxis) -> Polygon {
+    let mut mirrored = self.map_points(|p| match axis {
+      MirrorAxis::Horizontal => Point { x: -p.x, y: p.y },
+      MirrorAxis::Vertical => Point { x: p.x, y: -p.y },
+    });
+    mirrored.points.reverse();
+    if let Some(children) = mirrored.children.as_mut() {
+      for child in children.iter_mut() {
+        child.reverse();
+      }
+    }
+    mirrored
+  }
+
+  /// Moves the polygon by (`dx`, `dy`).
+  /// The points stay untouched, only `offsetx`/`offsety` are updated.
+  pub fn translate(&self, dx: f64, dy: f64) -> Polygon {
+    Polygon {
+      points: self.points.clone(),
+      children: self.children.clone(),
+      offsetx: Some(self.offsetx.unwrap_or(0.0) + dx),
+      offsety: Some(self.offsety.unwrap_or(0.0) + dy),
+    }
+  }
+
+  /// Shifts the points so the bounding box of the outer contour starts at (0, 0).
+  /// The shift is added to `offsetx`/`offsety`, so the placed position does not change.
+  pub fn normalize_to_origin(&self) -> Polygon {
+    let Some(first) = self.points.first() else {
+      return self.clone();
+    };
+    let (min_x, min_y) = self
+      .points
+      .iter()
+      .fold((first.x, first.y), |(x, y), p| (x.min(p.x), y.min(p.y)));
+    let mut normalized = self.map_points(|p| Point {
+      x: p.x - min_x,
+      y: p.y - min_y,
+    });
+    normalized.offsetx = Some(self.offsetx.unwrap_or(0.0) + min_x);
+    normalized.offsety = Some(self.offsety.unwrap_or(0.0) + min_y);
+    normalized
+  }
+
+  /// Precomputes the polygon for all `count` allowed rotations (`i * 360 / count` degrees).
+  /// Entry `i` of the result is the polygon rotated by `i * 360 / count` degrees.
+  pub fn rotations(&self, count: u32) -> Vec<Polygon> {
+    let step = 360.0 / count.max(1) as f64;
+    (0..count.max(1))
+      .map(|i| self.rotate(i as f64 * step))
+      .collect()
+  }
+
+  fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> Polygon {
+    Polygon {
+      points: self.points.iter().map(|p| f(*p)).collect(),
+      children: self.children.as_ref().map(|children| {
+        children
+          .iter()
+          .map(|child| child.iter().map(|p| f(*p)).collect())
+          .collect()
+      }),
+      offsetx: self.offsetx,
+      offsety: self.offsety,
+    }
+  }
+}
+
+/// Sine and cosine of an angle in degrees, exact for quarter turns.
+pub fn sin_cos_degrees(degrees: f64) -> (f64, f64) {
+  let normalized = degrees.rem_euclid(360.0);
+  if normalized == 0.0 {
+    (0.0, 1.0)
+  } else if normalized == 90.0 {
+    (1.0, 0.0)
+  } else if normalized == 180.0 {
+    (0.0, -1.0)
+  } else if normalized == 270.0 {
+    (-1.0, 0.0)
+  } else {
+    normalized.to_radians().sin_cos()
+  }
+}
+
+#[cfg_attr(feature = "node", napi)]
+pub fn rotate_polygon(polygon: Polygon, degrees: f64) -> Polygon {
+  polygon.rotate(degrees)
+}
+
+#[cfg_attr(feature = "node", napi)]
+pub fn mirror_polygon(polygon: Polygon, axis: MirrorAxis) -> Polygon {
+  polygon.mirror(axis)
+}
+
+#[cfg_attr(feature = "node", napi)]
+pub fn translate_polygon(polygon: Polygon, dx: f64, dy: f64) -> Polygon {
+  polygon.translate(dx, dy)
+}
+
+#[cfg_attr(feature = "node", napi)]
+pub fn normalize_polygon_to_origin(polygon: Polygon) -> Polygon {
+  polygon.normalize_to_origin()
+}
+
+#[cfg_attr(feature = "node", napi)]
+pub fn polygon_rotations(polygon: Polygon, count: u32) -> Vec<Polygon> {
+  polygon.rotations(count)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square_with_hole() -> Polygon {
+    Polygon {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 10.0, y: 0.0 },
+        Point { x: 10.0, y: 10.0 },
+        Point { x: 0.0, y: 10.0 },
+      ],
+      children: Some(vec![vec![
+        Point { x: 2.0, y: 2.0 },
+        Point { x: 2.0, y: 4.0 },
+        Point { x: 4.0, y: 4.0 },
+        Point { x: 4.0, y: 2.0 },
+      ]]),
+      offsetx: None,
+      offsety: None,
+    }
+  }
+
+  #[test]
+  fn test_rotate_keeps_holes() {
+    let rotated = square_with_hole().rotate(90.0);
+    assert_eq!(rotated.points[1], Point { x: 0.0, y: 10.0 });
+    assert_eq!(rotated.children.unwrap()[0][0], Point { x: -2.0, y: 2.0 });
+  }
+
+  #[test]
+  fn test_mirror_preserves_winding() {
+    let polygon = square_with_hole();
+    let mirrored = polygon.mirror(MirrorAxis::Horizontal);
+    assert_eq!(polygon_area(polygon), polygon_area(mirrored.clone()));
+    assert!(mirrored.points.iter().all(|p| p.x <= 0.0));
+  }
+
+  #[test]
+  fn test_normalize_to_origin_keeps_position() {
+    let normalized = square_with_hole()
+      .translate(5.0, 5.0)
+      .rotate(180.0)
+      .normalize_to_origin();
+    assert!(normalized.points.iter().all(|p| p.x >= 0.0 && p.y >= 0.0));
+    assert_eq!(normalized.offsetx, Some(-5.0));
+    assert_eq!(normalized.offsety, Some(-5.0));
+    assert_eq!(normalized.children.unwrap()[0][0], Point { x: 8.0, y: 8.0 });
+  }
+
+  #[test]
+  fn test_rotations() {
+    let rotations = square_with_hole().rotations(4);
+    assert_eq!(rotations.len(), 4);
+    assert_eq!(rotations[2].points[2], Point { x: -10.0, y: -10.0 });
+  }
+}
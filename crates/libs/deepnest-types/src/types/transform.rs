@@ -0,0 +1,142 @@
+use super::{sin_cos_degrees, Point};
+
+/// An affine transform `x' = a x + c y + e`, `y' = b x + d y + f`, as the SVG
+/// `matrix(a b c d e f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+  pub a: f64,
+  pub b: f64,
+  pub c: f64,
+  pub d: f64,
+  pub e: f64,
+  pub f: f64,
+}
+
+impl Transform {
+  pub const IDENTITY: Transform = Transform {
+    a: 1.0,
+    b: 0.0,
+    c: 0.0,
+    d: 1.0,
+    e: 0.0,
+    f: 0.0,
+  };
+
+  /// Reflection about the line through (`x1`, `y1`) and (`x2`, `y2`), or `None`
+  /// when the points do not define a line.
+  pub fn mirror(x1: f64, y1: f64, x2: f64, y2: f64) -> Option<Transform> {
+    let length = (x2 - x1).hypot(y2 - y1);
+    if length == 0.0 || !length.is_finite() {
+      return None;
+    }
+    let (ux, uy) = ((x2 - x1) / length, (y2 - y1) / length);
+    let a = ux * ux - uy * uy;
+    let b = 2.0 * ux * uy;
+    // The line point stays in place.
+    Some(Transform {
+      a,
+      b,
+      c: b,
+      d: -a,
+      e: x1 - (a * x1 + b * y1),
+      f: y1 - (b * x1 - a * y1),
+    })
+  }
+
+  /// Rotation by `degrees` around (`cx`, `cy`), clockwise on screen as in SVG.
+  pub fn rotate(degrees: f64, cx: f64, cy: f64) -> Transform {
+    let (sin, cos) = sin_cos_degrees(degrees);
+    Transform {
+      a: cos,
+      b: sin,
+      c: -sin,
+      d: cos,
+      e: cx - (cos * cx - sin * cy),
+      f: cy - (sin * cx + cos * cy),
+    }
+  }
+
+  /// Scaling by `sx` and `sy` relative to the origin.
+  pub fn scale(sx: f64, sy: f64) -> Transform {
+    Transform {
+      a: sx,
+      d: sy,
+      ..Transform::IDENTITY
+    }
+  }
+
+  pub fn apply(&self, p: Point) -> Point {
+    Point {
+      x: self.a * p.x + self.c * p.y + self.e,
+      y: self.b * p.x + self.d * p.y + self.f,
+    }
+  }
+
+  /// The transform applying `inner` first and `self` second.
+  pub fn then(&self, inner: &Transform) -> Transform {
+    Transform {
+      a: self.a * inner.a + self.c * inner.b,
+      b: self.b * inner.a + self.d * inner.b,
+      c: self.a * inner.c + self.c * inner.d,
+      d: self.b * inner.c + self.d * inner.d,
+      e: self.a * inner.e + self.c * inner.f + self.e,
+      f: self.b * inner.e + self.d * inner.f + self.f,
+    }
+  }
+
+  /// The determinant of the linear part, negative for transforms that mirror.
+  pub fn determinant(&self) -> f64 {
+    self.a * self.d - self.b * self.c
+  }
+
+  pub fn is_invertible(&self) -> bool {
+    let det = self.determinant();
+    det != 0.0
+      && det.is_finite()
+      && [self.a, self.b, self.c, self.d, self.e, self.f]
+        .iter()
+        .all(|v| v.is_finite())
+  }
+
+  /// The largest factor by which the transform stretches the axes.
+  pub fn stretch(&self) -> f64 {
+    (self.a.hypot(self.b)).max(self.c.hypot(self.d))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_mirror_and_rotate_fix_their_center() {
+    let mirror = Transform::mirror(1.0, 0.0, 1.0, 5.0).unwrap();
+    assert_eq!(
+      mirror.apply(Point { x: 3.0, y: 2.0 }),
+      Point { x: -1.0, y: 2.0 }
+    );
+    assert!(mirror.determinant() < 0.0);
+    assert!(Transform::mirror(1.0, 1.0, 1.0, 1.0).is_none());
+
+    let rotate = Transform::rotate(90.0, 5.0, 5.0);
+    assert_eq!(
+      rotate.apply(Point { x: 5.0, y: 5.0 }),
+      Point { x: 5.0, y: 5.0 }
+    );
+    assert_eq!(
+      rotate.apply(Point { x: 10.0, y: 5.0 }),
+      Point { x: 5.0, y: 10.0 }
+    );
+  }
+
+  #[test]
+  fn test_then_applies_inner_first() {
+    let combined = Transform::rotate(90.0, 0.0, 0.0).then(&Transform::scale(2.0, 1.0));
+    assert_eq!(
+      combined.apply(Point { x: 1.0, y: 1.0 }),
+      Point { x: -1.0, y: 2.0 }
+    );
+    assert_eq!(combined.stretch(), 2.0);
+    assert!(!Transform::scale(0.0, 1.0).is_invertible());
+  }
+}
@@ -17,6 +17,18 @@ export interface Polygon {
   offsety?: number
 }
 export declare function polygonArea(polygon: Polygon): number
+/** Axis used to mirror a polygon */
+export const enum MirrorAxis {
+  /** Mirror across the y axis (x becomes -x) */
+  Horizontal = 0,
+  /** Mirror across the x axis (y becomes -y) */
+  Vertical = 1
+}
+export declare function rotatePolygon(polygon: Polygon, degrees: number): Polygon
+export declare function mirrorPolygon(polygon: Polygon, axis: MirrorAxis): Polygon
+export declare function translatePolygon(polygon: Polygon, dx: number, dy: number): Polygon
+export declare function normalizePolygonToOrigin(polygon: Polygon): Polygon
+export declare function polygonRotations(polygon: Polygon, count: number): Array<Polygon>
 /** Rect used for polygon bounds */
 export interface Rect {
   x: number
@@ -31,9 +43,12 @@ export interface Vector {
 }
 export const DEFAULT_TOLERANCE: number
 export const DEFAULT_CURVE_TOLERANCE: number
+/** Default number of nearest neighbours considered by `Hull::concave_hull`. */
+export const DEFAULT_CONCAVE_HULL_K: number
 export declare class Arc {
   /**
-   * Approximates (linearizes) an elliptical arc into a polyline (a vector of points).
+   * Approximates (linearizes) an elliptical arc into a polyline (a vector of points)
+   * with `Linearizer`.
    *
    * # Parameters
    * - `p1`: start point (from the SVG arc definition).
@@ -42,19 +57,89 @@ export declare class Arc {
    * - `angle`: x‑axis rotation (in degrees).
    * - `largearc`: large‑arc flag.
    * - `sweep`: sweep flag.
-   * - `tol`: tolerance for flatness.
+   * - `tol`: maximum distance between the arc and the polyline.
    *
    * # Returns
-   * A vector of points approximating the arc.
+   * A vector of points approximating the arc, from `p1` to `p2`.
    */
   static linearize(p1: Point, p2: Point, rx: number, ry: number, angle: number, largearc: boolean, sweep: boolean, tol: number): Array<Point>
 }
+/** An axis-aligned bounding box of a polygon after rotating it by `angle` degrees. */
+export interface RotatedBounds {
+  /** rotation (in degrees) to apply to the polygon */
+  angle: number
+  /** bounds of the rotated polygon */
+  bounds: Rect
+}
+export declare class BoundingBox {
+  /**
+   * Returns the axis-aligned bounds the polygon would have after rotating it by
+   * `angle` degrees around the origin, without allocating the rotated polygon.
+   *
+   * The rotation matches `Polygon::rotate` / `rotate_polygon` from deepnest-types.
+   * Returns `None` if the polygon has fewer than 3 points.
+   */
+  static boundsAtRotation(polygon: Array<Point>, angle: number): Rect | null
+  /**
+   * Computes the minimum-area enclosing rectangle of the polygon with rotating calipers
+   * over its convex hull.
+   *
+   * The result holds the rotation that aligns the rectangle with the axes and the bounds
+   * of the polygon at that rotation. The angle is normalized to `[0, 90)` degrees.
+   * Returns `None` if the polygon has fewer than 3 distinct, non-collinear points.
+   */
+  static minimumAreaRect(polygon: Array<Point>): RotatedBounds | null
+  /**
+   * Suggests the initial rotation (in degrees) for a part.
+   *
+   * With `rotations` set, only the `rotations` angles allowed by the nest configuration
+   * (`i * 360 / rotations`) are considered. Otherwise any angle is allowed and the
+   * minimum-area rectangle is used.
+   * The rotation with the smallest bounding box wins; on ties the narrower box is
+   * preferred, since parts are placed from left to right on the sheet.
+   * Returns `None` if the polygon has fewer than 3 points.
+   */
+  static optimalRotation(polygon: Array<Point>, rotations?: number | undefined | null): RotatedBounds | null
+}
+/** A segment shared by the edges of two placed parts. */
+export interface SharedSegment {
+  start: Point
+  end: Point
+  /** index of the part that keeps the segment in the cut paths */
+  partA: number
+  /** index of the part the segment is removed from */
+  partB: number
+}
+export interface CommonLineResult {
+  /** collinear overlapping edges between different parts */
+  sharedSegments: Array<SharedSegment>
+  /** total cut length saved by cutting the shared segments only once */
+  savedLength: number
+  /**
+   * the edges of all parts with the shared segments cut only once; closed paths
+   * repeat their first point at the end
+   */
+  cutPaths: Array<Array<Point>>
+}
+export declare class CommonLine {
+  /**
+   * Finds the common lines of the final placements, like the "merge lines" option of
+   * Deepnest: collinear overlapping edges of adjacent parts are cut only once.
+   *
+   * Each polygon is a placed part (offsets applied, `children` are holes). Edges are
+   * shared if they lie on each other within `tolerance` (defaulting to
+   * `DEFAULT_TOLERANCE`), using the same tests as `on_segment` / `almost_equal_points`.
+   * A shared segment stays with the part of lower index and is removed from the other.
+   */
+  static mergeLines(placements: Array<Polygon>, tolerance?: number | undefined | null): CommonLineResult
+}
 export declare class CubicBezier {
   /**
-   * Approximates (linearizes) a cubic Bézier curve by subdividing until each segment is flat enough.
+   * Approximates (linearizes) a cubic Bézier curve with `Linearizer`, so no point of
+   * the curve is farther than `tolerance` from the returned polyline.
    *
-   * The function returns a vector of points along the curve. The initial point `p1` is included,
-   * and each flat segment’s end point is appended.
+   * The function returns a vector of points along the curve, starting with `p1` and
+   * ending with `p2`.
    */
   static linearize(p1: Point, p2: Point, c1: Point, c2: Point, tolerance?: number | undefined | null): Array<Point>
 }
@@ -164,6 +249,7 @@ export declare class GeometryUtils {
    * with the same winding direction, this function returns the outer perimeter (hull)
    * of both polygons as a single continuous polygon.
    * If the polygons are not touching (or inputs are invalid) the function returns None.
+   * See `Merge::merge_touching` for any number of touching or overlapping polygons.
    *
    * The algorithm:
    * 1. Find an extreme point (lowest y after applying offsets) among both A and B.
@@ -175,14 +261,84 @@ export declare class GeometryUtils {
    */
   static polygonHull(a: Polygon, b: Polygon): Array<Point> | null
 }
+export declare class Hull {
+  /**
+   * Computes the convex hull of `points` with Andrew's monotone chain algorithm.
+   *
+   * The hull is returned counter-clockwise (negative `polygon_area`), starting at the
+   * lowest-x/lowest-y point. Collinear and duplicate points are dropped.
+   * Inputs with fewer than three distinct points return those points unchanged.
+   */
+  static convexHull(points: Array<Point>): Array<Point>
+  /**
+   * Computes a concave hull of `points` with the k-nearest-neighbours algorithm
+   * (Moreira & Santos, 2007).
+   *
+   * `k` controls the smoothness: small values follow the point set closely, larger
+   * values approach the convex hull. It defaults to `DEFAULT_CONCAVE_HULL_K` and is
+   * increased automatically until a simple polygon enclosing all points is found.
+   * If no such polygon exists, the convex hull is returned.
+   *
+   * The hull is returned counter-clockwise (negative `polygon_area`).
+   */
+  static concaveHull(points: Array<Point>, k?: number | undefined | null): Array<Point>
+}
+export declare class Merge {
+  /**
+   * Merges any number of touching or overlapping polygons into the outer perimeter of
+   * their union. This generalizes `GeometryUtils::polygon_hull`, which only handles
+   * two polygons touching along a single chain.
+   *
+   * The offsets (`offsetx`/`offsety`) of each polygon are applied first, and its
+   * `children` are treated as holes. Points closer than `tolerance` (defaulting to
+   * `DEFAULT_TOLERANCE`) are considered coincident, so edges shared by touching parts
+   * cancel out.
+   *
+   * Every connected region of the union is returned as one polygon without offsets.
+   * Outer rings are counter-clockwise (negative `polygon_area`), holes are clockwise
+   * and stored in `children`. Parts touching only at a corner stay separate polygons.
+   */
+  static mergeTouching(polygons: Array<Polygon>, tolerance?: number | undefined | null): Array<Polygon>
+}
+export interface ToolpathOptions {
+  /** position of the tool before the first cut, defaults to (0, 0) */
+  origin?: Point
+  /** length of the lead-in move into the contour, no lead-in if unset or zero */
+  leadIn?: number
+  /** length of the lead-out move off the contour, no lead-out if unset or zero */
+  leadOut?: number
+}
+/** A single closed contour to cut. */
+export interface Cut {
+  /** index of the placed part */
+  part: number
+  /** index of the hole in the part's `children`, `None` for the outer contour */
+  hole?: number
+  /** the contour starting and ending at the chosen start point */
+  path: Array<Point>
+  /** point where the tool pierces before moving onto the start point */
+  leadIn?: Point
+  /** point where the tool leaves after returning to the start point */
+  leadOut?: Point
+  /** rapid travel from the previous cut (or the origin) to this one */
+  rapidDistance: number
+}
+export interface ToolpathResult {
+  /** contours in cutting order */
+  cuts: Array<Cut>
+  /** total rapid travel */
+  rapidDistance: number
+  /** longest single rapid move */
+  longestRapid: number
+  /** total length of the contours, excluding lead-ins and lead-outs */
+  cutDistance: number
+}
 export declare class QuadraticBezier {
   /**
-   * Converts (linearizes) a quadratic Bézier curve into a sequence of points
-   * by repeatedly subdividing the curve until each segment is flat enough
-   * according to the provided tolerance.
+   * Converts (linearizes) a quadratic Bézier curve into a sequence of points with
+   * `Linearizer`, so no point of the curve is farther than `tolerance` from them.
    *
-   * The returned vector contains the starting point and the end point of each
-   * flat segment, in order along the curve.
+   * The returned vector starts with `p1` and ends with `p2`.
    */
   static linearize(p1: Point, p2: Point, c1: Point, tolerance?: number | undefined | null): Array<Point>
 }
@@ -190,6 +346,18 @@ export declare class QuadraticBezier {
  * A helper struct representing a quadratic Bézier segment defined by
  * endpoints `p1` and `p2` and a single control point `c1`.
  */
+export declare class Toolpath {
+  /**
+   * Orders the contours of the placed parts for cutting.
+   *
+   * A contour is only cut once every contour inside it is done, so holes come before
+   * their outer contour and parts placed in holes before the surrounding part.
+   * Within these constraints the order is built nearest-neighbour first and improved
+   * with 2-opt to minimize the rapid travel. Each contour starts at its vertex closest
+   * to the previous tool position; lead-ins and lead-outs point away from the material.
+   */
+  static orderCuts(placements: Array<Polygon>, options?: ToolpathOptions | undefined | null): ToolpathResult
+}
 export declare class BezierSegment {
   p1: Point
   p2: Point
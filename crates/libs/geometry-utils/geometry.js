@@ -310,14 +310,26 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { rotatePoint, polygonArea, Arc, CubicBezier, DEFAULT_TOLERANCE, DEFAULT_CURVE_TOLERANCE, GeometryUtils, QuadraticBezier, BezierSegment } = nativeBinding
+const { rotatePoint, polygonArea, MirrorAxis, rotatePolygon, mirrorPolygon, translatePolygon, normalizePolygonToOrigin, polygonRotations, Arc, BoundingBox, CommonLine, CubicBezier, DEFAULT_TOLERANCE, DEFAULT_CURVE_TOLERANCE, DEFAULT_CONCAVE_HULL_K, GeometryUtils, Hull, Merge, QuadraticBezier, Toolpath, BezierSegment } = nativeBinding
 
 module.exports.rotatePoint = rotatePoint
 module.exports.polygonArea = polygonArea
+module.exports.MirrorAxis = MirrorAxis
+module.exports.rotatePolygon = rotatePolygon
+module.exports.mirrorPolygon = mirrorPolygon
+module.exports.translatePolygon = translatePolygon
+module.exports.normalizePolygonToOrigin = normalizePolygonToOrigin
+module.exports.polygonRotations = polygonRotations
 module.exports.Arc = Arc
+module.exports.BoundingBox = BoundingBox
+module.exports.CommonLine = CommonLine
 module.exports.CubicBezier = CubicBezier
 module.exports.DEFAULT_TOLERANCE = DEFAULT_TOLERANCE
 module.exports.DEFAULT_CURVE_TOLERANCE = DEFAULT_CURVE_TOLERANCE
+module.exports.DEFAULT_CONCAVE_HULL_K = DEFAULT_CONCAVE_HULL_K
 module.exports.GeometryUtils = GeometryUtils
+module.exports.Hull = Hull
+module.exports.Merge = Merge
 module.exports.QuadraticBezier = QuadraticBezier
+module.exports.Toolpath = Toolpath
 module.exports.BezierSegment = BezierSegment
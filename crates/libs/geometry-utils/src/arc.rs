@@ -1,11 +1,11 @@
 use deepnest_types::types::Point;
 use derive_more::{From, Into};
-use std::collections::VecDeque;
 
 use crate::geometryutils::GeometryUtils;
+use crate::linearizer::Linearizer;
 
 #[derive(Debug, Clone, Copy, From, Into)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct Arc;
 
 /// A center–arc representation.
@@ -35,7 +35,7 @@ pub struct SvgArc {
   pub sweep: bool,
 }
 
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 impl Arc {
   /// Convert from a center–arc definition to an SVG-style arc.
   ///
@@ -198,7 +198,8 @@ impl Arc {
     }
   }
 
-  /// Approximates (linearizes) an elliptical arc into a polyline (a vector of points).
+  /// Approximates (linearizes) an elliptical arc into a polyline (a vector of points)
+  /// with `Linearizer`.
   ///
   /// # Parameters
   /// - `p1`: start point (from the SVG arc definition).
@@ -207,10 +208,10 @@ impl Arc {
   /// - `angle`: x‑axis rotation (in degrees).
   /// - `largearc`: large‑arc flag.
   /// - `sweep`: sweep flag.
-  /// - `tol`: tolerance for flatness.
+  /// - `tol`: maximum distance between the arc and the polyline.
   ///
   /// # Returns
-  /// A vector of points approximating the arc.
+  /// A vector of points approximating the arc, from `p1` to `p2`.
   #[napi]
   pub fn linearize(
     p1: Point,
@@ -222,60 +223,8 @@ impl Arc {
     sweep: bool,
     tol: f64,
   ) -> Vec<Point> {
-    // Start with the endpoint.
-    let mut finished: Vec<Point> = vec![p2];
-    let initial_arc = Arc::svg_to_center(p1, p2, rx, ry, angle, largearc, sweep);
-    let mut todo: VecDeque<CenterArc> = VecDeque::new();
-    todo.push_back(initial_arc);
-
-    // Iteratively subdivide the arc until flat enough.
-    while let Some(arc) = todo.pop_front() {
-      // Obtain the full arc's SVG representation and one with half the extent.
-      let fullarc =
-        Arc::center_to_svg(arc.center, arc.rx, arc.ry, arc.theta, arc.extent, arc.angle);
-      let subarc = Arc::center_to_svg(
-        arc.center,
-        arc.rx,
-        arc.ry,
-        arc.theta,
-        0.5 * arc.extent,
-        arc.angle,
-      );
-      let arcmid = subarc.p2;
-
-      // Compute the midpoint of the line joining fullarc.p1 and fullarc.p2.
-      let mid = Point {
-        x: 0.5 * (fullarc.p1.x + fullarc.p2.x),
-        y: 0.5 * (fullarc.p1.y + fullarc.p2.y),
-      };
-
-      // If the line's midpoint is within `tol` of the arc's midpoint, consider it flat.
-      if GeometryUtils::within_distance(mid, arcmid, tol) {
-        // Insert the endpoint at the beginning (to preserve order).
-        finished.insert(0, fullarc.p2);
-      } else {
-        // Otherwise, subdivide the arc into two halves.
-        let arc1 = CenterArc {
-          center: arc.center,
-          rx: arc.rx,
-          ry: arc.ry,
-          theta: arc.theta,
-          extent: 0.5 * arc.extent,
-          angle: arc.angle,
-        };
-        let arc2 = CenterArc {
-          center: arc.center,
-          rx: arc.rx,
-          ry: arc.ry,
-          theta: arc.theta + 0.5 * arc.extent,
-          extent: 0.5 * arc.extent,
-          angle: arc.angle,
-        };
-        // Process the subdivisions.
-        todo.push_front(arc2);
-        todo.push_front(arc1);
-      }
-    }
-    finished
+    let mut points = vec![p1];
+    Linearizer::new(tol).arc(p1, p2, rx, ry, angle, largearc, sweep, &mut points);
+    points
   }
 }
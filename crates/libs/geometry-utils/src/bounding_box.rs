@@ -0,0 +1,248 @@
+use deepnest_types::types::{Point, Rect};
+use derive_more::{From, Into};
+
+use crate::geometryutils::GeometryUtils;
+use crate::hull::Hull;
+
+#[derive(Debug, Clone, Copy, From, Into)]
+#[cfg_attr(feature = "node", napi)]
+pub struct BoundingBox;
+
+/// An axis-aligned bounding box of a polygon after rotating it by `angle` degrees.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct RotatedBounds {
+  /// rotation (in degrees) to apply to the polygon
+  pub angle: f64,
+  /// bounds of the rotated polygon
+  pub bounds: Rect,
+}
+
+#[cfg_attr(feature = "node", napi)]
+impl BoundingBox {
+  /// Returns the axis-aligned bounds the polygon would have after rotating it by
+  /// `angle` degrees around the origin, without allocating the rotated polygon.
+  ///
+  /// The rotation matches `Polygon::rotate` / `rotate_polygon` from deepnest-types.
+  /// Returns `None` if the polygon has fewer than 3 points.
+  #[napi]
+  pub fn bounds_at_rotation(
+    polygon: Vec<Point>,
+    angle: f64,
+  ) -> Option<Rect> {
+    if polygon.len() < 3 {
+      return None;
+    }
+    Some(BoundingBox::rotated_bounds(&polygon, angle))
+  }
+
+  /// Computes the minimum-area enclosing rectangle of the polygon with rotating calipers
+  /// over its convex hull.
+  ///
+  /// The result holds the rotation that aligns the rectangle with the axes and the bounds
+  /// of the polygon at that rotation. The angle is normalized to `[0, 90)` degrees.
+  /// Returns `None` if the polygon has fewer than 3 distinct, non-collinear points.
+  #[napi]
+  pub fn minimum_area_rect(polygon: Vec<Point>) -> Option<RotatedBounds> {
+    let hull = Hull::convex_hull(polygon);
+    let n = hull.len();
+    if n < 3 {
+      return None;
+    }
+
+    let edge_direction = |i: usize| -> Point {
+      let a = hull[i];
+      let b = hull[(i + 1) % n];
+      GeometryUtils::normalize_vector(Point {
+        x: b.x - a.x,
+        y: b.y - a.y,
+      })
+    };
+    let dot = |p: Point, d: Point| p.x * d.x + p.y * d.y;
+    // The hull is counter-clockwise, so the left normal of an edge points into the hull.
+    let normal = |d: Point| Point { x: -d.y, y: d.x };
+
+    // Initial caliper positions for the first edge.
+    let u = edge_direction(0);
+    let v = normal(u);
+    let mut right = 0;
+    let mut top = 0;
+    let mut left = 0;
+    for i in 1..n {
+      if dot(hull[i], u) > dot(hull[right], u) {
+        right = i;
+      }
+      if dot(hull[i], v) > dot(hull[top], v) {
+        top = i;
+      }
+      if dot(hull[i], u) < dot(hull[left], u) {
+        left = i;
+      }
+    }
+
+    let mut best_area = f64::INFINITY;
+    let mut best_direction = u;
+    for i in 0..n {
+      let u = edge_direction(i);
+      let v = normal(u);
+      // All calipers move counter-clockwise together with the edge.
+      while dot(hull[(right + 1) % n], u) > dot(hull[right], u) {
+        right = (right + 1) % n;
+      }
+      while dot(hull[(top + 1) % n], v) > dot(hull[top], v) {
+        top = (top + 1) % n;
+      }
+      while dot(hull[(left + 1) % n], u) < dot(hull[left], u) {
+        left = (left + 1) % n;
+      }
+
+      let width = dot(hull[right], u) - dot(hull[left], u);
+      let height = dot(hull[top], v) - dot(hull[i], v);
+      let area = width * height;
+      if area < best_area {
+        best_area = area;
+        best_direction = u;
+      }
+    }
+
+    // Rotate the best edge onto the x axis.
+    let angle = (-GeometryUtils::radians_to_degrees(best_direction.y.atan2(best_direction.x)))
+      .rem_euclid(90.0);
+    Some(RotatedBounds {
+      angle,
+      bounds: BoundingBox::rotated_bounds(&hull, angle),
+    })
+  }
+
+  /// Suggests the initial rotation (in degrees) for a part.
+  ///
+  /// With `rotations` set, only the `rotations` angles allowed by the nest configuration
+  /// (`i * 360 / rotations`) are considered. Otherwise any angle is allowed and the
+  /// minimum-area rectangle is used.
+  /// The rotation with the smallest bounding box wins; on ties the narrower box is
+  /// preferred, since parts are placed from left to right on the sheet.
+  /// Returns `None` if the polygon has fewer than 3 points.
+  #[napi]
+  pub fn optimal_rotation(
+    polygon: Vec<Point>,
+    rotations: Option<u32>,
+  ) -> Option<RotatedBounds> {
+    if polygon.len() < 3 {
+      return None;
+    }
+    let candidates: Vec<f64> = match rotations {
+      Some(count) => {
+        let count = count.max(1);
+        (0..count)
+          .map(|i| i as f64 * 360.0 / count as f64)
+          .collect()
+      }
+      None => {
+        let base = BoundingBox::minimum_area_rect(polygon.clone())?.angle;
+        (0..4).map(|i| base + 90.0 * i as f64).collect()
+      }
+    };
+
+    let hull = Hull::convex_hull(polygon);
+    let mut best: Option<RotatedBounds> = None;
+    for angle in candidates {
+      let bounds = BoundingBox::rotated_bounds(&hull, angle);
+      let better = match best {
+        None => true,
+        Some(current) => {
+          let area = bounds.width * bounds.height;
+          let best_area = current.bounds.width * current.bounds.height;
+          if GeometryUtils::almost_equal(area, best_area, Some(best_area * 1e-9)) {
+            bounds.width < current.bounds.width
+              && !GeometryUtils::almost_equal(bounds.width, current.bounds.width, None)
+          } else {
+            area < best_area
+          }
+        }
+      };
+      if better {
+        best = Some(RotatedBounds { angle, bounds });
+      }
+    }
+    best
+  }
+}
+
+impl BoundingBox {
+  fn rotated_bounds(
+    points: &[Point],
+    angle: f64,
+  ) -> Rect {
+    let (sin, cos) = GeometryUtils::degrees_to_radians(angle).sin_cos();
+    let rotate = |p: &Point| (p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+    let (x0, y0) = rotate(&points[0]);
+    let (xmin, xmax, ymin, ymax) = points
+      .iter()
+      .skip(1)
+      .map(rotate)
+      .fold((x0, x0, y0, y0), |(xmin, xmax, ymin, ymax), (x, y)| {
+        (xmin.min(x), xmax.max(x), ymin.min(y), ymax.max(y))
+      });
+    Rect {
+      x: xmin,
+      y: ymin,
+      width: xmax - xmin,
+      height: ymax - ymin,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn diamond() -> Vec<Point> {
+    // A 2x1 rectangle rotated by 45 degrees.
+    let s = std::f64::consts::FRAC_1_SQRT_2;
+    vec![
+      Point { x: 0.0, y: 0.0 },
+      Point {
+        x: 2.0 * s,
+        y: 2.0 * s,
+      },
+      Point { x: s, y: 3.0 * s },
+      Point { x: -s, y: s },
+    ]
+  }
+
+  #[test]
+  fn test_bounds_at_rotation() {
+    let bounds = BoundingBox::bounds_at_rotation(diamond(), -45.0).unwrap();
+    assert!(GeometryUtils::almost_equal(bounds.width, 2.0, Some(1e-9)));
+    assert!(GeometryUtils::almost_equal(bounds.height, 1.0, Some(1e-9)));
+  }
+
+  #[test]
+  fn test_minimum_area_rect() {
+    let rect = BoundingBox::minimum_area_rect(diamond()).unwrap();
+    assert!(GeometryUtils::almost_equal(rect.angle, 45.0, Some(1e-9)));
+    assert!(GeometryUtils::almost_equal(
+      rect.bounds.width * rect.bounds.height,
+      2.0,
+      Some(1e-9)
+    ));
+  }
+
+  #[test]
+  fn test_optimal_rotation_prefers_narrow_box() {
+    let best = BoundingBox::optimal_rotation(diamond(), None).unwrap();
+    assert!(GeometryUtils::almost_equal(
+      best.bounds.width,
+      1.0,
+      Some(1e-9)
+    ));
+    assert!(GeometryUtils::almost_equal(
+      best.bounds.height,
+      2.0,
+      Some(1e-9)
+    ));
+
+    let allowed = BoundingBox::optimal_rotation(diamond(), Some(4)).unwrap();
+    assert_eq!(allowed.angle % 90.0, 0.0);
+  }
+}
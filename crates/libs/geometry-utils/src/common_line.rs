@@ -0,0 +1,341 @@
+use deepnest_types::types::{Point, Polygon};
+use derive_more::{From, Into};
+
+use crate::constants::DEFAULT_TOLERANCE;
+use crate::geometryutils::GeometryUtils;
+
+#[derive(Debug, Clone, Copy, From, Into)]
+#[cfg_attr(feature = "node", napi)]
+pub struct CommonLine;
+
+/// A segment shared by the edges of two placed parts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SharedSegment {
+  pub start: Point,
+  pub end: Point,
+  /// index of the part that keeps the segment in the cut paths
+  pub part_a: u32,
+  /// index of the part the segment is removed from
+  pub part_b: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct CommonLineResult {
+  /// collinear overlapping edges between different parts
+  pub shared_segments: Vec<SharedSegment>,
+  /// total cut length saved by cutting the shared segments only once
+  pub saved_length: f64,
+  /// the edges of all parts with the shared segments cut only once; closed paths
+  /// repeat their first point at the end
+  pub cut_paths: Vec<Vec<Point>>,
+}
+
+/// An edge of a placed part, with the offsets applied.
+struct Edge {
+  part: usize,
+  ring: usize,
+  start: Point,
+  end: Point,
+}
+
+#[cfg_attr(feature = "node", napi)]
+impl CommonLine {
+  /// Finds the common lines of the final placements, like the "merge lines" option of
+  /// Deepnest: collinear overlapping edges of adjacent parts are cut only once.
+  ///
+  /// Each polygon is a placed part (offsets applied, `children` are holes). Edges are
+  /// shared if they lie on each other within `tolerance` (defaulting to
+  /// `DEFAULT_TOLERANCE`), using the same tests as `on_segment` / `almost_equal_points`.
+  /// A shared segment stays with the part of lower index and is removed from the other.
+  #[napi]
+  pub fn merge_lines(
+    placements: Vec<Polygon>,
+    tolerance: Option<f64>,
+  ) -> CommonLineResult {
+    let tol = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    let rings = CommonLine::collect_rings(&placements, tol);
+    let edges: Vec<Edge> = rings
+      .iter()
+      .enumerate()
+      .flat_map(|(ring, (part, points))| {
+        let n = points.len();
+        (0..n).map(move |i| Edge {
+          part: *part,
+          ring,
+          start: points[i],
+          end: points[(i + 1) % n],
+        })
+      })
+      .collect();
+
+    // Sweep over the edges sorted by their left end to skip distant pairs.
+    let min_x = |e: &Edge| e.start.x.min(e.end.x);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| min_x(&edges[i]).total_cmp(&min_x(&edges[j])));
+
+    let mut shared_segments = vec![];
+    // Removed parameter intervals along each edge.
+    let mut removed: Vec<Vec<(f64, f64)>> = vec![vec![]; edges.len()];
+    for (n, &i) in order.iter().enumerate() {
+      let reach = edges[i].start.x.max(edges[i].end.x) + tol;
+      for &j in order[n + 1..].iter() {
+        if min_x(&edges[j]) > reach {
+          break;
+        }
+        if edges[i].part == edges[j].part {
+          continue;
+        }
+        let (keep, remove) = if edges[i].part < edges[j].part {
+          (i, j)
+        } else {
+          (j, i)
+        };
+        let Some((start, end)) = CommonLine::overlap(&edges[keep], &edges[remove], tol) else {
+          continue;
+        };
+        let t0 = parameter(&edges[remove], start);
+        let t1 = parameter(&edges[remove], end);
+        removed[remove].push((t0.min(t1), t0.max(t1)));
+        shared_segments.push(SharedSegment {
+          start,
+          end,
+          part_a: edges[keep].part as u32,
+          part_b: edges[remove].part as u32,
+        });
+      }
+    }
+
+    let mut saved_length = 0.0;
+    let mut kept: Vec<Vec<(Point, Point)>> = vec![vec![]; rings.len()];
+    for (edge, intervals) in edges.iter().zip(removed.iter_mut()) {
+      let length = distance(edge.start, edge.end);
+      intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+      let mut cursor = 0.0;
+      for &(t0, t1) in intervals.iter() {
+        if t1 <= cursor {
+          continue;
+        }
+        if t0 > cursor && (t0 - cursor) * length > tol {
+          kept[edge.ring].push((at(edge, cursor), at(edge, t0)));
+        }
+        saved_length += (t1 - t0.max(cursor)) * length;
+        cursor = t1;
+      }
+      if cursor < 1.0 && (1.0 - cursor) * length > tol {
+        kept[edge.ring].push((at(edge, cursor), edge.end));
+      }
+    }
+
+    let cut_paths = kept
+      .into_iter()
+      .flat_map(|pieces| join_pieces(pieces, tol))
+      .collect();
+
+    CommonLineResult {
+      shared_segments,
+      saved_length,
+      cut_paths,
+    }
+  }
+}
+
+impl CommonLine {
+  /// Applies the offsets and returns the rings of all parts with their part index.
+  fn collect_rings(
+    placements: &[Polygon],
+    tol: f64,
+  ) -> Vec<(usize, Vec<Point>)> {
+    let mut rings = vec![];
+    for (part, polygon) in placements.iter().enumerate() {
+      let offsetx = polygon.offsetx.unwrap_or(0.0);
+      let offsety = polygon.offsety.unwrap_or(0.0);
+      for points in std::iter::once(&polygon.points).chain(polygon.children.iter().flatten()) {
+        let mut points: Vec<Point> = points
+          .iter()
+          .map(|p| Point {
+            x: p.x + offsetx,
+            y: p.y + offsety,
+          })
+          .collect();
+        points.dedup_by(|a, b| GeometryUtils::almost_equal_points(*a, *b, Some(tol)));
+        while points.len() > 1
+          && GeometryUtils::almost_equal_points(points[0], points[points.len() - 1], Some(tol))
+        {
+          points.pop();
+        }
+        if points.len() >= 2 {
+          rings.push((part, points));
+        }
+      }
+    }
+    rings
+  }
+
+  /// Returns the overlap of two collinear edges, oriented along `a`, if it is longer
+  /// than `tol`.
+  fn overlap(
+    a: &Edge,
+    b: &Edge,
+    tol: f64,
+  ) -> Option<(Point, Point)> {
+    let on = |edge: &Edge, p: Point| {
+      GeometryUtils::almost_equal_points(p, edge.start, Some(tol))
+        || GeometryUtils::almost_equal_points(p, edge.end, Some(tol))
+        || GeometryUtils::on_segment(edge.start, edge.end, p, Some(tol))
+    };
+    let mut points: Vec<Point> = vec![];
+    for p in [a.start, a.end] {
+      if on(b, p) {
+        points.push(p);
+      }
+    }
+    for p in [b.start, b.end] {
+      if on(a, p)
+        && !points
+          .iter()
+          .any(|q| GeometryUtils::almost_equal_points(*q, p, Some(tol)))
+      {
+        points.push(p);
+      }
+    }
+    if points.len() != 2 || distance(points[0], points[1]) <= tol {
+      return None;
+    }
+    if parameter(a, points[0]) > parameter(a, points[1]) {
+      points.swap(0, 1);
+    }
+    Some((points[0], points[1]))
+  }
+}
+
+fn distance(
+  a: Point,
+  b: Point,
+) -> f64 {
+  ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+}
+
+/// Position of `p` projected onto `edge`, from 0 at its start to 1 at its end.
+fn parameter(
+  edge: &Edge,
+  p: Point,
+) -> f64 {
+  let dx = edge.end.x - edge.start.x;
+  let dy = edge.end.y - edge.start.y;
+  let t = ((p.x - edge.start.x) * dx + (p.y - edge.start.y) * dy) / (dx * dx + dy * dy);
+  t.clamp(0.0, 1.0)
+}
+
+fn at(
+  edge: &Edge,
+  t: f64,
+) -> Point {
+  Point {
+    x: edge.start.x + (edge.end.x - edge.start.x) * t,
+    y: edge.start.y + (edge.end.y - edge.start.y) * t,
+  }
+}
+
+/// Joins the consecutive kept pieces of a ring into polylines.
+fn join_pieces(
+  pieces: Vec<(Point, Point)>,
+  tol: f64,
+) -> Vec<Vec<Point>> {
+  let mut paths: Vec<Vec<Point>> = vec![];
+  for (start, end) in pieces {
+    match paths.last_mut() {
+      Some(path) if GeometryUtils::almost_equal_points(path[path.len() - 1], start, Some(tol)) => {
+        path.push(end);
+      }
+      _ => paths.push(vec![start, end]),
+    }
+  }
+  // The ring may have been cut open in the middle of the first path.
+  if paths.len() > 1 {
+    let last = paths[paths.len() - 1][paths[paths.len() - 1].len() - 1];
+    if GeometryUtils::almost_equal_points(last, paths[0][0], Some(tol)) {
+      let mut tail = paths.pop().unwrap();
+      tail.extend(paths[0].drain(..).skip(1));
+      paths[0] = tail;
+    }
+  }
+  paths
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rectangle(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+  ) -> Polygon {
+    Polygon {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: width, y: 0.0 },
+        Point {
+          x: width,
+          y: height,
+        },
+        Point { x: 0.0, y: height },
+      ],
+      children: None,
+      offsetx: Some(x),
+      offsety: Some(y),
+    }
+  }
+
+  #[test]
+  fn test_merge_lines_adjacent_parts() {
+    // A 2x2 square with a 1x1 square next to it, sharing one unit of its right edge.
+    let result = CommonLine::merge_lines(
+      vec![rectangle(0.0, 0.0, 2.0, 2.0), rectangle(2.0, 0.5, 1.0, 1.0)],
+      None,
+    );
+    assert_eq!(result.shared_segments.len(), 1);
+    let shared = result.shared_segments[0];
+    assert_eq!((shared.part_a, shared.part_b), (0, 1));
+    assert!(GeometryUtils::almost_equal(
+      distance(shared.start, shared.end),
+      1.0,
+      Some(1e-9)
+    ));
+    assert!(GeometryUtils::almost_equal(
+      result.saved_length,
+      1.0,
+      Some(1e-9)
+    ));
+
+    // The first part stays closed, the second one becomes an open path.
+    assert_eq!(result.cut_paths.len(), 2);
+    assert_eq!(result.cut_paths[0].len(), 5);
+    assert_eq!(result.cut_paths[0][0], result.cut_paths[0][4]);
+    assert_eq!(result.cut_paths[1].len(), 4);
+    let cut_length: f64 = result
+      .cut_paths
+      .iter()
+      .flat_map(|path| path.windows(2).map(|w| distance(w[0], w[1])))
+      .sum();
+    assert!(GeometryUtils::almost_equal(
+      cut_length,
+      8.0 + 3.0,
+      Some(1e-9)
+    ));
+  }
+
+  #[test]
+  fn test_merge_lines_separate_parts() {
+    let result = CommonLine::merge_lines(
+      vec![rectangle(0.0, 0.0, 1.0, 1.0), rectangle(1.5, 0.0, 1.0, 1.0)],
+      None,
+    );
+    assert!(result.shared_segments.is_empty());
+    assert_eq!(result.saved_length, 0.0);
+    assert_eq!(result.cut_paths.len(), 2);
+  }
+}
@@ -1,5 +1,9 @@
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub const DEFAULT_TOLERANCE: f64 = 1e-9;
 
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub const DEFAULT_CURVE_TOLERANCE: f64 = 0.3;
+
+/// Default number of nearest neighbours considered by `Hull::concave_hull`.
+#[cfg_attr(feature = "node", napi)]
+pub const DEFAULT_CONCAVE_HULL_K: u32 = 3;
@@ -1,10 +1,10 @@
 use crate::constants::DEFAULT_CURVE_TOLERANCE;
+use crate::linearizer::Linearizer;
 use deepnest_types::types::Point;
 use derive_more::{From, Into};
-use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Copy, From, Into)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct CubicBezier;
 
 /// A cubic Bézier segment defined by endpoints `p1` and `p2` and control points `c1` and `c2`.
@@ -15,7 +15,7 @@ pub struct CubicBezierSegment {
   pub c1: Point,
   pub c2: Point,
 }
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 impl CubicBezier {
   /// Returns `true` if the cubic Bézier curve defined by endpoints `p1` and `p2` and control points
   /// `c1` and `c2` is flat enough according to the modified Roger Willcocks criterion.
@@ -115,10 +115,11 @@ impl CubicBezier {
     (seg1, seg2)
   }
 
-  /// Approximates (linearizes) a cubic Bézier curve by subdividing until each segment is flat enough.
+  /// Approximates (linearizes) a cubic Bézier curve with `Linearizer`, so no point of
+  /// the curve is farther than `tolerance` from the returned polyline.
   ///
-  /// The function returns a vector of points along the curve. The initial point `p1` is included,
-  /// and each flat segment’s end point is appended.
+  /// The function returns a vector of points along the curve, starting with `p1` and
+  /// ending with `p2`.
   #[napi]
   pub fn linearize(
     p1: Point,
@@ -127,27 +128,9 @@ impl CubicBezier {
     c2: Point,
     tolerance: Option<f64>,
   ) -> Vec<Point> {
-    let tol = tolerance.unwrap_or(DEFAULT_CURVE_TOLERANCE);
-    let mut finished = Vec::new();
-    finished.push(p1);
-
-    let mut todo: VecDeque<CubicBezierSegment> = VecDeque::new();
-    todo.push_back(CubicBezierSegment { p1, p2, c1, c2 });
-
-    // Iteratively process segments until all are sufficiently flat.
-    while let Some(segment) = todo.pop_front() {
-      if CubicBezier::is_flat(segment.p1, segment.p2, segment.c1, segment.c2, Some(tol)) {
-        // When the segment is flat, add its endpoint.
-        finished.push(segment.p2);
-      } else {
-        // Otherwise, subdivide the segment and process both halves.
-        let (seg1, seg2) =
-          CubicBezier::subdivide(segment.p1, segment.p2, segment.c1, segment.c2, Some(0.5));
-        // Push seg1 and seg2 to the front so that they are processed next.
-        todo.push_front(seg2);
-        todo.push_front(seg1);
-      }
-    }
-    finished
+    let linearizer = Linearizer::new(tolerance.unwrap_or(DEFAULT_CURVE_TOLERANCE));
+    let mut points = vec![p1];
+    linearizer.cubic(p1, c1, c2, p2, &mut points);
+    points
   }
 }
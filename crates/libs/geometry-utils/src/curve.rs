@@ -0,0 +1,120 @@
+//! Linearization of the curved parts from `deepnest_types::types::CurvePath`.
+//!
+//! The curved geometry is the source of truth for export; the polygons produced here
+//! are only meant for NFP generation and collision tests.
+
+use deepnest_types::types::{CurvePart, CurvePath, Point, Polygon, Segment};
+
+use crate::geometryutils::GeometryUtils;
+use crate::linearizer::Linearizer;
+
+/// Linearizes a curved path within the limits of `linearizer`.
+///
+/// The result starts at `path.start`. For closed paths the closing point is not
+/// repeated, as in `Polygon::points`.
+pub fn linearize_path(
+  path: &CurvePath,
+  linearizer: &Linearizer,
+) -> Vec<Point> {
+  let mut points = vec![path.start];
+  let mut current = path.start;
+  for segment in path.segments.iter() {
+    let to = segment.to();
+    match *segment {
+      Segment::Line { .. } => linearizer.line(to, &mut points),
+      Segment::Arc {
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+        ..
+      } => linearizer.arc(
+        current,
+        to,
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+        &mut points,
+      ),
+      Segment::Cubic {
+        control1, control2, ..
+      } => linearizer.cubic(current, control1, control2, to, &mut points),
+    }
+    current = to;
+  }
+  points.dedup_by(|a, b| GeometryUtils::almost_equal_points(*a, *b, None));
+  if path.closed
+    && points.len() > 1
+    && GeometryUtils::almost_equal_points(points[0], points[points.len() - 1], None)
+  {
+    points.pop();
+  }
+  points
+}
+
+/// Linearizes a curved part into a polygon with its holes as `children`.
+pub fn linearize_part(
+  part: &CurvePart,
+  linearizer: &Linearizer,
+) -> Polygon {
+  Polygon {
+    points: linearize_path(&part.outer, linearizer),
+    children: if part.holes.is_empty() {
+      None
+    } else {
+      Some(
+        part
+          .holes
+          .iter()
+          .map(|hole| linearize_path(hole, linearizer))
+          .collect(),
+      )
+    },
+    offsetx: None,
+    offsety: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn circle(radius: f64) -> CurvePath {
+    let arc = |to: Point| Segment::Arc {
+      to,
+      rx: radius,
+      ry: radius,
+      x_axis_rotation: 0.0,
+      large_arc: false,
+      sweep: true,
+    };
+    CurvePath {
+      start: Point { x: radius, y: 0.0 },
+      segments: vec![
+        arc(Point { x: -radius, y: 0.0 }),
+        arc(Point { x: radius, y: 0.0 }),
+      ],
+      closed: true,
+    }
+  }
+
+  #[test]
+  fn test_linearize_part_follows_placement() {
+    let part = CurvePart {
+      outer: circle(10.0),
+      holes: vec![],
+    };
+    let polygon = linearize_part(&part.place(90.0, 20.0, 0.0), &Linearizer::new(0.01));
+    assert_ne!(polygon.points[0], polygon.points[polygon.points.len() - 1]);
+    for p in polygon.points.iter() {
+      let radius = (p.x - 20.0).hypot(p.y);
+      assert!((10.0 - 0.01..=10.0 + 1e-9).contains(&radius));
+    }
+    // Counter-clockwise arcs give a counter-clockwise ring, which has a negative area here.
+    let area = -GeometryUtils::polygon_area(polygon.points);
+    assert!((area - std::f64::consts::PI * 100.0).abs() < 1.0);
+  }
+}
@@ -6,10 +6,10 @@ use derive_more::{From, Into};
 use std::primitive::{f64, isize};
 
 #[derive(Debug, Clone, Copy, From, Into)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct GeometryUtils;
 
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 impl GeometryUtils {
   /// Returns `true` if `a` and `b` are approximately equal within the given tolerance.
   /// If `tolerance` is `None`, a default tolerance of `1e-9` is used.
@@ -170,63 +170,14 @@ impl GeometryUtils {
     polygon: Polygon,
     tolerance: Option<f64>,
   ) -> Option<bool> {
-    if polygon.points.len() < 3 {
-      return None;
-    }
-    let tol = tolerance.unwrap_or(DEFAULT_TOLERANCE);
-
     // Use the provided offsets (defaulting to 0.0 if None).
-    let offsetx = polygon.offsetx.unwrap_or(0.0);
-    let offsety = polygon.offsety.unwrap_or(0.0);
-    let mut inside = false;
-    let n = polygon.points.len();
-
-    // Iterate over each edge of the polygon. The polygon is assumed closed,
-    // so the "previous" vertex for index 0 is the last vertex.
-    for i in 0..n {
-      let j = if i == 0 { n - 1 } else { i - 1 };
-
-      // Adjust the vertices with the offsets.
-      let xi = polygon.points[i].x + offsetx;
-      let yi = polygon.points[i].y + offsety;
-      let xj = polygon.points[j].x + offsetx;
-      let yj = polygon.points[j].y + offsety;
-
-      // If the point is approximately equal to a vertex, return None.
-      if GeometryUtils::almost_equal(xi, point.x, Some(tol))
-        && GeometryUtils::almost_equal(yi, point.y, Some(tol))
-      {
-        return None;
-      }
-
-      // If the point lies on the current segment, return None.
-      if GeometryUtils::on_segment(
-        Point { x: xi, y: yi },
-        Point { x: xj, y: yj },
-        point,
-        Some(tol),
-      ) {
-        return None;
-      }
-
-      // Skip degenerate segments.
-      if GeometryUtils::almost_equal(xi, xj, Some(tol))
-        && GeometryUtils::almost_equal(yi, yj, Some(tol))
-      {
-        continue;
-      }
-
-      // Ray-casting: check if a horizontal ray from `point` crosses this edge.
-      let cond1 = (yi > point.y) != (yj > point.y);
-      if cond1 {
-        let intersect_x = ((xj - xi) * (point.y - yi) / (yj - yi)) + xi;
-        if point.x < intersect_x {
-          inside = !inside;
-        }
-      }
-    }
-
-    Some(inside)
+    GeometryUtils::point_in_points(
+      point,
+      &polygon.points,
+      polygon.offsetx.unwrap_or(0.0),
+      polygon.offsety.unwrap_or(0.0),
+      tolerance,
+    )
   }
 
   /// Returns true if the edges of polygon A and polygon B intersect.
@@ -605,6 +556,7 @@ impl GeometryUtils {
   /// with the same winding direction, this function returns the outer perimeter (hull)
   /// of both polygons as a single continuous polygon.
   /// If the polygons are not touching (or inputs are invalid) the function returns None.
+  /// See `Merge::merge_touching` for any number of touching or overlapping polygons.
   ///
   /// The algorithm:
   /// 1. Find an extreme point (lowest y after applying offsets) among both A and B.
@@ -1047,3 +999,68 @@ impl GeometryUtils {
 
   // END::Helper Functions
 }
+
+impl GeometryUtils {
+  /// `point_in_polygon` for borrowed points, which are moved by the offsets.
+  pub fn point_in_points(
+    point: Point,
+    points: &[Point],
+    offsetx: f64,
+    offsety: f64,
+    tolerance: Option<f64>,
+  ) -> Option<bool> {
+    if points.len() < 3 {
+      return None;
+    }
+    let tol = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    let mut inside = false;
+    let n = points.len();
+
+    // Iterate over each edge of the polygon. The polygon is assumed closed,
+    // so the "previous" vertex for index 0 is the last vertex.
+    for i in 0..n {
+      let j = if i == 0 { n - 1 } else { i - 1 };
+
+      // Adjust the vertices with the offsets.
+      let xi = points[i].x + offsetx;
+      let yi = points[i].y + offsety;
+      let xj = points[j].x + offsetx;
+      let yj = points[j].y + offsety;
+
+      // If the point is approximately equal to a vertex, return None.
+      if GeometryUtils::almost_equal(xi, point.x, Some(tol))
+        && GeometryUtils::almost_equal(yi, point.y, Some(tol))
+      {
+        return None;
+      }
+
+      // If the point lies on the current segment, return None.
+      if GeometryUtils::on_segment(
+        Point { x: xi, y: yi },
+        Point { x: xj, y: yj },
+        point,
+        Some(tol),
+      ) {
+        return None;
+      }
+
+      // Skip degenerate segments.
+      if GeometryUtils::almost_equal(xi, xj, Some(tol))
+        && GeometryUtils::almost_equal(yi, yj, Some(tol))
+      {
+        continue;
+      }
+
+      // Ray-casting: check if a horizontal ray from `point` crosses this edge.
+      let cond1 = (yi > point.y) != (yj > point.y);
+      if cond1 {
+        let intersect_x = ((xj - xi) * (point.y - yi) / (yj - yi)) + xi;
+        if point.x < intersect_x {
+          inside = !inside;
+        }
+      }
+    }
+
+    Some(inside)
+  }
+}
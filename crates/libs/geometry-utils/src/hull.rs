@@ -0,0 +1,285 @@
+use deepnest_types::types::Point;
+use derive_more::{From, Into};
+
+use crate::constants::DEFAULT_CONCAVE_HULL_K;
+use crate::geometryutils::GeometryUtils;
+
+#[derive(Debug, Clone, Copy, From, Into)]
+#[cfg_attr(feature = "node", napi)]
+pub struct Hull;
+
+#[cfg_attr(feature = "node", napi)]
+impl Hull {
+  /// Computes the convex hull of `points` with Andrew's monotone chain algorithm.
+  ///
+  /// The hull is returned counter-clockwise (negative `polygon_area`), starting at the
+  /// lowest-x/lowest-y point. Collinear and duplicate points are dropped.
+  /// Inputs with fewer than three distinct points return those points unchanged.
+  #[napi]
+  pub fn convex_hull(points: Vec<Point>) -> Vec<Point> {
+    let mut sorted = points;
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+      return sorted;
+    }
+
+    let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() + 1);
+    // Lower hull
+    for p in sorted.iter() {
+      while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0 {
+        hull.pop();
+      }
+      hull.push(*p);
+    }
+    // Upper hull
+    let lower_len = hull.len() + 1;
+    for p in sorted.iter().rev().skip(1) {
+      while hull.len() >= lower_len && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0
+      {
+        hull.pop();
+      }
+      hull.push(*p);
+    }
+    // The last point equals the first one.
+    hull.pop();
+    hull
+  }
+
+  /// Computes a concave hull of `points` with the k-nearest-neighbours algorithm
+  /// (Moreira & Santos, 2007).
+  ///
+  /// `k` controls the smoothness: small values follow the point set closely, larger
+  /// values approach the convex hull. It defaults to `DEFAULT_CONCAVE_HULL_K` and is
+  /// increased automatically until a simple polygon enclosing all points is found.
+  /// If no such polygon exists, the convex hull is returned.
+  ///
+  /// The hull is returned counter-clockwise (negative `polygon_area`).
+  #[napi]
+  pub fn concave_hull(
+    points: Vec<Point>,
+    k: Option<u32>,
+  ) -> Vec<Point> {
+    let mut dataset = points;
+    dataset.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    dataset.dedup();
+    if dataset.len() <= 3 {
+      return Hull::convex_hull(dataset);
+    }
+
+    let mut k = (k.unwrap_or(DEFAULT_CONCAVE_HULL_K) as usize).max(3);
+    while k < dataset.len() {
+      if let Some(hull) = Hull::k_nearest_hull(&dataset, k) {
+        return hull;
+      }
+      k += 1;
+    }
+    Hull::convex_hull(dataset)
+  }
+}
+
+impl Hull {
+  /// One pass of the k-nearest-neighbours concave hull for a fixed `k`.
+  /// Returns `None` if the walk self-intersects or leaves points outside.
+  fn k_nearest_hull(
+    points: &[Point],
+    k: usize,
+  ) -> Option<Vec<Point>> {
+    // Start at the lowest point; all other points lie above it.
+    let first_index = (0..points.len()).min_by(|&a, &b| {
+      points[a]
+        .y
+        .total_cmp(&points[b].y)
+        .then(points[a].x.total_cmp(&points[b].x))
+    })?;
+    let first = points[first_index];
+    let mut dataset: Vec<Point> = points.to_vec();
+    dataset.swap_remove(first_index);
+
+    let mut hull = vec![first];
+    let mut candidates: Vec<Point> = Vec::with_capacity(dataset.len() + 1);
+    let mut current = first;
+    // Walk counter-clockwise, as if we arrived at the first point moving along +x.
+    let mut direction = Point { x: 1.0, y: 0.0 };
+
+    loop {
+      // The first point may only close the hull after a few steps.
+      if hull.len() == 4 {
+        dataset.push(first);
+      }
+
+      // Only the k nearest points are needed, in no particular order.
+      candidates.clear();
+      candidates.extend_from_slice(&dataset);
+      if candidates.len() > k {
+        candidates.select_nth_unstable_by(k - 1, |a, b| {
+          distance_sq(current, *a).total_cmp(&distance_sq(current, *b))
+        });
+        candidates.truncate(k);
+      }
+      // Prefer the sharpest right-hand turn to stay on the outside.
+      candidates.sort_by(|a, b| {
+        turn_angle(direction, current, *a).total_cmp(&turn_angle(direction, current, *b))
+      });
+
+      let next = candidates.iter().copied().find(|candidate| {
+        let closing = GeometryUtils::almost_equal_points(*candidate, first, None);
+        // Skip the edge ending at `current` and, when closing, the one starting at `first`.
+        let skip_first = if closing { 1 } else { 0 };
+        (skip_first..hull.len().saturating_sub(2))
+          .all(|t| !segments_intersect(current, *candidate, hull[t], hull[t + 1]))
+      })?;
+
+      if GeometryUtils::almost_equal_points(next, first, None) {
+        break;
+      }
+      direction = Point {
+        x: next.x - current.x,
+        y: next.y - current.y,
+      };
+      current = next;
+      hull.push(next);
+      let index = dataset.iter().position(|p| *p == next)?;
+      dataset.swap_remove(index);
+      if dataset.is_empty() {
+        return None;
+      }
+    }
+
+    // Points on the hull boundary yield `None` and count as enclosed.
+    let encloses_all = points
+      .iter()
+      .all(|p| GeometryUtils::point_in_points(*p, &hull, 0.0, 0.0, None) != Some(false));
+    encloses_all.then_some(hull)
+  }
+}
+
+/// Cross product of the vectors `o -> a` and `o -> b`.
+/// Positive if `o`, `a`, `b` make a counter-clockwise turn.
+fn cross(
+  o: Point,
+  a: Point,
+  b: Point,
+) -> f64 {
+  (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn distance_sq(
+  a: Point,
+  b: Point,
+) -> f64 {
+  (a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)
+}
+
+/// Signed angle between `direction` and `from -> to`; negative values are right-hand turns.
+fn turn_angle(
+  direction: Point,
+  from: Point,
+  to: Point,
+) -> f64 {
+  let v = Point {
+    x: to.x - from.x,
+    y: to.y - from.y,
+  };
+  let det = direction.x * v.y - direction.y * v.x;
+  let dot = direction.x * v.x + direction.y * v.y;
+  det.atan2(dot)
+}
+
+/// Returns `true` if the segments `a-b` and `c-d` intersect or touch.
+fn segments_intersect(
+  a: Point,
+  b: Point,
+  c: Point,
+  d: Point,
+) -> bool {
+  let o1 = cross(a, b, c);
+  let o2 = cross(a, b, d);
+  let o3 = cross(c, d, a);
+  let o4 = cross(c, d, b);
+
+  if ((o1 > 0.0 && o2 < 0.0) || (o1 < 0.0 && o2 > 0.0))
+    && ((o3 > 0.0 && o4 < 0.0) || (o3 < 0.0 && o4 > 0.0))
+  {
+    return true;
+  }
+  // Collinear cases: an endpoint lying on the other segment.
+  (o1 == 0.0
+    && GeometryUtils::in_range(c.x, a.x, b.x, None)
+    && GeometryUtils::in_range(c.y, a.y, b.y, None))
+    || (o2 == 0.0
+      && GeometryUtils::in_range(d.x, a.x, b.x, None)
+      && GeometryUtils::in_range(d.y, a.y, b.y, None))
+    || (o3 == 0.0
+      && GeometryUtils::in_range(a.x, c.x, d.x, None)
+      && GeometryUtils::in_range(a.y, c.y, d.y, None))
+    || (o4 == 0.0
+      && GeometryUtils::in_range(b.x, c.x, d.x, None)
+      && GeometryUtils::in_range(b.y, c.y, d.y, None))
+}
+
+#[cfg(test)]
+mod tests {
+  use deepnest_types::types::Polygon;
+
+  use super::*;
+
+  fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+    coords.iter().map(|&(x, y)| Point { x, y }).collect()
+  }
+
+  #[test]
+  fn test_convex_hull() {
+    let hull = Hull::convex_hull(points(&[
+      (0.0, 0.0),
+      (1.0, 1.0),
+      (2.0, 0.0),
+      (2.0, 2.0),
+      (0.0, 2.0),
+      (1.0, 0.0),
+      (0.0, 0.0),
+    ]));
+    assert_eq!(
+      hull,
+      points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)])
+    );
+    assert!(GeometryUtils::polygon_area(hull) < 0.0);
+  }
+
+  #[test]
+  fn test_convex_hull_collinear() {
+    let hull = Hull::convex_hull(points(&[(0.0, 0.0), (2.0, 0.0), (1.0, 0.0)]));
+    assert_eq!(hull, points(&[(0.0, 0.0), (2.0, 0.0)]));
+  }
+
+  #[test]
+  fn test_concave_hull_follows_notch() {
+    // A U shape sampled along its outline every unit; the notch is 5 units wide.
+    let mut outline = vec![];
+    outline.extend((0..=7).map(|x| (x as f64, 0.0)));
+    outline.extend((1..=5).map(|y| (7.0, y as f64)));
+    outline.extend((1..=5).rev().map(|y| (6.0, y as f64)));
+    outline.extend((2..=5).rev().map(|x| (x as f64, 1.0)));
+    outline.extend((1..=5).map(|y| (1.0, y as f64)));
+    outline.extend((1..=5).rev().map(|y| (0.0, y as f64)));
+    let u_shape = points(&outline);
+    let concave = Hull::concave_hull(u_shape.clone(), None);
+    let convex = Hull::convex_hull(u_shape.clone());
+    assert!(
+      GeometryUtils::polygon_area(concave.clone()).abs()
+        < GeometryUtils::polygon_area(convex).abs()
+    );
+    assert!(GeometryUtils::polygon_area(concave.clone()) < 0.0);
+    // (3.5, 3.0) lies inside the notch
+    let polygon = Polygon {
+      points: concave,
+      children: None,
+      offsetx: None,
+      offsety: None,
+    };
+    assert_eq!(
+      GeometryUtils::point_in_polygon(Point { x: 3.5, y: 3.0 }, polygon, None),
+      Some(false)
+    );
+  }
+}
@@ -1,11 +1,24 @@
+#[cfg(feature = "node")]
 #[macro_use]
 extern crate napi_derive;
+#[cfg(not(feature = "node"))]
+#[macro_use]
+extern crate napi_noop;
 
 pub mod arc;
+pub mod bounding_box;
+pub mod common_line;
 pub mod constants;
 pub mod cubic_bezier;
+pub mod curve;
 pub mod geometryutils;
+pub mod hull;
+pub mod linearizer;
+pub mod merge;
 pub mod quadratic_bezier;
+pub mod simplify;
+pub mod toolpath;
+pub mod vector;
 pub use crate::geometryutils::*;
 
 #[cfg(test)]
@@ -0,0 +1,349 @@
+//! Linearization of lines, Bézier curves and elliptical arcs with a bounded chord error.
+//!
+//! `Linearizer` uses upper bounds of the distance between a curve and its chords, so
+//! the tolerance is a guaranteed deviation in the units of the coordinates.
+//! `CubicBezier::linearize`, `QuadraticBezier::linearize` and `Arc::linearize` use it
+//! as well.
+
+use deepnest_types::types::Point;
+
+use crate::arc::Arc;
+use crate::constants::DEFAULT_CURVE_TOLERANCE;
+use crate::cubic_bezier::{CubicBezier, CubicBezierSegment};
+use crate::geometryutils::GeometryUtils;
+use crate::quadratic_bezier::{BezierSegment, QuadraticBezier};
+
+/// Subdivisions of a Bézier curve stop at this depth, even if the curve is not flat.
+/// Every halving divides the error bound by four, so the limit is only reached when
+/// `max_deviation` is below 4^-16 (about 2e-10) of the bound for the whole curve, e.g.
+/// for a zero tolerance. The curve then has 2^16 chords that may deviate more.
+const MAX_DEPTH: u32 = 16;
+
+/// Upper limit of the chords of a single arc. A full circle reaches it when
+/// `max_deviation` is below about 1e-9 of its radius; the chords may then deviate more.
+const MAX_ARC_SEGMENTS: f64 = 65536.0;
+
+/// Converts curves into polylines.
+///
+/// A curve is split into at most 65536 chords, which keeps a zero or tiny
+/// `max_deviation` from running out of memory; only then the chords may deviate more.
+///
+/// Every curve is appended to a point list that already ends with its start point,
+/// so paths are built by calling the methods segment by segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Linearizer {
+  /// maximum distance between a curve and the chords replacing it
+  pub max_deviation: f64,
+  /// minimum length of the chords of a curve, 0 to disable; takes precedence over
+  /// `max_deviation`, but the end points of a curve are always kept
+  pub min_segment_length: f64,
+}
+
+impl Default for Linearizer {
+  fn default() -> Self {
+    Linearizer::new(DEFAULT_CURVE_TOLERANCE)
+  }
+}
+
+impl Linearizer {
+  pub fn new(max_deviation: f64) -> Linearizer {
+    Linearizer {
+      max_deviation,
+      min_segment_length: 0.0,
+    }
+  }
+
+  /// The same limits for coordinates that are later scaled by `factor`.
+  pub fn scaled(
+    &self,
+    factor: f64,
+  ) -> Linearizer {
+    Linearizer {
+      max_deviation: self.max_deviation / factor,
+      min_segment_length: self.min_segment_length / factor,
+    }
+  }
+
+  /// Appends the end of a straight line.
+  pub fn line(
+    &self,
+    to: Point,
+    out: &mut Vec<Point>,
+  ) {
+    out.push(to);
+  }
+
+  /// Appends the points of a quadratic Bézier curve after `p1`, ending with `p2`.
+  pub fn quadratic(
+    &self,
+    p1: Point,
+    c1: Point,
+    p2: Point,
+    out: &mut Vec<Point>,
+  ) {
+    let first = out.len();
+    let mut todo = vec![(BezierSegment { p1, p2, c1 }, 0)];
+    while let Some((segment, depth)) = todo.pop() {
+      // The second derivative is constant, so the chord error is exactly |p1 - 2 c1 + p2| / 4.
+      let error = 0.25 * second_difference(segment.p1, segment.c1, segment.p2);
+      if depth >= MAX_DEPTH || error <= self.max_deviation {
+        out.push(segment.p2);
+      } else {
+        let (left, right) =
+          QuadraticBezier::subdivide(segment.p1, segment.p2, segment.c1, Some(0.5));
+        todo.push((right, depth + 1));
+        todo.push((left, depth + 1));
+      }
+    }
+    self.merge_short_segments(p1, out, first);
+  }
+
+  /// Appends the points of a cubic Bézier curve after `p1`, ending with `p2`.
+  pub fn cubic(
+    &self,
+    p1: Point,
+    c1: Point,
+    c2: Point,
+    p2: Point,
+    out: &mut Vec<Point>,
+  ) {
+    let first = out.len();
+    let mut todo = vec![(CubicBezierSegment { p1, p2, c1, c2 }, 0)];
+    while let Some((segment, depth)) = todo.pop() {
+      // |B''| <= 6 max(|p1 - 2 c1 + c2|, |c1 - 2 c2 + p2|), and a chord deviates by at most |B''| / 8.
+      let error = 0.75
+        * second_difference(segment.p1, segment.c1, segment.c2)
+          .max(second_difference(segment.c1, segment.c2, segment.p2));
+      if depth >= MAX_DEPTH || error <= self.max_deviation {
+        out.push(segment.p2);
+      } else {
+        let (left, right) =
+          CubicBezier::subdivide(segment.p1, segment.p2, segment.c1, segment.c2, Some(0.5));
+        todo.push((right, depth + 1));
+        todo.push((left, depth + 1));
+      }
+    }
+    self.merge_short_segments(p1, out, first);
+  }
+
+  /// Appends the points of an SVG elliptical arc after `p1`, ending with `p2`. Arcs
+  /// with a zero radius are straight lines, as in SVG.
+  #[allow(clippy::too_many_arguments)]
+  pub fn arc(
+    &self,
+    p1: Point,
+    p2: Point,
+    rx: f64,
+    ry: f64,
+    angle: f64,
+    large_arc: bool,
+    sweep: bool,
+    out: &mut Vec<Point>,
+  ) {
+    if rx == 0.0 || ry == 0.0 || GeometryUtils::almost_equal_points(p1, p2, None) {
+      out.push(p2);
+      return;
+    }
+    let arc = Arc::svg_to_center(p1, p2, rx, ry, angle, large_arc, sweep);
+    // The chords of the unit circle deviate by 1 - cos(step / 2); scaling to the
+    // ellipse stretches them by at most the larger radius.
+    let radius = arc.rx.max(arc.ry);
+    let step = 2.0 * (1.0 - self.max_deviation / radius).max(-1.0).acos();
+    let count = (arc.extent.to_radians().abs() / step)
+      .ceil()
+      .clamp(1.0, MAX_ARC_SEGMENTS) as usize;
+
+    let first = out.len();
+    let (sin, cos) = arc.angle.to_radians().sin_cos();
+    // The center parametrization of `Arc::svg_to_center` runs from `p2` to `p1`.
+    for i in (1..count).rev() {
+      let (s, c) = (arc.theta + arc.extent * i as f64 / count as f64)
+        .to_radians()
+        .sin_cos();
+      out.push(Point {
+        x: arc.center.x + cos * arc.rx * c - sin * arc.ry * s,
+        y: arc.center.y + sin * arc.rx * c + cos * arc.ry * s,
+      });
+    }
+    out.push(p2);
+    self.merge_short_segments(p1, out, first);
+  }
+
+  /// Drops the points of the curve starting at `out[first]` that are closer than
+  /// `min_segment_length` to the previous point or to the end of the curve.
+  fn merge_short_segments(
+    &self,
+    from: Point,
+    out: &mut Vec<Point>,
+    first: usize,
+  ) {
+    if self.min_segment_length <= 0.0 || out.len() <= first + 1 {
+      return;
+    }
+    let end = out[out.len() - 1];
+    let far = |a: Point, b: Point| !GeometryUtils::within_distance(a, b, self.min_segment_length);
+    let mut previous = from;
+    let mut kept = first;
+    for i in first..out.len() - 1 {
+      let p = out[i];
+      if far(previous, p) && far(p, end) {
+        out[kept] = p;
+        kept += 1;
+        previous = p;
+      }
+    }
+    out[kept] = end;
+    out.truncate(kept + 1);
+  }
+}
+
+/// Length of the second difference `a - 2 b + c` of three control points.
+fn second_difference(
+  a: Point,
+  b: Point,
+  c: Point,
+) -> f64 {
+  (a.x - 2.0 * b.x + c.x).hypot(a.y - 2.0 * b.y + c.y)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn point(
+    x: f64,
+    y: f64,
+  ) -> Point {
+    Point { x, y }
+  }
+
+  /// Largest distance between the chords of `points` and the samples of `curve`.
+  fn max_deviation(
+    points: &[Point],
+    curve: impl Fn(f64) -> Point,
+  ) -> f64 {
+    (0..=1000)
+      .map(|i| {
+        let p = curve(i as f64 / 1000.0);
+        points
+          .windows(2)
+          .map(|chord| distance_to_segment(p, chord[0], chord[1]))
+          .fold(f64::INFINITY, f64::min)
+      })
+      .fold(0.0, f64::max)
+  }
+
+  fn distance_to_segment(
+    p: Point,
+    a: Point,
+    b: Point,
+  ) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+    (p.x - a.x - t * dx).hypot(p.y - a.y - t * dy)
+  }
+
+  #[test]
+  fn test_curves_stay_within_max_deviation() {
+    let (p1, c1, c2, p2) = (
+      point(0.0, 0.0),
+      point(0.0, 100.0),
+      point(100.0, -50.0),
+      point(100.0, 0.0),
+    );
+    for tolerance in [1.0, 0.1, 0.01] {
+      let linearizer = Linearizer::new(tolerance);
+      let mut points = vec![p1];
+      linearizer.cubic(p1, c1, c2, p2, &mut points);
+      assert_eq!(points[points.len() - 1], p2);
+      let cubic = |t: f64| {
+        let s = 1.0 - t;
+        let (a, b, c, d) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+        point(
+          a * p1.x + b * c1.x + c * c2.x + d * p2.x,
+          a * p1.y + b * c1.y + c * c2.y + d * p2.y,
+        )
+      };
+      assert!(max_deviation(&points, cubic) <= tolerance);
+
+      let mut points = vec![p1];
+      linearizer.quadratic(p1, c1, p2, &mut points);
+      let quadratic = |t: f64| {
+        let s = 1.0 - t;
+        let (a, b, c) = (s * s, 2.0 * s * t, t * t);
+        point(
+          a * p1.x + b * c1.x + c * p2.x,
+          a * p1.y + b * c1.y + c * p2.y,
+        )
+      };
+      assert!(max_deviation(&points, quadratic) <= tolerance);
+
+      // Half ellipses with radii 50 and 20, rotated by 30 degrees. The sweep flag
+      // picks the half with increasing (sweep) or decreasing angles.
+      let (sin, cos) = 30f64.to_radians().sin_cos();
+      for sweep in [true, false] {
+        let direction = if sweep { 1.0 } else { -1.0 };
+        let ellipse = |t: f64| {
+          let (s, c) = (std::f64::consts::PI * t * direction).sin_cos();
+          point(
+            cos * 50.0 * c - sin * 20.0 * s,
+            sin * 50.0 * c + cos * 20.0 * s,
+          )
+        };
+        let (from, to) = (ellipse(0.0), ellipse(1.0));
+        let mut points = vec![from];
+        linearizer.arc(from, to, 50.0, 20.0, 30.0, false, sweep, &mut points);
+        assert_eq!(points[points.len() - 1], to);
+        assert!(max_deviation(&points, ellipse) <= tolerance);
+      }
+    }
+  }
+
+  #[test]
+  fn test_min_segment_length() {
+    let linearizer = Linearizer {
+      max_deviation: 0.001,
+      min_segment_length: 2.0,
+    };
+    let (from, to) = (point(10.0, 0.0), point(-10.0, 0.0));
+    let mut points = vec![from];
+    linearizer.arc(from, to, 10.0, 10.0, 0.0, false, false, &mut points);
+    assert_eq!(points[points.len() - 1], to);
+    for chord in points.windows(2) {
+      assert!((chord[1].x - chord[0].x).hypot(chord[1].y - chord[0].y) >= 2.0);
+    }
+    // Curves shorter than the minimum keep their ends.
+    let mut points = vec![from];
+    linearizer.cubic(
+      from,
+      point(10.5, 0.5),
+      point(11.0, 0.5),
+      point(11.0, 0.0),
+      &mut points,
+    );
+    assert_eq!(points, vec![from, point(11.0, 0.0)]);
+  }
+
+  #[test]
+  fn test_linearize_functions_use_linearizer() {
+    let (from, to) = (point(10.0, 0.0), point(-10.0, 0.0));
+    let mut expected = vec![from];
+    Linearizer::new(0.01).arc(from, to, 10.0, 10.0, 0.0, false, true, &mut expected);
+    assert_eq!(
+      Arc::linearize(from, to, 10.0, 10.0, 0.0, false, true, 0.01),
+      expected
+    );
+
+    let (c1, c2) = (point(10.0, 10.0), point(-10.0, 10.0));
+    let mut expected = vec![from];
+    Linearizer::default().cubic(from, c1, c2, to, &mut expected);
+    assert_eq!(CubicBezier::linearize(from, to, c1, c2, None), expected);
+
+    let mut expected = vec![from];
+    Linearizer::new(0.01).quadratic(from, c1, to, &mut expected);
+    assert_eq!(
+      QuadraticBezier::linearize(from, to, c1, Some(0.01)),
+      expected
+    );
+  }
+}
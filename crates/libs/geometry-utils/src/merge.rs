@@ -0,0 +1,673 @@
+use std::collections::{HashMap, HashSet};
+
+use deepnest_types::types::{Point, Polygon};
+use derive_more::{From, Into};
+
+use crate::constants::DEFAULT_TOLERANCE;
+use crate::geometryutils::GeometryUtils;
+
+#[derive(Debug, Clone, Copy, From, Into)]
+#[cfg_attr(feature = "node", napi)]
+pub struct Merge;
+
+/// A ring of one of the input polygons, with the offsets applied.
+/// Outer rings are stored mathematically counter-clockwise, holes clockwise, so that
+/// the winding number of a point is positive exactly where it is covered by a part.
+struct Ring {
+  points: Vec<Point>,
+  min: Point,
+  max: Point,
+}
+
+/// The merged vertices, hashed on a grid of `tol`-sized cells so that a point only
+/// has to be compared with the vertices in its own and the neighbouring cells.
+struct Vertices {
+  points: Vec<Point>,
+  cells: HashMap<(i64, i64), Vec<usize>>,
+  tol: f64,
+}
+
+#[cfg_attr(feature = "node", napi)]
+impl Merge {
+  /// Merges any number of touching or overlapping polygons into the outer perimeter of
+  /// their union. This generalizes `GeometryUtils::polygon_hull`, which only handles
+  /// two polygons touching along a single chain.
+  ///
+  /// The offsets (`offsetx`/`offsety`) of each polygon are applied first, and its
+  /// `children` are treated as holes. Points closer than `tolerance` (defaulting to
+  /// `DEFAULT_TOLERANCE`) are considered coincident, so edges shared by touching parts
+  /// cancel out.
+  ///
+  /// Every connected region of the union is returned as one polygon without offsets.
+  /// Outer rings are counter-clockwise (negative `polygon_area`), holes are clockwise
+  /// and stored in `children`. Parts touching only at a corner stay separate polygons.
+  #[napi]
+  pub fn merge_touching(
+    polygons: Vec<Polygon>,
+    tolerance: Option<f64>,
+  ) -> Vec<Polygon> {
+    let tol = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    let rings = Merge::collect_rings(&polygons, tol);
+    if rings.is_empty() {
+      return vec![];
+    }
+
+    let mut vertices = Vertices::new(tol);
+    let mut segments: Vec<(usize, usize)> = vec![];
+    for ring in rings.iter() {
+      let indices: Vec<usize> = ring.points.iter().map(|p| vertices.index(*p)).collect();
+      for i in 0..indices.len() {
+        let (a, b) = (indices[i], indices[(i + 1) % indices.len()]);
+        if a != b {
+          segments.push((a, b));
+        }
+      }
+    }
+
+    let segments = Merge::split_segments(&mut vertices, segments, tol);
+    let boundary = Merge::boundary_edges(&vertices.points, &segments, &rings, tol);
+    let loops = Merge::chain_edges(&vertices.points, &boundary);
+
+    let mut outers: Vec<(Vec<Point>, f64)> = vec![];
+    let mut holes: Vec<Vec<Point>> = vec![];
+    for ring in loops {
+      let ring = remove_collinear(ring, tol);
+      if ring.len() < 3 {
+        continue;
+      }
+      // `polygon_area` is negative for counter-clockwise rings, i.e. outer boundaries.
+      let area = GeometryUtils::polygon_area(ring.clone());
+      if area < 0.0 {
+        outers.push((ring, -area));
+      } else {
+        holes.push(ring);
+      }
+    }
+
+    let mut children: Vec<Vec<Vec<Point>>> = vec![vec![]; outers.len()];
+    for hole in holes {
+      // A consistent union has an outer ring around every hole. Should rounding
+      // defeat the containment test, the bounds decide, and a hole without any
+      // outer ring is dropped rather than returned as a part with inverted material.
+      let owner =
+        Merge::hole_owner(&hole, &outers, tol).or_else(|| Merge::bounds_owner(&hole, &outers, tol));
+      debug_assert!(owner.is_some(), "hole without an outer ring: {:?}", hole);
+      if let Some(index) = owner {
+        children[index].push(hole);
+      }
+    }
+
+    outers
+      .into_iter()
+      .zip(children)
+      .map(|((points, _), holes)| Polygon {
+        points,
+        children: (!holes.is_empty()).then_some(holes),
+        offsetx: None,
+        offsety: None,
+      })
+      .collect()
+  }
+}
+
+impl Merge {
+  /// Applies the offsets and orients all rings for the winding number test.
+  fn collect_rings(
+    polygons: &[Polygon],
+    tol: f64,
+  ) -> Vec<Ring> {
+    let mut rings = vec![];
+    for polygon in polygons {
+      let offsetx = polygon.offsetx.unwrap_or(0.0);
+      let offsety = polygon.offsety.unwrap_or(0.0);
+      let outer = std::iter::once((&polygon.points, false));
+      let holes = polygon.children.iter().flatten().map(|hole| (hole, true));
+      for (points, is_hole) in outer.chain(holes) {
+        let mut points: Vec<Point> = points
+          .iter()
+          .map(|p| Point {
+            x: p.x + offsetx,
+            y: p.y + offsety,
+          })
+          .collect();
+        points.dedup_by(|a, b| GeometryUtils::almost_equal_points(*a, *b, Some(tol)));
+        while points.len() > 1
+          && GeometryUtils::almost_equal_points(points[0], points[points.len() - 1], Some(tol))
+        {
+          points.pop();
+        }
+        if points.len() < 3 {
+          continue;
+        }
+        let counter_clockwise = GeometryUtils::polygon_area(points.clone()) < 0.0;
+        if counter_clockwise == is_hole {
+          points.reverse();
+        }
+        let (min, max) = bounds(&points);
+        rings.push(Ring { points, min, max });
+      }
+    }
+    rings
+  }
+
+  /// Returns the index of the smallest outer ring containing `hole`.
+  ///
+  /// Containment is decided by the first hole vertex or edge midpoint that is not on
+  /// the outer ring. If all of them are, the hole's bounding box is compared instead.
+  fn hole_owner(
+    hole: &[Point],
+    outers: &[(Vec<Point>, f64)],
+    tol: f64,
+  ) -> Option<usize> {
+    let midpoints = (0..hole.len()).map(|i| {
+      let (a, b) = (hole[i], hole[(i + 1) % hole.len()]);
+      Point {
+        x: (a.x + b.x) / 2.0,
+        y: (a.y + b.y) / 2.0,
+      }
+    });
+    let probes: Vec<Point> = hole.iter().copied().chain(midpoints).collect();
+    let (hole_min, hole_max) = bounds(hole);
+    outers
+      .iter()
+      .enumerate()
+      .filter(|(_, (outer, _))| {
+        probes
+          .iter()
+          .find_map(|p| GeometryUtils::point_in_points(*p, outer, 0.0, 0.0, Some(tol)))
+          .unwrap_or_else(|| bounds_contain(outer, hole_min, hole_max, tol))
+      })
+      .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+      .map(|(index, _)| index)
+  }
+
+  /// Returns the index of the smallest outer ring whose bounding box contains the
+  /// bounding box of `hole`.
+  fn bounds_owner(
+    hole: &[Point],
+    outers: &[(Vec<Point>, f64)],
+    tol: f64,
+  ) -> Option<usize> {
+    let (hole_min, hole_max) = bounds(hole);
+    outers
+      .iter()
+      .enumerate()
+      .filter(|(_, (outer, _))| bounds_contain(outer, hole_min, hole_max, tol))
+      .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+      .map(|(index, _)| index)
+  }
+
+  /// Splits all segments at their mutual intersections and at vertices lying on them,
+  /// then drops duplicates, so that overlapping edges become identical segments.
+  fn split_segments(
+    vertices: &mut Vertices,
+    segments: Vec<(usize, usize)>,
+    tol: f64,
+  ) -> Vec<(usize, usize)> {
+    let mut splits: Vec<Vec<usize>> = vec![vec![]; segments.len()];
+
+    // Sweep over the segments sorted by their left end to skip distant pairs.
+    let min_x = |s: &(usize, usize), v: &[Point]| v[s.0].x.min(v[s.1].x);
+    let max_x = |s: &(usize, usize), v: &[Point]| v[s.0].x.max(v[s.1].x);
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by(|&i, &j| {
+      min_x(&segments[i], &vertices.points).total_cmp(&min_x(&segments[j], &vertices.points))
+    });
+
+    for (n, &i) in order.iter().enumerate() {
+      let reach = max_x(&segments[i], &vertices.points) + tol;
+      for &j in order[n + 1..].iter() {
+        if min_x(&segments[j], &vertices.points) > reach {
+          break;
+        }
+        let (a, b) = segments[i];
+        let (c, d) = segments[j];
+        let (pa, pb, pc, pd) = (
+          vertices.points[a],
+          vertices.points[b],
+          vertices.points[c],
+          vertices.points[d],
+        );
+        if pa.y.min(pb.y) > pc.y.max(pd.y) + tol || pc.y.min(pd.y) > pa.y.max(pb.y) + tol {
+          continue;
+        }
+
+        // Endpoints touching the other segment.
+        for (vertex, point, target) in [(c, pc, i), (d, pd, i), (a, pa, j), (b, pb, j)] {
+          let (s, e) = segments[target];
+          if GeometryUtils::on_segment(vertices.points[s], vertices.points[e], point, Some(tol)) {
+            splits[target].push(vertex);
+          }
+        }
+
+        // Proper crossings.
+        if let Some(point) = crossing(pa, pb, pc, pd) {
+          let vertex = vertices.index(point);
+          for target in [i, j] {
+            let (s, e) = segments[target];
+            if vertex != s && vertex != e {
+              splits[target].push(vertex);
+            }
+          }
+        }
+      }
+    }
+
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    let mut result = vec![];
+    for ((start, end), mut points) in segments.into_iter().zip(splits) {
+      let origin = vertices.points[start];
+      let distance = |v: &usize| {
+        let p = vertices.points[*v];
+        (p.x - origin.x).powi(2) + (p.y - origin.y).powi(2)
+      };
+      points.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+      points.dedup();
+
+      let mut previous = start;
+      for vertex in points.into_iter().chain(std::iter::once(end)) {
+        if vertex == previous {
+          continue;
+        }
+        let key = (previous.min(vertex), previous.max(vertex));
+        if seen.insert(key) {
+          result.push((previous, vertex));
+        }
+        previous = vertex;
+      }
+    }
+    result
+  }
+
+  /// Keeps the segments separating covered from uncovered area, directed so that the
+  /// union lies on their left.
+  fn boundary_edges(
+    vertices: &[Point],
+    segments: &[(usize, usize)],
+    rings: &[Ring],
+    tol: f64,
+  ) -> Vec<(usize, usize)> {
+    segments
+      .iter()
+      .filter_map(|&(a, b)| {
+        let (pa, pb) = (vertices[a], vertices[b]);
+        let normal = GeometryUtils::normalize_vector(Point {
+          x: pa.y - pb.y,
+          y: pb.x - pa.x,
+        });
+        let length = ((pb.x - pa.x).powi(2) + (pb.y - pa.y).powi(2)).sqrt();
+        // Probe just beside the midpoint, staying well within the segment's neighbourhood.
+        let step = (length * 1e-3).min(tol.max(1e-7) * 10.0);
+        let mid = Point {
+          x: (pa.x + pb.x) / 2.0,
+          y: (pa.y + pb.y) / 2.0,
+        };
+        let probe = |sign: f64| Point {
+          x: mid.x + normal.x * step * sign,
+          y: mid.y + normal.y * step * sign,
+        };
+        let left = winding_number(probe(1.0), rings) > 0;
+        let right = winding_number(probe(-1.0), rings) > 0;
+        match (left, right) {
+          (true, false) => Some((a, b)),
+          (false, true) => Some((b, a)),
+          _ => None,
+        }
+      })
+      .collect()
+  }
+
+  /// Links the directed boundary edges into closed rings. Where several edges leave a
+  /// vertex, the sharpest left turn is taken so that rings meeting at a single point
+  /// are kept apart.
+  fn chain_edges(
+    vertices: &[Point],
+    edges: &[(usize, usize)],
+  ) -> Vec<Vec<Point>> {
+    let mut outgoing: Vec<Vec<usize>> = vec![vec![]; vertices.len()];
+    for (index, &(a, _)) in edges.iter().enumerate() {
+      outgoing[a].push(index);
+    }
+    let direction = |edge: usize| {
+      let (a, b) = edges[edge];
+      Point {
+        x: vertices[b].x - vertices[a].x,
+        y: vertices[b].y - vertices[a].y,
+      }
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = vec![];
+    for first in 0..edges.len() {
+      if used[first] {
+        continue;
+      }
+      used[first] = true;
+      let start = edges[first].0;
+      let mut ring = vec![vertices[start]];
+      let mut current = first;
+      let closed = loop {
+        let vertex = edges[current].1;
+        if vertex == start {
+          break true;
+        }
+        ring.push(vertices[vertex]);
+        let incoming = direction(current);
+        let next = outgoing[vertex]
+          .iter()
+          .filter(|&&edge| !used[edge])
+          .max_by(|&&e1, &&e2| {
+            left_turn(incoming, direction(e1)).total_cmp(&left_turn(incoming, direction(e2)))
+          })
+          .copied();
+        match next {
+          Some(edge) => {
+            used[edge] = true;
+            current = edge;
+          }
+          None => break false,
+        }
+      };
+      if closed {
+        rings.push(ring);
+      }
+    }
+    rings
+  }
+}
+
+impl Vertices {
+  fn new(tol: f64) -> Self {
+    Vertices {
+      points: vec![],
+      cells: HashMap::new(),
+      tol,
+    }
+  }
+
+  fn cell(
+    &self,
+    point: Point,
+  ) -> (i64, i64) {
+    let size = self.tol.max(f64::MIN_POSITIVE);
+    (
+      (point.x / size).floor() as i64,
+      (point.y / size).floor() as i64,
+    )
+  }
+
+  /// Returns the index of `point`, adding it if no vertex is within `tol`.
+  /// Such a vertex can only lie in the 3x3 cells around the point's own cell.
+  fn index(
+    &mut self,
+    point: Point,
+  ) -> usize {
+    let (cx, cy) = self.cell(point);
+    let existing = (cx - 1..=cx + 1)
+      .flat_map(|x| (cy - 1..=cy + 1).map(move |y| (x, y)))
+      .filter_map(|key| self.cells.get(&key))
+      .flatten()
+      .copied()
+      .filter(|&index| {
+        GeometryUtils::almost_equal_points(self.points[index], point, Some(self.tol))
+      })
+      .min();
+    match existing {
+      Some(index) => index,
+      None => {
+        self.points.push(point);
+        let index = self.points.len() - 1;
+        self.cells.entry((cx, cy)).or_default().push(index);
+        index
+      }
+    }
+  }
+}
+
+/// Whether the bounding box of `outer` contains the box from `min` to `max`.
+fn bounds_contain(
+  outer: &[Point],
+  min: Point,
+  max: Point,
+  tol: f64,
+) -> bool {
+  let (outer_min, outer_max) = bounds(outer);
+  outer_min.x <= min.x + tol
+    && outer_min.y <= min.y + tol
+    && outer_max.x >= max.x - tol
+    && outer_max.y >= max.y - tol
+}
+
+/// Bounding box of `points`, which must not be empty.
+fn bounds(points: &[Point]) -> (Point, Point) {
+  points
+    .iter()
+    .skip(1)
+    .fold((points[0], points[0]), |(min, max), p| {
+      (
+        Point {
+          x: min.x.min(p.x),
+          y: min.y.min(p.y),
+        },
+        Point {
+          x: max.x.max(p.x),
+          y: max.y.max(p.y),
+        },
+      )
+    })
+}
+
+/// Intersection point of the segments `a-b` and `c-d` if they cross properly.
+fn crossing(
+  a: Point,
+  b: Point,
+  c: Point,
+  d: Point,
+) -> Option<Point> {
+  let r = Point {
+    x: b.x - a.x,
+    y: b.y - a.y,
+  };
+  let s = Point {
+    x: d.x - c.x,
+    y: d.y - c.y,
+  };
+  let denom = r.x * s.y - r.y * s.x;
+  if denom == 0.0 {
+    return None;
+  }
+  let t = ((c.x - a.x) * s.y - (c.y - a.y) * s.x) / denom;
+  let u = ((c.x - a.x) * r.y - (c.y - a.y) * r.x) / denom;
+  if t <= 0.0 || t >= 1.0 || u <= 0.0 || u >= 1.0 {
+    return None;
+  }
+  Some(Point {
+    x: a.x + t * r.x,
+    y: a.y + t * r.y,
+  })
+}
+
+/// Signed angle from `incoming` to `outgoing`; larger values are sharper left turns.
+fn left_turn(
+  incoming: Point,
+  outgoing: Point,
+) -> f64 {
+  let det = incoming.x * outgoing.y - incoming.y * outgoing.x;
+  let dot = incoming.x * outgoing.x + incoming.y * outgoing.y;
+  det.atan2(dot)
+}
+
+/// Sum of the winding numbers of `point` with respect to all rings.
+fn winding_number(
+  point: Point,
+  rings: &[Ring],
+) -> i32 {
+  let mut winding = 0;
+  for ring in rings {
+    if point.x < ring.min.x || point.x > ring.max.x || point.y < ring.min.y || point.y > ring.max.y
+    {
+      continue;
+    }
+    let n = ring.points.len();
+    for i in 0..n {
+      let a = ring.points[i];
+      let b = ring.points[(i + 1) % n];
+      let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+      if a.y <= point.y {
+        if b.y > point.y && side > 0.0 {
+          winding += 1;
+        }
+      } else if b.y <= point.y && side < 0.0 {
+        winding -= 1;
+      }
+    }
+  }
+  winding
+}
+
+/// Drops vertices lying on the line between their neighbours.
+fn remove_collinear(
+  ring: Vec<Point>,
+  tol: f64,
+) -> Vec<Point> {
+  let mut ring = ring;
+  let mut changed = true;
+  while changed && ring.len() >= 3 {
+    changed = false;
+    let n = ring.len();
+    for i in 0..n {
+      let previous = ring[(i + n - 1) % n];
+      let next = ring[(i + 1) % n];
+      let current = ring[i];
+      let turn = (current.x - previous.x) * (next.y - previous.y)
+        - (current.y - previous.y) * (next.x - previous.x);
+      let forward = (current.x - previous.x) * (next.x - current.x)
+        + (current.y - previous.y) * (next.y - current.y);
+      if turn.abs() <= tol && forward > 0.0 {
+        ring.remove(i);
+        changed = true;
+        break;
+      }
+    }
+  }
+  ring
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(
+    x: f64,
+    y: f64,
+    size: f64,
+  ) -> Polygon {
+    Polygon {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: size, y: 0.0 },
+        Point { x: size, y: size },
+        Point { x: 0.0, y: size },
+      ],
+      children: None,
+      offsetx: Some(x),
+      offsety: Some(y),
+    }
+  }
+
+  #[test]
+  fn test_merge_touching_row() {
+    // Three unit squares in a row, the last one overlapping the second.
+    let merged = Merge::merge_touching(
+      vec![
+        square(0.0, 0.0, 1.0),
+        square(1.0, 0.0, 1.0),
+        square(1.5, 0.0, 1.0),
+      ],
+      None,
+    );
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].points.len(), 4);
+    assert!(merged[0].children.is_none());
+    let area = GeometryUtils::polygon_area(merged[0].points.clone());
+    assert!(GeometryUtils::almost_equal(area, -2.5, Some(1e-9)));
+  }
+
+  #[test]
+  fn test_merge_touching_keeps_holes() {
+    // Four 1x3 / 3x1 bars framing a unit hole, plus a separate part.
+    let bar = |x: f64, y: f64, w: f64, h: f64| Polygon {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: w, y: 0.0 },
+        Point { x: w, y: h },
+        Point { x: 0.0, y: h },
+      ],
+      children: None,
+      offsetx: Some(x),
+      offsety: Some(y),
+    };
+    let merged = Merge::merge_touching(
+      vec![
+        bar(0.0, 0.0, 3.0, 1.0),
+        bar(0.0, 2.0, 3.0, 1.0),
+        bar(0.0, 1.0, 1.0, 1.0),
+        bar(2.0, 1.0, 1.0, 1.0),
+        square(5.0, 5.0, 1.0),
+      ],
+      None,
+    );
+    assert_eq!(merged.len(), 2);
+    let frame = merged.iter().find(|p| p.children.is_some()).unwrap();
+    assert_eq!(frame.points.len(), 4);
+    let holes = frame.children.as_ref().unwrap();
+    assert_eq!(holes.len(), 1);
+    let hole_area = GeometryUtils::polygon_area(holes[0].clone());
+    assert!(GeometryUtils::almost_equal(hole_area, 1.0, Some(1e-9)));
+  }
+
+  #[test]
+  fn test_merge_touching_grid() {
+    // A 20x20 grid of unit squares shares every corner between four parts.
+    let squares = (0..400)
+      .map(|i| square((i % 20) as f64, (i / 20) as f64, 1.0))
+      .collect();
+    let merged = Merge::merge_touching(squares, None);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].points.len(), 4);
+    let area = GeometryUtils::polygon_area(merged[0].points.clone());
+    assert!(GeometryUtils::almost_equal(area, -400.0, Some(1e-6)));
+  }
+
+  #[test]
+  fn test_merge_touching_corner_stays_separate() {
+    let merged = Merge::merge_touching(vec![square(0.0, 0.0, 1.0), square(1.0, 1.0, 1.0)], None);
+    assert_eq!(merged.len(), 2);
+    assert!(merged.iter().all(|p| p.points.len() == 4));
+  }
+
+  #[test]
+  fn test_bounds_owner() {
+    // Rings with the offsets applied, as `merge_touching` passes them.
+    let ring = |x: f64, y: f64, size: f64| {
+      let points: Vec<Point> = square(0.0, 0.0, size)
+        .points
+        .iter()
+        .map(|p| Point {
+          x: p.x + x,
+          y: p.y + y,
+        })
+        .collect();
+      (points, size * size)
+    };
+    let outers = vec![
+      ring(0.0, 0.0, 10.0),
+      ring(1.0, 1.0, 4.0),
+      ring(20.0, 0.0, 2.0),
+    ];
+    let hole = ring(2.0, 2.0, 1.0).0;
+    assert_eq!(Merge::bounds_owner(&hole, &outers, 1e-9), Some(1));
+    let outside = ring(30.0, 0.0, 1.0).0;
+    assert_eq!(Merge::bounds_owner(&outside, &outers, 1e-9), None);
+  }
+}
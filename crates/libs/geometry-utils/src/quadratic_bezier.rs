@@ -1,23 +1,23 @@
 use crate::constants::DEFAULT_CURVE_TOLERANCE;
+use crate::linearizer::Linearizer;
 use deepnest_types::types::Point;
 use derive_more::{From, Into};
-use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Copy, From, Into)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct QuadraticBezier;
 
 /// A helper struct representing a quadratic Bézier segment defined by
 /// endpoints `p1` and `p2` and a single control point `c1`.
 #[derive(Debug, Clone, Copy, From, Into)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct BezierSegment {
   pub p1: Point,
   pub p2: Point,
   pub c1: Point,
 }
 
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 impl QuadraticBezier {
   /// Returns `true` if the quadratic Bézier curve defined by endpoints `p1`, `p2`
   /// and control point `c1` is sufficiently flat according to Roger Willcocks's
@@ -78,12 +78,10 @@ impl QuadraticBezier {
     (seg1, seg2)
   }
 
-  /// Converts (linearizes) a quadratic Bézier curve into a sequence of points
-  /// by repeatedly subdividing the curve until each segment is flat enough
-  /// according to the provided tolerance.
+  /// Converts (linearizes) a quadratic Bézier curve into a sequence of points with
+  /// `Linearizer`, so no point of the curve is farther than `tolerance` from them.
   ///
-  /// The returned vector contains the starting point and the end point of each
-  /// flat segment, in order along the curve.
+  /// The returned vector starts with `p1` and ends with `p2`.
   #[napi]
   pub fn linearize(
     p1: Point,
@@ -91,27 +89,9 @@ impl QuadraticBezier {
     c1: Point,
     tolerance: Option<f64>,
   ) -> Vec<Point> {
-    let tol = tolerance.unwrap_or(DEFAULT_CURVE_TOLERANCE);
-    let mut finished = Vec::new();
-    finished.push(p1);
-
-    // Use a deque to avoid recursion stack overflows.
-    let mut todo: VecDeque<BezierSegment> = VecDeque::new();
-    todo.push_back(BezierSegment { p1, p2, c1 });
-
-    while let Some(segment) = todo.pop_front() {
-      if QuadraticBezier::is_flat(segment.p1, segment.p2, segment.c1, Some(tol)) {
-        // When flat, accept the segment by adding its endpoint.
-        finished.push(segment.p2);
-      } else {
-        // Subdivide the segment at t=0.5 and process both halves.
-        let (seg1, seg2) =
-          QuadraticBezier::subdivide(segment.p1, segment.p2, segment.c1, Some(0.5));
-        // Insert in order so that seg1 (the first half) is processed next.
-        todo.push_front(seg2);
-        todo.push_front(seg1);
-      }
-    }
-    finished
+    let linearizer = Linearizer::new(tolerance.unwrap_or(DEFAULT_CURVE_TOLERANCE));
+    let mut points = vec![p1];
+    linearizer.quadratic(p1, c1, p2, &mut points);
+    points
   }
 }
@@ -0,0 +1,849 @@
+//! Simplification of rings that keeps parts valid for nesting.
+//!
+//! `simplify_part` removes vertices of all rings of a part jointly and keeps their
+//! topology. `simplify_conservative` offsets every vertex by the tolerance away from the
+//! material and then replaces runs of vertices by shortcuts between the offset
+//! points. A shortcut is only taken if it keeps the original ring on its inner side,
+//! like an edge of a local hull, so a simplified outer contour contains the original
+//! and a simplified hole lies inside the original hole. The tests are exact
+//! orientation and crossing tests; nothing is estimated.
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use deepnest_types::types::{Point, Polygon};
+
+use crate::vector::{add, cross, distance_to_segment, dot, normalize, scale, sub};
+
+/// Replaces runs of vertices of the closed ring `points` by single edges, such that
+/// the original vertices are within `tolerance` of the result.
+///
+/// With `grow` the result contains the ring, as needed for an outer contour; without
+/// it the result is contained by the ring, as needed for a hole. The result has the
+/// orientation of the ring and crosses neither the ring nor itself. Rings with fewer
+/// than four vertices and non-positive tolerances are returned unchanged.
+pub fn simplify_conservative(
+  points: &[Point],
+  tolerance: f64,
+  grow: bool,
+) -> Vec<Point> {
+  let mut ring = points.to_vec();
+  ring.dedup();
+  if ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+    ring.pop();
+  }
+  if ring.len() < 4 || !(tolerance > 0.0 && tolerance.is_finite()) {
+    return ring;
+  }
+  let mut simplifier = Simplifier::new(ring, tolerance, grow);
+  simplifier.run();
+  simplifier.result
+}
+
+struct Simplifier {
+  ring: Vec<Point>,
+  /// the vertices moved by the tolerance away from the material
+  offsets: Vec<Point>,
+  tolerance: f64,
+  grow: bool,
+  result: Vec<Point>,
+  /// the ring vertex of each vertex of the result
+  indices: Vec<usize>,
+  /// edges of the result so far, which later edges must not cross
+  edges: Vec<(Point, Point)>,
+}
+
+impl Simplifier {
+  fn new(
+    ring: Vec<Point>,
+    tolerance: f64,
+    grow: bool,
+  ) -> Simplifier {
+    let n = ring.len();
+    let doubled_area: f64 = (0..n).map(|i| cross(ring[i], ring[(i + 1) % n])).sum();
+    // Left normals point out of counter-clockwise rings.
+    let away = if (doubled_area > 0.0) == grow {
+      1.0
+    } else {
+      -1.0
+    };
+    let normal = |a: Point, b: Point| {
+      let d = normalize(sub(b, a));
+      Point {
+        x: away * d.y,
+        y: -away * d.x,
+      }
+    };
+    let offsets = (0..n)
+      .map(|i| {
+        let (previous, p, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let mut direction = normalize(add(normal(previous, p), normal(p, next)));
+        if direction == (Point { x: 0.0, y: 0.0 }) {
+          // A spike that turns back on itself is extended beyond its tip.
+          direction = normalize(sub(p, previous));
+        }
+        add(p, scale(direction, tolerance))
+      })
+      .collect();
+    Simplifier {
+      ring,
+      offsets,
+      tolerance,
+      grow,
+      result: vec![],
+      indices: vec![],
+      edges: vec![],
+    }
+  }
+
+  /// Greedily takes the longest valid edge from the current vertex. Each vertex of
+  /// the result is the offset point of a ring vertex, or the ring vertex itself if
+  /// the offset point cannot be used. Vertices are only taken if the result can be
+  /// continued from them. Every edge is checked with `is_valid`; if no edge to the
+  /// next ring vertex is valid, the result is the unchanged ring.
+  fn run(&mut self) {
+    let n = self.ring.len();
+    let start = self.ring[0];
+    self.result.push(start);
+    self.indices.push(0);
+    let (mut index, mut current) = (0, start);
+    while index < n {
+      // The gallop doubles the reach while edges fit, then bisects to the longest.
+      let mut good = index + 1;
+      let Some(mut best) = self.target(current, index, good).or_else(|| {
+        let vertex = self.ring[good % n];
+        self
+          .is_valid(current, vertex, index, good, &[])
+          .then_some(vertex)
+      }) else {
+        // No checked edge continues the result, so the ring itself is the result.
+        self.result = self.ring.clone();
+        self.indices = (0..n).collect();
+        return;
+      };
+      let mut bad = None;
+      let mut step = 1;
+      while bad.is_none() && good < n {
+        let to = (good + step).min(n);
+        match self.target(current, index, to) {
+          Some(vertex) => {
+            good = to;
+            best = vertex;
+            step *= 2;
+          }
+          None => bad = Some(to),
+        }
+      }
+      if let Some(mut bad) = bad {
+        while bad - good > 1 {
+          let to = (good + bad) / 2;
+          match self.target(current, index, to) {
+            Some(vertex) => {
+              good = to;
+              best = vertex;
+            }
+            None => bad = to,
+          }
+        }
+      }
+      self.edges.push((current, best));
+      if good < n {
+        self.result.push(best);
+        self.indices.push(good);
+      }
+      index = good;
+      current = best;
+    }
+    self.move_start();
+  }
+
+  /// The vertex for ring vertex `to` if an edge from `from` (at ring vertex `index`)
+  /// to it is valid and the result can be continued from it.
+  fn target(
+    &self,
+    from: Point,
+    index: usize,
+    to: usize,
+  ) -> Option<Point> {
+    let n = self.ring.len();
+    if to == n {
+      return self
+        .is_valid(from, self.ring[0], index, to, &[])
+        .then_some(self.ring[0]);
+    }
+    let continues = |vertex: Point| {
+      let next = to + 1;
+      if next == n {
+        self.is_valid(vertex, self.ring[0], to, next, &[])
+      } else {
+        self.is_valid(vertex, self.offsets[next], to, next, &[])
+          || self.is_valid(vertex, self.ring[next], to, next, &[])
+      }
+    };
+    [self.offsets[to], self.ring[to]]
+      .into_iter()
+      .find(|&vertex| self.is_valid(from, vertex, index, to, &[]) && continues(vertex))
+  }
+
+  /// Replaces the start, which is a ring vertex, by its offset point if both of its
+  /// edges stay valid.
+  fn move_start(&mut self) {
+    let count = self.result.len();
+    if count < 3 {
+      return;
+    }
+    let (previous, next, offset) = (self.result[count - 1], self.result[1], self.offsets[0]);
+    let (last, first) = (self.indices[count - 1], self.indices[1]);
+    let skip = [self.edges[0], self.edges[self.edges.len() - 1]];
+    if self.is_valid(previous, offset, last, self.ring.len(), &skip)
+      && self.is_valid(offset, next, 0, first, &skip)
+    {
+      self.result[0] = offset;
+    }
+  }
+
+  /// Whether the edge from `a` to `b` may replace ring vertices `from` to `to`: the
+  /// vertices in between are within the tolerance, the edge does not enter the
+  /// material and does not cross the result, apart from the edges in `skip`.
+  fn is_valid(
+    &self,
+    a: Point,
+    b: Point,
+    from: usize,
+    to: usize,
+    skip: &[(Point, Point)],
+  ) -> bool {
+    let n = self.ring.len();
+    if a == b {
+      return false;
+    }
+    let within =
+      (from + 1..to).all(|k| distance_to_segment(self.ring[k % n], a, b) <= self.tolerance);
+    // An edge of the ring is on its boundary; the midpoint test of `stays_out` could
+    // misjudge it by rounding.
+    let on_ring = to == from + 1 && a == self.ring[from % n] && b == self.ring[to % n];
+    within
+      && (on_ring || self.stays_out(a, b))
+      && !self
+        .edges
+        .iter()
+        .filter(|edge| !skip.contains(edge))
+        .any(|&(c, d)| crosses(a, b, c, d))
+  }
+
+  /// Whether the segment from `a` to `b` lies outside the material, or on its
+  /// boundary. The segment is split where it touches the ring, and every piece must
+  /// be on the side away from the material.
+  fn stays_out(
+    &self,
+    a: Point,
+    b: Point,
+  ) -> bool {
+    let n = self.ring.len();
+    let ab = sub(b, a);
+    let mut contacts = vec![0.0, 1.0];
+    for i in 0..n {
+      let (c, d) = (self.ring[i], self.ring[(i + 1) % n]);
+      if crosses(a, b, c, d) {
+        return false;
+      }
+      if orientation(a, b, c) == 0.0 {
+        let t = dot(sub(c, a), ab) / dot(ab, ab);
+        if t > 0.0 && t < 1.0 {
+          contacts.push(t);
+        }
+      }
+    }
+    contacts.sort_by(f64::total_cmp);
+    contacts.windows(2).filter(|t| t[1] > t[0]).all(|t| {
+      let middle = add(a, scale(ab, 0.5 * (t[0] + t[1])));
+      match point_in_ring(middle, &self.ring) {
+        Some(inside) => inside != self.grow,
+        None => true,
+      }
+    })
+  }
+}
+
+/// Simplifies the outer contour and the holes of `part` together, with the
+/// Visvalingam-Whyatt order: vertices spanning the smallest triangles with their
+/// neighbours are removed first.
+///
+/// A vertex is only removed if the removed vertices stay within `tolerance` of the
+/// new edge and no vertex of any ring lies in the triangle that is cut off or added.
+/// So if the rings of `part` are simple and apart, the rings of the result are too,
+/// and every hole stays inside the outer contour. Each ring keeps at least three
+/// vertices, and the offsets of `part` are kept.
+pub fn simplify_part(
+  part: &Polygon,
+  tolerance: f64,
+) -> Polygon {
+  let mut rings = vec![part.points.clone()];
+  rings.extend(part.children.iter().flatten().cloned());
+  for ring in rings.iter_mut() {
+    ring.dedup();
+    if ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+      ring.pop();
+    }
+  }
+  if tolerance > 0.0 && tolerance.is_finite() {
+    let mut network = Network::new(&rings);
+    while network.remove_vertices(tolerance) {}
+    rings = network.rings();
+  }
+  let points = rings.remove(0);
+  Polygon {
+    points,
+    children: part.children.as_ref().map(|_| rings),
+    offsetx: part.offsetx,
+    offsety: part.offsety,
+  }
+}
+
+/// The vertices of all rings, linked to their neighbours in the rings.
+struct Network {
+  points: Vec<Point>,
+  previous: Vec<usize>,
+  next: Vec<usize>,
+  removed: Vec<bool>,
+  /// the range of the vertices of each ring in `points`
+  rings: Vec<(usize, usize)>,
+  /// the ring of each vertex
+  ring_of: Vec<usize>,
+  /// vertices left per ring
+  counts: Vec<usize>,
+  /// changes of the triangle of each vertex, to skip outdated entries of the heap
+  versions: Vec<usize>,
+  grid: Grid,
+}
+
+impl Network {
+  fn new(rings: &[Vec<Point>]) -> Network {
+    let mut network = Network {
+      points: vec![],
+      previous: vec![],
+      next: vec![],
+      removed: vec![],
+      rings: vec![],
+      ring_of: vec![],
+      counts: vec![],
+      versions: vec![],
+      grid: Grid::new(&[]),
+    };
+    for (r, ring) in rings.iter().enumerate() {
+      let (start, n) = (network.points.len(), ring.len());
+      for (i, p) in ring.iter().enumerate() {
+        network.points.push(*p);
+        network.previous.push(start + (i + n - 1) % n);
+        network.next.push(start + (i + 1) % n);
+        network.removed.push(false);
+        network.ring_of.push(r);
+        network.versions.push(0);
+      }
+      network.rings.push((start, start + n));
+      network.counts.push(n);
+    }
+    network.grid = Grid::new(&network.points);
+    network
+  }
+
+  /// Removes the vertices that can be removed, smallest triangles first, and tells
+  /// whether any were. The triangles of the neighbours of a removed vertex are
+  /// updated in the heap. Removals can unblock vertices that are not neighbours, so
+  /// this is repeated.
+  fn remove_vertices(
+    &mut self,
+    tolerance: f64,
+  ) -> bool {
+    let mut heap: BinaryHeap<Candidate> = (0..self.points.len())
+      .filter(|&v| !self.removed[v])
+      .map(|v| self.candidate(v))
+      .collect();
+    let mut any = false;
+    while let Some(candidate) = heap.pop() {
+      let v = candidate.vertex;
+      if self.removed[v] || candidate.version != self.versions[v] || !self.can_remove(v, tolerance)
+      {
+        continue;
+      }
+      let (u, w) = (self.previous[v], self.next[v]);
+      self.next[u] = w;
+      self.previous[w] = u;
+      self.removed[v] = true;
+      self.counts[self.ring_of[v]] -= 1;
+      any = true;
+      for neighbour in [u, w] {
+        self.versions[neighbour] += 1;
+        heap.push(self.candidate(neighbour));
+      }
+    }
+    any
+  }
+
+  fn candidate(
+    &self,
+    v: usize,
+  ) -> Candidate {
+    let (u, w) = (self.previous[v], self.next[v]);
+    Candidate {
+      area: orientation(self.points[u], self.points[v], self.points[w]).abs(),
+      vertex: v,
+      version: self.versions[v],
+    }
+  }
+
+  fn can_remove(
+    &self,
+    v: usize,
+    tolerance: f64,
+  ) -> bool {
+    if self.counts[self.ring_of[v]] <= 3 {
+      return false;
+    }
+    let (u, w) = (self.previous[v], self.next[v]);
+    let (a, b, c) = (self.points[u], self.points[v], self.points[w]);
+
+    // The vertices removed before between `u` and `w` are replaced by the new edge too.
+    let (start, end) = self.rings[self.ring_of[v]];
+    let successor = |k: usize| if k + 1 == end { start } else { k + 1 };
+    let mut k = successor(u);
+    while k != w {
+      if distance_to_segment(self.points[k], a, c) > tolerance {
+        return false;
+      }
+      k = successor(k);
+    }
+
+    // Another vertex in the triangle would end up on the other side of the ring, and
+    // every edge that the new edge would cross has such a vertex.
+    let turn = orientation(a, b, c);
+    let min = Point {
+      x: a.x.min(b.x).min(c.x),
+      y: a.y.min(b.y).min(c.y),
+    };
+    let max = Point {
+      x: a.x.max(b.x).max(c.x),
+      y: a.y.max(b.y).max(c.y),
+    };
+    self.grid.within(min, max).all(|x| {
+      let p = self.points[x];
+      if self.removed[x] || x == u || x == v || x == w {
+        return true;
+      }
+      if p.x < min.x || p.x > max.x || p.y < min.y || p.y > max.y {
+        return true;
+      }
+      if turn == 0.0 {
+        // A straight vertex only cuts off the edges through it.
+        return !(orientation(a, c, p) == 0.0 && (p == b || distance_to_segment(p, a, c) == 0.0));
+      }
+      let sides = [
+        orientation(a, b, p) * turn,
+        orientation(b, c, p) * turn,
+        orientation(c, a, p) * turn,
+      ];
+      sides.iter().any(|&side| side < 0.0)
+    })
+  }
+
+  fn rings(&self) -> Vec<Vec<Point>> {
+    self
+      .rings
+      .iter()
+      .map(|&(start, end)| {
+        let Some(first) = (start..end).find(|&v| !self.removed[v]) else {
+          return vec![];
+        };
+        let mut ring = vec![self.points[first]];
+        let mut v = self.next[first];
+        while v != first {
+          ring.push(self.points[v]);
+          v = self.next[v];
+        }
+        ring
+      })
+      .collect()
+  }
+}
+
+/// A vertex in the heap of `Network::remove_vertices`, ordered so that the smallest
+/// triangle comes first.
+struct Candidate {
+  area: f64,
+  vertex: usize,
+  version: usize,
+}
+
+impl PartialEq for Candidate {
+  fn eq(
+    &self,
+    other: &Self,
+  ) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+  fn partial_cmp(
+    &self,
+    other: &Self,
+  ) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Candidate {
+  fn cmp(
+    &self,
+    other: &Self,
+  ) -> Ordering {
+    // `BinaryHeap` is a max-heap, so the order is reversed.
+    other
+      .area
+      .total_cmp(&self.area)
+      .then(other.vertex.cmp(&self.vertex))
+  }
+}
+
+/// A uniform grid over points, with about one point per cell, to find the points
+/// in a box without visiting all of them.
+struct Grid {
+  min: Point,
+  size: f64,
+  columns: usize,
+  rows: usize,
+  cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+  fn new(points: &[Point]) -> Grid {
+    let (mut min, mut max) = (
+      Point {
+        x: f64::INFINITY,
+        y: f64::INFINITY,
+      },
+      Point {
+        x: f64::NEG_INFINITY,
+        y: f64::NEG_INFINITY,
+      },
+    );
+    for p in points {
+      min = Point {
+        x: min.x.min(p.x),
+        y: min.y.min(p.y),
+      };
+      max = Point {
+        x: max.x.max(p.x),
+        y: max.y.max(p.y),
+      };
+    }
+    let count = points.len().max(1) as f64;
+    let (width, height) = (max.x - min.x, max.y - min.y);
+    // Cells of at least the area per point, and not more than `count` of them along
+    // the longer side, so flat boxes do not get too many cells.
+    let mut size = (width * height / count)
+      .sqrt()
+      .max(width.max(height) / count);
+    if !(size > 0.0 && size.is_finite()) {
+      // There are no points, or they are all the same.
+      size = 1.0;
+    }
+    let mut grid = Grid {
+      min,
+      size,
+      columns: 1,
+      rows: 1,
+      cells: vec![],
+    };
+    if !points.is_empty() {
+      // Only infinite coordinates would need more cells.
+      let (column, row) = grid.cell(max);
+      grid.columns = column.min(points.len()) + 1;
+      grid.rows = row.min(points.len()) + 1;
+    }
+    grid.cells = vec![vec![]; grid.columns * grid.rows];
+    for (i, p) in points.iter().enumerate() {
+      let (column, row) = grid.cell(*p);
+      let (column, row) = (column.min(grid.columns - 1), row.min(grid.rows - 1));
+      grid.cells[row * grid.columns + column].push(i);
+    }
+    grid
+  }
+
+  /// The column and row of the cell of `p`, which may be outside the grid.
+  fn cell(
+    &self,
+    p: Point,
+  ) -> (usize, usize) {
+    // The casts saturate, and map NaN to 0.
+    (
+      ((p.x - self.min.x) / self.size).floor() as usize,
+      ((p.y - self.min.y) / self.size).floor() as usize,
+    )
+  }
+
+  /// The points in the cells that overlap the box from `min` to `max`, and possibly
+  /// a few more around it.
+  fn within(
+    &self,
+    min: Point,
+    max: Point,
+  ) -> impl Iterator<Item = usize> + '_ {
+    let (first_column, first_row) = self.cell(min);
+    let (last_column, last_row) = self.cell(max);
+    let (last_column, last_row) = (
+      last_column.min(self.columns - 1),
+      last_row.min(self.rows - 1),
+    );
+    (first_row..=last_row).flat_map(move |row| {
+      (first_column..=last_column)
+        .flat_map(move |column| self.cells[row * self.columns + column].iter().copied())
+    })
+  }
+}
+
+/// Whether the segments cross at a single point inside both of them.
+fn crosses(
+  a: Point,
+  b: Point,
+  c: Point,
+  d: Point,
+) -> bool {
+  let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+  let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+  o1 * o2 < 0.0 && o3 * o4 < 0.0
+}
+
+/// Whether `p` is strictly inside the ring, `None` if it is on an edge.
+fn point_in_ring(
+  p: Point,
+  ring: &[Point],
+) -> Option<bool> {
+  let n = ring.len();
+  let mut inside = false;
+  for i in 0..n {
+    let (a, b) = (ring[i], ring[(i + 1) % n]);
+    if orientation(a, b, p) == 0.0
+      && p.x >= a.x.min(b.x)
+      && p.x <= a.x.max(b.x)
+      && p.y >= a.y.min(b.y)
+      && p.y <= a.y.max(b.y)
+    {
+      return None;
+    }
+    if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (b.x - a.x) * (p.y - a.y) / (b.y - a.y) {
+      inside = !inside;
+    }
+  }
+  Some(inside)
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`; positive if it turns left.
+fn orientation(
+  a: Point,
+  b: Point,
+  c: Point,
+) -> f64 {
+  cross(sub(b, a), sub(c, a))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::vector::length;
+
+  fn point(
+    x: f64,
+    y: f64,
+  ) -> Point {
+    Point { x, y }
+  }
+
+  /// A gear with `teeth` teeth between the radii 40 and 50, sampled densely, with a
+  /// little noise on the radius.
+  fn gear(teeth: usize) -> Vec<Point> {
+    (0..teeth * 60)
+      .map(|i| {
+        let angle = (i as f64 / (teeth * 60) as f64) * std::f64::consts::TAU;
+        let radius = if (i / 30) % 2 == 0 { 50.0 } else { 40.0 };
+        let noise = 0.01 * ((i * 7919) % 11) as f64 / 11.0;
+        point(
+          (radius + noise) * angle.cos(),
+          (radius + noise) * angle.sin(),
+        )
+      })
+      .collect()
+  }
+
+  /// Checks that `inner` lies within `outer`: no edges cross and no vertex of
+  /// `inner` is strictly outside `outer`.
+  fn assert_contains(
+    outer: &[Point],
+    inner: &[Point],
+  ) {
+    for i in 0..inner.len() {
+      let (a, b) = (inner[i], inner[(i + 1) % inner.len()]);
+      for j in 0..outer.len() {
+        assert!(!crosses(a, b, outer[j], outer[(j + 1) % outer.len()]));
+      }
+      assert_ne!(point_in_ring(a, outer), Some(false));
+    }
+  }
+
+  #[test]
+  fn test_outer_contours_grow() {
+    let ring = gear(12);
+    let simplified = simplify_conservative(&ring, 0.5, true);
+    assert!(simplified.len() * 4 < ring.len(), "{}", simplified.len());
+    assert_contains(&simplified, &ring);
+    // Each vertex of the result stays within the tolerance of the original.
+    for p in simplified.iter() {
+      let nearest = ring
+        .iter()
+        .map(|q| length(sub(*p, *q)))
+        .fold(f64::INFINITY, f64::min);
+      assert!(nearest <= 0.5 + 1e-9);
+    }
+    // The orientation is kept.
+    let area = |ring: &[Point]| -> f64 {
+      (0..ring.len())
+        .map(|i| cross(ring[i], ring[(i + 1) % ring.len()]))
+        .sum()
+    };
+    assert!(area(&simplified) > area(&ring));
+  }
+
+  #[test]
+  fn test_holes_shrink() {
+    let mut ring = gear(8);
+    ring.reverse();
+    let simplified = simplify_conservative(&ring, 0.5, false);
+    assert!(simplified.len() * 4 < ring.len(), "{}", simplified.len());
+    assert_contains(&ring, &simplified);
+
+    // Tolerances that cannot be used leave the ring as it is.
+    let square = [
+      point(0.0, 0.0),
+      point(10.0, 0.0),
+      point(10.0, 10.0),
+      point(0.0, 10.0),
+    ];
+    assert_eq!(simplify_conservative(&square, 0.0, true), square);
+  }
+
+  #[test]
+  fn test_simplify_part_keeps_holes_inside() {
+    // A noisy circle of radius 50 with a hole of radius 48, closer to the outer
+    // contour than the tolerance allows the rings to move independently.
+    let circle = |radius: f64, count: usize, phase: f64| -> Vec<Point> {
+      (0..count)
+        .map(|i| {
+          let angle = (i as f64 + phase) / count as f64 * std::f64::consts::TAU;
+          let noise = 0.2 * ((i * 7919) % 7) as f64 / 7.0;
+          point(
+            (radius + noise) * angle.cos(),
+            (radius + noise) * angle.sin(),
+          )
+        })
+        .collect()
+    };
+    let mut hole = circle(48.0, 300, 0.5);
+    hole.reverse();
+    let part = Polygon {
+      points: circle(50.0, 400, 0.0),
+      children: Some(vec![hole]),
+      offsetx: Some(5.0),
+      offsety: None,
+    };
+    let simplified = simplify_part(&part, 3.0);
+    let outer = &simplified.points;
+    let holes = simplified.children.as_ref().unwrap();
+    assert!(outer.len() * 4 < part.points.len(), "{}", outer.len());
+    assert!(holes[0].len() * 4 < 300, "{}", holes[0].len());
+    assert_eq!(simplified.offsetx, Some(5.0));
+
+    // No ring crosses itself or the other, and the hole is inside.
+    let rings = [outer.clone(), holes[0].clone()];
+    for (r, ring) in rings.iter().enumerate() {
+      for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        for (s, other) in rings.iter().enumerate() {
+          for j in 0..other.len() {
+            let (c, d) = (other[j], other[(j + 1) % other.len()]);
+            if r != s || (i != j && a != d && b != c) {
+              assert!(!crosses(a, b, c, d), "{:?} {:?}", (a, b), (c, d));
+            }
+          }
+        }
+      }
+    }
+    assert!(holes[0]
+      .iter()
+      .all(|p| point_in_ring(*p, outer) == Some(true)));
+  }
+
+  #[test]
+  fn test_random_rings() {
+    // Jagged star-shaped rings, whose offset points often cannot be used.
+    let mut seed: u64 = 12345;
+    let mut random = || {
+      seed = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+      (seed >> 33) as f64 / (1u64 << 31) as f64
+    };
+    for case in 0..100 {
+      let n = 20 + case % 60;
+      let ring: Vec<Point> = (0..n)
+        .map(|i| {
+          let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+          let radius = 10.0 + 6.0 * random();
+          point(radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+      for tolerance in [0.3, 3.0] {
+        let grown = simplify_conservative(&ring, tolerance, true);
+        assert_contains(&grown, &ring);
+        let shrunk = simplify_conservative(&ring, tolerance, false);
+        assert_contains(&ring, &shrunk);
+        // Every edge of the ring fits, so the ring is never given up on.
+        assert_ne!(grown, ring);
+        assert_ne!(shrunk, ring);
+      }
+    }
+  }
+
+  #[test]
+  fn test_grid() {
+    let points: Vec<Point> = (0..500)
+      .map(|i| point(((i * 7919) % 101) as f64, ((i * 104729) % 37) as f64 * 0.5))
+      .collect();
+    let grid = Grid::new(&points);
+    assert!(grid.columns * grid.rows <= 3 * points.len() + 2);
+    let (min, max) = (point(10.5, 3.0), point(40.0, 9.25));
+    let mut found: Vec<usize> = grid
+      .within(min, max)
+      .filter(|&i| {
+        let p = points[i];
+        p.x >= min.x && p.x <= max.x && p.y >= min.y && p.y <= max.y
+      })
+      .collect();
+    found.sort();
+    let expected: Vec<usize> = (0..points.len())
+      .filter(|&i| {
+        let p = points[i];
+        p.x >= min.x && p.x <= max.x && p.y >= min.y && p.y <= max.y
+      })
+      .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(found, expected);
+
+    // Points on a line and a single point get a grid too.
+    let line = [point(0.0, 1.0), point(5.0, 1.0), point(9.0, 1.0)];
+    let grid = Grid::new(&line);
+    assert_eq!(grid.within(point(4.0, 0.0), point(9.0, 2.0)).count(), 2);
+    let grid = Grid::new(&[point(3.0, 3.0)]);
+    assert_eq!(grid.within(point(3.0, 3.0), point(3.0, 3.0)).count(), 1);
+  }
+}
@@ -0,0 +1,494 @@
+use deepnest_types::types::{Point, Polygon};
+use derive_more::{From, Into};
+
+use crate::geometryutils::GeometryUtils;
+
+/// Upper bound for the number of 2-opt improvement passes.
+const MAX_TWO_OPT_PASSES: usize = 50;
+
+#[derive(Debug, Clone, Copy, From, Into)]
+#[cfg_attr(feature = "node", napi)]
+pub struct Toolpath;
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct ToolpathOptions {
+  /// position of the tool before the first cut, defaults to (0, 0)
+  pub origin: Option<Point>,
+  /// length of the lead-in move into the contour, no lead-in if unset or zero
+  pub lead_in: Option<f64>,
+  /// length of the lead-out move off the contour, no lead-out if unset or zero
+  pub lead_out: Option<f64>,
+}
+
+/// A single closed contour to cut.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct Cut {
+  /// index of the placed part
+  pub part: u32,
+  /// index of the hole in the part's `children`, `None` for the outer contour
+  pub hole: Option<u32>,
+  /// the contour starting and ending at the chosen start point
+  pub path: Vec<Point>,
+  /// point where the tool pierces before moving onto the start point
+  pub lead_in: Option<Point>,
+  /// point where the tool leaves after returning to the start point
+  pub lead_out: Option<Point>,
+  /// rapid travel from the previous cut (or the origin) to this one
+  pub rapid_distance: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct ToolpathResult {
+  /// contours in cutting order
+  pub cuts: Vec<Cut>,
+  /// total rapid travel
+  pub rapid_distance: f64,
+  /// longest single rapid move
+  pub longest_rapid: f64,
+  /// total length of the contours, excluding lead-ins and lead-outs
+  pub cut_distance: f64,
+}
+
+/// A contour of a placed part, with the offsets applied.
+/// Outer contours are counter-clockwise (negative `polygon_area`), holes clockwise,
+/// so the part material is always on the left of the cutting direction.
+struct Contour {
+  part: usize,
+  hole: Option<usize>,
+  points: Vec<Point>,
+}
+
+/// The containment constraints between contours, as adjacency lists.
+struct Precedence {
+  /// `inner[b]` lists the contours lying inside contour `b`, which have to be cut first.
+  inner: Vec<Vec<usize>>,
+  /// `outer[a]` lists the contours that contour `a` lies inside.
+  outer: Vec<Vec<usize>>,
+}
+
+#[cfg_attr(feature = "node", napi)]
+impl Toolpath {
+  /// Orders the contours of the placed parts for cutting.
+  ///
+  /// A contour is only cut once every contour inside it is done, so holes come before
+  /// their outer contour and parts placed in holes before the surrounding part.
+  /// Within these constraints the order is built nearest-neighbour first and improved
+  /// with 2-opt to minimize the rapid travel. Each contour starts at its vertex closest
+  /// to the previous tool position; lead-ins and lead-outs point away from the material.
+  #[napi]
+  pub fn order_cuts(
+    placements: Vec<Polygon>,
+    options: Option<ToolpathOptions>,
+  ) -> ToolpathResult {
+    let options = options.unwrap_or_default();
+    let origin = options.origin.unwrap_or(Point { x: 0.0, y: 0.0 });
+    let contours = Toolpath::collect_contours(&placements);
+    let precedence = Toolpath::precedence(&contours);
+
+    let order = Toolpath::nearest_neighbour(&contours, &precedence, origin);
+    let order = Toolpath::two_opt(&contours, &precedence, order, origin);
+
+    let lead = |length: Option<f64>, contour: &Contour, index: usize| {
+      length
+        .filter(|length| *length > 0.0)
+        .map(|length| lead_point(&contour.points, index, length))
+    };
+    let mut position = origin;
+    let mut cuts = Vec::with_capacity(order.len());
+    let mut rapid_distance = 0.0;
+    let mut longest_rapid: f64 = 0.0;
+    let mut cut_distance = 0.0;
+    for index in order {
+      let contour = &contours[index];
+      let start = nearest_vertex(&contour.points, position);
+      let lead_in = lead(options.lead_in, contour, start);
+      let lead_out = lead(options.lead_out, contour, start);
+
+      let n = contour.points.len();
+      let path: Vec<Point> = (0..=n).map(|i| contour.points[(start + i) % n]).collect();
+      cut_distance += path.windows(2).map(|w| distance(w[0], w[1])).sum::<f64>();
+
+      let rapid = distance(position, lead_in.unwrap_or(path[0]));
+      rapid_distance += rapid;
+      longest_rapid = longest_rapid.max(rapid);
+      position = lead_out.unwrap_or(path[0]);
+
+      cuts.push(Cut {
+        part: contour.part as u32,
+        hole: contour.hole.map(|hole| hole as u32),
+        path,
+        lead_in,
+        lead_out,
+        rapid_distance: rapid,
+      });
+    }
+
+    ToolpathResult {
+      cuts,
+      rapid_distance,
+      longest_rapid,
+      cut_distance,
+    }
+  }
+}
+
+impl Toolpath {
+  fn collect_contours(placements: &[Polygon]) -> Vec<Contour> {
+    let mut contours = vec![];
+    for (part, polygon) in placements.iter().enumerate() {
+      let offsetx = polygon.offsetx.unwrap_or(0.0);
+      let offsety = polygon.offsety.unwrap_or(0.0);
+      let outer = std::iter::once((None, &polygon.points));
+      let holes = polygon
+        .children
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, hole)| (Some(i), hole));
+      for (hole, points) in outer.chain(holes) {
+        let mut points: Vec<Point> = points
+          .iter()
+          .map(|p| Point {
+            x: p.x + offsetx,
+            y: p.y + offsety,
+          })
+          .collect();
+        if points.len() > 1 && points[0] == points[points.len() - 1] {
+          points.pop();
+        }
+        if points.len() < 3 {
+          continue;
+        }
+        let counter_clockwise = GeometryUtils::polygon_area(points.clone()) < 0.0;
+        if counter_clockwise == hole.is_some() {
+          points.reverse();
+        }
+        contours.push(Contour { part, hole, points });
+      }
+    }
+    contours
+  }
+
+  /// Finds for every contour the contours lying inside it, which have to be cut first.
+  fn precedence(contours: &[Contour]) -> Precedence {
+    let bounds: Vec<_> = contours
+      .iter()
+      .map(|contour| GeometryUtils::get_polygon_bounds(contour.points.clone()))
+      .collect();
+
+    let mut precedence = Precedence {
+      inner: vec![vec![]; contours.len()],
+      outer: vec![vec![]; contours.len()],
+    };
+    for (a, inner) in contours.iter().enumerate() {
+      for (b, outer) in contours.iter().enumerate() {
+        if a == b {
+          continue;
+        }
+        // The holes of a part always lie inside its outer contour.
+        let inside = if inner.part == outer.part {
+          inner.hole.is_some() && outer.hole.is_none()
+        } else {
+          let (Some(inner_bounds), Some(outer_bounds)) = (bounds[a], bounds[b]) else {
+            continue;
+          };
+          if inner_bounds.x < outer_bounds.x
+            || inner_bounds.y < outer_bounds.y
+            || inner_bounds.x + inner_bounds.width > outer_bounds.x + outer_bounds.width
+            || inner_bounds.y + inner_bounds.height > outer_bounds.y + outer_bounds.height
+          {
+            continue;
+          }
+          // Parts do not overlap, so the first vertex off the boundary decides.
+          inner
+            .points
+            .iter()
+            .find_map(|p| GeometryUtils::point_in_points(*p, &outer.points, 0.0, 0.0, None))
+            .unwrap_or(false)
+        };
+        if inside {
+          precedence.inner[b].push(a);
+          precedence.outer[a].push(b);
+        }
+      }
+    }
+    precedence
+  }
+
+  /// Greedily picks the closest contour whose inner contours are all cut.
+  fn nearest_neighbour(
+    contours: &[Contour],
+    precedence: &Precedence,
+    origin: Point,
+  ) -> Vec<usize> {
+    let n = contours.len();
+    // Number of inner contours of each contour that are not cut yet.
+    let mut pending: Vec<usize> = precedence.inner.iter().map(Vec::len).collect();
+    let mut ready: Vec<usize> = (0..n).filter(|&b| pending[b] == 0).collect();
+    let mut done = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut position = origin;
+    while order.len() < n {
+      let next = ready
+        .iter()
+        .enumerate()
+        .map(|(slot, &b)| {
+          let start = nearest_vertex(&contours[b].points, position);
+          (slot, distance(position, contours[b].points[start]))
+        })
+        .min_by(|x, y| x.1.total_cmp(&y.1));
+      // The containment relation is acyclic for non-overlapping parts; bail out otherwise.
+      let Some((slot, _)) = next else {
+        order.extend((0..n).filter(|&b| !done[b]));
+        break;
+      };
+      let next = ready.swap_remove(slot);
+      done[next] = true;
+      for &b in precedence.outer[next].iter() {
+        pending[b] -= 1;
+        if pending[b] == 0 {
+          ready.push(b);
+        }
+      }
+      position = contours[next].points[nearest_vertex(&contours[next].points, position)];
+      order.push(next);
+    }
+    order
+  }
+
+  /// Reverses sub-sequences of the order while that shortens the rapid travel and
+  /// keeps every inner contour ahead of its outer one.
+  fn two_opt(
+    contours: &[Contour],
+    precedence: &Precedence,
+    mut order: Vec<usize>,
+    origin: Point,
+  ) -> Vec<usize> {
+    let n = order.len();
+    if n < 3 {
+      return order;
+    }
+    // Use the start points chosen by the nearest-neighbour pass.
+    let mut starts: Vec<Point> = vec![origin; contours.len()];
+    let mut position = origin;
+    for &index in order.iter() {
+      position = contours[index].points[nearest_vertex(&contours[index].points, position)];
+      starts[index] = position;
+    }
+    // `rank[c]` is the position of contour `c` in `order`.
+    let mut rank = vec![0; contours.len()];
+    for (position, &index) in order.iter().enumerate() {
+      rank[index] = position;
+    }
+
+    for _ in 0..MAX_TWO_OPT_PASSES {
+      let mut improved = false;
+      for i in 0..n - 1 {
+        let previous = if i == 0 { origin } else { starts[order[i - 1]] };
+        for j in i + 1..n {
+          // Reversing flips every pair inside the range, so none may be constrained.
+          if precedence.inner[order[j]]
+            .iter()
+            .any(|&a| (i..j).contains(&rank[a]))
+          {
+            break;
+          }
+          let first = starts[order[i]];
+          let last = starts[order[j]];
+          let mut delta = distance(previous, last) - distance(previous, first);
+          if j + 1 < n {
+            let next = starts[order[j + 1]];
+            delta += distance(first, next) - distance(last, next);
+          }
+          if delta < -1e-9 {
+            order[i..=j].reverse();
+            for k in i..=j {
+              rank[order[k]] = k;
+            }
+            improved = true;
+            break;
+          }
+        }
+      }
+      if !improved {
+        break;
+      }
+    }
+    order
+  }
+}
+
+fn distance(
+  a: Point,
+  b: Point,
+) -> f64 {
+  ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+}
+
+fn nearest_vertex(
+  points: &[Point],
+  position: Point,
+) -> usize {
+  (0..points.len())
+    .min_by(|&a, &b| distance(points[a], position).total_cmp(&distance(points[b], position)))
+    .unwrap_or(0)
+}
+
+/// A point `length` away from vertex `index`, on the scrap side of the contour along the
+/// bisector of its two edges.
+fn lead_point(
+  points: &[Point],
+  index: usize,
+  length: f64,
+) -> Point {
+  let n = points.len();
+  let previous = points[(index + n - 1) % n];
+  let current = points[index];
+  let next = points[(index + 1) % n];
+  // The material is on the left, so the right-hand normals point at the scrap.
+  let right_normal = |a: Point, b: Point| {
+    GeometryUtils::normalize_vector(Point {
+      x: b.y - a.y,
+      y: a.x - b.x,
+    })
+  };
+  let incoming = right_normal(previous, current);
+  let outgoing = right_normal(current, next);
+  let mut direction = Point {
+    x: incoming.x + outgoing.x,
+    y: incoming.y + outgoing.y,
+  };
+  if direction.x.hypot(direction.y) < 1e-9 {
+    direction = outgoing;
+  }
+  let direction = GeometryUtils::normalize_vector(direction);
+  Point {
+    x: current.x + direction.x * length,
+    y: current.y + direction.y * length,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(
+    x: f64,
+    y: f64,
+    size: f64,
+  ) -> Vec<Point> {
+    vec![
+      Point { x, y },
+      Point { x: x + size, y },
+      Point {
+        x: x + size,
+        y: y + size,
+      },
+      Point { x, y: y + size },
+    ]
+  }
+
+  fn part(
+    points: Vec<Point>,
+    holes: Vec<Vec<Point>>,
+  ) -> Polygon {
+    Polygon {
+      points,
+      children: (!holes.is_empty()).then_some(holes),
+      offsetx: None,
+      offsety: None,
+    }
+  }
+
+  #[test]
+  fn test_order_cuts_holes_first() {
+    let placements = vec![
+      part(
+        square(0.0, 0.0, 10.0),
+        vec![square(6.0, 6.0, 2.0), square(2.0, 2.0, 2.0)],
+      ),
+      // A small part placed inside the first hole.
+      part(square(6.5, 6.5, 1.0), vec![]),
+      part(square(20.0, 0.0, 5.0), vec![]),
+    ];
+    let result = Toolpath::order_cuts(placements, None);
+    let order: Vec<(u32, Option<u32>)> = result.cuts.iter().map(|c| (c.part, c.hole)).collect();
+    let position = |key: (u32, Option<u32>)| order.iter().position(|k| *k == key).unwrap();
+    assert_eq!(order.len(), 5);
+    assert!(position((0, Some(0))) < position((0, None)));
+    assert!(position((0, Some(1))) < position((0, None)));
+    assert!(position((1, None)) < position((0, Some(0))));
+    // The closest hole is cut first.
+    assert_eq!(order[0], (0, Some(1)));
+    assert!(result
+      .cuts
+      .iter()
+      .all(|c| c.path[0] == c.path[c.path.len() - 1]));
+    assert!(GeometryUtils::almost_equal(
+      result.cut_distance,
+      40.0 + 8.0 + 8.0 + 4.0 + 20.0,
+      Some(1e-9)
+    ));
+  }
+
+  #[test]
+  fn test_order_cuts_minimizes_rapids() {
+    // Squares on a line, listed out of order.
+    let placements: Vec<Polygon> = [2.0, 0.0, 3.0, 1.0]
+      .iter()
+      .map(|x| part(square(x * 10.0, 0.0, 1.0), vec![]))
+      .collect();
+    let result = Toolpath::order_cuts(placements, None);
+    let parts: Vec<u32> = result.cuts.iter().map(|c| c.part).collect();
+    assert_eq!(parts, vec![1, 3, 0, 2]);
+    assert!(GeometryUtils::almost_equal(
+      result.rapid_distance,
+      30.0,
+      Some(1e-9)
+    ));
+  }
+
+  #[test]
+  fn test_lead_points_on_scrap_side() {
+    let options = ToolpathOptions {
+      origin: None,
+      lead_in: Some(1.0),
+      lead_out: Some(0.5),
+    };
+    let result = Toolpath::order_cuts(
+      vec![part(square(0.0, 0.0, 10.0), vec![square(4.0, 4.0, 2.0)])],
+      Some(options),
+    );
+    let outer = Polygon {
+      points: square(0.0, 0.0, 10.0),
+      children: None,
+      offsetx: None,
+      offsety: None,
+    };
+    let hole = Polygon {
+      points: square(4.0, 4.0, 2.0),
+      children: None,
+      offsetx: None,
+      offsety: None,
+    };
+    // The hole's lead-in lies inside the hole, the outer one outside the part.
+    let hole_lead = result.cuts[0].lead_in.unwrap();
+    assert_eq!(
+      GeometryUtils::point_in_polygon(hole_lead, hole, None),
+      Some(true)
+    );
+    let outer_lead = result.cuts[1].lead_in.unwrap();
+    assert_eq!(
+      GeometryUtils::point_in_polygon(outer_lead, outer, None),
+      Some(false)
+    );
+    assert!(GeometryUtils::almost_equal(
+      distance(outer_lead, result.cuts[1].path[0]),
+      1.0,
+      Some(1e-9)
+    ));
+  }
+}
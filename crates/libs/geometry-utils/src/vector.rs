@@ -0,0 +1,84 @@
+//! Small vector helpers on `Point`, shared by the simplification and curve fitting code.
+
+use deepnest_types::types::Point;
+
+pub fn add(
+  a: Point,
+  b: Point,
+) -> Point {
+  Point {
+    x: a.x + b.x,
+    y: a.y + b.y,
+  }
+}
+
+pub fn sub(
+  a: Point,
+  b: Point,
+) -> Point {
+  Point {
+    x: a.x - b.x,
+    y: a.y - b.y,
+  }
+}
+
+pub fn scale(
+  p: Point,
+  factor: f64,
+) -> Point {
+  Point {
+    x: p.x * factor,
+    y: p.y * factor,
+  }
+}
+
+pub fn dot(
+  a: Point,
+  b: Point,
+) -> f64 {
+  a.x * b.x + a.y * b.y
+}
+
+/// The z component of the cross product; positive if `b` turns left from `a`.
+pub fn cross(
+  a: Point,
+  b: Point,
+) -> f64 {
+  a.x * b.y - a.y * b.x
+}
+
+pub fn length(p: Point) -> f64 {
+  p.x.hypot(p.y)
+}
+
+pub fn distance(
+  a: Point,
+  b: Point,
+) -> f64 {
+  length(sub(a, b))
+}
+
+/// The unit vector in the direction of `p`, or `p` itself if it is zero.
+pub fn normalize(p: Point) -> Point {
+  let length = length(p);
+  if length == 0.0 {
+    p
+  } else {
+    scale(p, 1.0 / length)
+  }
+}
+
+/// The distance of `p` to the segment from `a` to `b`, which may be a single point.
+pub fn distance_to_segment(
+  p: Point,
+  a: Point,
+  b: Point,
+) -> f64 {
+  let ab = sub(b, a);
+  let squared = dot(ab, ab);
+  if squared == 0.0 {
+    return distance(p, a);
+  }
+  let t = (dot(sub(p, a), ab) / squared).clamp(0.0, 1.0);
+  distance(p, add(a, scale(ab, t)))
+}
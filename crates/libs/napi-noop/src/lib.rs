@@ -0,0 +1,13 @@
+//! A `#[napi]` attribute that leaves its item unchanged, for building the crates with
+//! Node.js bindings without their `node` feature.
+//!
+//! napi-derive only exports the methods of a `#[napi] impl` that carry a literal
+//! `#[napi]`, so those cannot be wrapped in `cfg_attr`. Without the `node` feature
+//! they resolve to this attribute instead.
+
+use proc_macro::TokenStream;
+
+#[proc_macro_attribute]
+pub fn napi(_attr: TokenStream, item: TokenStream) -> TokenStream {
+  item
+}
@@ -0,0 +1,42 @@
+use std::fmt::Write;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use deepnest_svg_preprocessor::{points_on_path, Linearizer, PathData, PathParser};
+
+/// Path data like a nesting export: many closed parts with lines, curves and arcs.
+fn drawing(parts: usize) -> String {
+  let mut d = String::new();
+  for i in 0..parts {
+    let x = (i % 100) as f64 * 120.5;
+    let y = (i / 100) as f64 * 80.25;
+    write!(
+      d,
+      "M{x} {y}h100.5v40c0 20-20 30-40 30s-30-10-40-30l-20.25-10q-5-15 0-20a10 10 0 0 1 10-10z",
+    )
+    .unwrap();
+  }
+  d
+}
+
+fn path_parsing(c: &mut Criterion) {
+  let mut group = c.benchmark_group("path_parsing");
+  for parts in [100, 10_000] {
+    let d = drawing(parts);
+    group.throughput(Throughput::Bytes(d.len() as u64));
+    group.bench_with_input(BenchmarkId::new("stream", parts), &d, |b, d| {
+      b.iter(|| PathParser::new(black_box(d)).filter(Result::is_ok).count())
+    });
+    group.bench_with_input(BenchmarkId::new("path_data", parts), &d, |b, d| {
+      b.iter(|| black_box(d).parse::<PathData>().unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("points_on_path", parts), &d, |b, d| {
+      let linearizer = Linearizer::default();
+      b.iter(|| points_on_path(black_box(d).clone(), &linearizer, None, None).unwrap())
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, path_parsing);
+criterion_main!(benches);
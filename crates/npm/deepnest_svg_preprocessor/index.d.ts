@@ -12,14 +12,265 @@ export interface Point {
   y: number
 }
 export declare function simplifyPolygon(points: Array<Point>, tolerance: number, highQuality: boolean): Array<Point>
-export declare function pointsOnSvgPath(path: string, tolerance?: number | undefined | null, distance?: number | undefined | null): Array<Array<Point>>
-export declare function pointsOnSvgPathWithClosedInfo(path: string, tolerance?: number | undefined | null, distance?: number | undefined | null): PathResult
+/**
+ * Simplifies a part without moving any edge into its material: the outer contour only
+ * grows and the holes only shrink, by at most `tolerance`, so parts nested with the
+ * result never overlap the original parts.
+ */
+export declare function simplifyPolygonConservative(polygon: Polygon, tolerance: number): Polygon
+/**
+ * Simplifies the outer contour and the holes of a part together, such that removed
+ * vertices stay within `tolerance`, no contour crosses itself or another one and
+ * the holes stay inside the outer contour.
+ */
+export declare function simplifyPart(polygon: Polygon, tolerance: number): Polygon
+/**
+ * Linearizes SVG path data into point sets. Errors are thrown with the codes of
+ * `PreprocessorError`, e.g. `PATH_DATA_ERROR` with the byte offset in the message.
+ * A command with missing parameters is an error wherever it is in the path.
+ * Arcs are approximated with cubic curves unless `arcs` is `ArcMode.Exact`.
+ * `tolerance` is the maximum distance between a curve and its chords, see `Linearizer`.
+ * It defaults to 0.3. It used to be a flatness threshold with a default of 0.15, so
+ * curves now give different points, usually fewer.
+ */
+export declare function pointsOnSvgPath(path: string, tolerance?: number | undefined | null, distance?: number | undefined | null, arcs?: ArcMode | undefined | null, minSegmentLength?: number | undefined | null): Array<Array<Point>>
+export declare function pointsOnSvgPathWithClosedInfo(path: string, tolerance?: number | undefined | null, distance?: number | undefined | null, arcs?: ArcMode | undefined | null, minSegmentLength?: number | undefined | null): PathResult
+export interface FontOptions {
+  /** directories searched recursively for font files */
+  fontDirs?: Array<string>
+  /** single font files (TrueType, OpenType or collections) */
+  fontFiles?: Array<string>
+  /** also load the fonts installed on the system, defaults to false */
+  systemFonts?: boolean
+  /** family for text without a `font-family`, defaults to "Times New Roman" as in usvg */
+  defaultFamily?: string
+}
 export interface LoadSvgResult {
-  success: boolean
   result: string
+  /** units of the document and the scale of `result` */
+  units: SvgUnitReport
+}
+/**
+ * Normalizes an SVG document with usvg. px and unitless lengths are converted with
+ * `dpi_scale`, or else with the DPI of `source` or of the detected application.
+ * Text is converted to outlines if its font is among `fonts`, and dropped otherwise.
+ */
+export declare function loadSvgString(svgData: string, dpiScale?: number | undefined | null, source?: SvgSource | undefined | null, fonts?: FontOptions | undefined | null): LoadSvgResult
+/** Normalizes an SVG or SVGZ file, see `load_svg_string`. */
+export declare function loadSvgFile(svgPath: string, dpiScale?: number | undefined | null, source?: SvgSource | undefined | null, fonts?: FontOptions | undefined | null): LoadSvgResult
+export interface Polygon {
+  points: Array<Point>
+  children?: Array<Array<Point>>
+  offsetx?: number
+  offsety?: number
+}
+export interface ExtractPartsOptions {
+  /** maximum deviation of linearized curves, see `Linearizer` */
+  tolerance?: number
+  /** minimum length of the segments of linearized curves, 0 by default */
+  minSegmentLength?: number
+  /** distance within which open paths are joined, defaults to 1e-4 user units */
+  joinTolerance?: number
+  /** DPI for px and unitless lengths, see `detect_units` */
+  dpi?: number
+  /** application that wrote the document, detected if unset */
+  source?: SvgSource
+  /** fonts for text; without them text is dropped */
+  fonts?: FontOptions
+}
+/** A text outline, to be engraved or marked instead of cut. */
+export interface SvgEngraving {
+  points: Array<Point>
+  closed: boolean
+}
+export interface SvgPart {
+  /** id of the element the outer contour was taken from, if it has one */
+  id?: string
+  /** outer contour in user units with all transforms applied, holes as `children` */
+  polygon: Polygon
+  /** the contours of `polygon` before linearization, as SVG path data */
+  curve: CurvePartData
+  /** text outlines on the part, in the same coordinates as `polygon` */
+  engravings: Array<SvgEngraving>
+}
+export interface ExtractPartsResult {
+  parts: Array<SvgPart>
+  /** open paths that could not be joined into closed loops */
+  openPaths: Array<Array<Point>>
+  /** the ends of `open_paths`, where the drawing has gaps */
+  unmatchedEndpoints: Array<Point>
+  /** text outlines that do not lie on any part */
+  engravings: Array<SvgEngraving>
+  /** units of the document; `scale_to_mm` converts the coordinates to millimeters */
+  units: SvgUnitReport
+}
+/**
+ * Extracts the closed contours of an SVG document as parts with holes, with all
+ * transforms applied. Open paths that could not be closed are returned separately.
+ * Text outlines are returned as engravings of the part they lie on.
+ */
+export declare function extractParts(svgData: string, options?: ExtractPartsOptions | undefined | null): ExtractPartsResult
+/** A curved part as SVG path data with absolute commands, see `PathData::from`. */
+export interface CurvePartData {
+  /** outer contour, counter-clockwise like the polygon of the part */
+  outer: string
+  /** holes, clockwise */
+  holes: Array<string>
+}
+/** Result of stitching open path fragments. */
+export interface JoinPathsResult {
+  /** closed loops, without repeating the first point */
+  closed: Array<Array<Point>>
+  /** chains that could not be closed */
+  open: Array<Array<Point>>
+  /** both ends of every open chain, to locate the gaps in the drawing */
+  unmatchedEndpoints: Array<Point>
+}
+/**
+ * Stitches open path fragments into closed loops, joining ends within `tolerance`
+ * (defaults to 1e-4) and reversing fragments as needed. The ends of the chains that
+ * stay open are reported so the drawing can be repaired.
+ */
+export declare function joinPathFragments(fragments: Array<Array<Point>>, tolerance?: number | undefined | null): JoinPathsResult
+/**
+ * Replaces a dense polyline, such as a scanned outline, with as few lines, circular
+ * arcs and cubic curves as possible within `tolerance`. A closed path starts at its
+ * first corner. Fewer than two distinct points throw `INVALID_ARGUMENT`.
+ */
+export declare function fitCurveToPoints(points: Array<Point>, tolerance: number, closed?: boolean | undefined | null): PathData
+/**
+ * The Catmull-Rom spline through `points` as cubic curves. `tightness` defaults to 0,
+ * and 1 gives straight lines. Fewer than three points throw `INVALID_ARGUMENT`.
+ */
+export declare function catmullRomCurve(points: Array<Point>, tightness?: number | undefined | null): PathData
+export interface DxfImportOptions {
+  /** maximum deviation of linearized curves, defaults to `DEFAULT_CURVE_TOLERANCE` */
+  tolerance?: number
+  /** minimum length of the segments of linearized curves, 0 by default */
+  minSegmentLength?: number
+  /** distance within which open entities are joined, defaults to 1e-4 drawing units */
+  joinTolerance?: number
+}
+/** The drawing units as declared in the DXF header. */
+export interface DxfUnitReport {
+  /** raw `$INSUNITS` value, if present */
+  insunits?: number
+  /** unit name such as "mm" or "in", "unitless" if undeclared */
+  unit: string
+  /** factor converting drawing units to millimeters, if known */
+  scaleToMm?: number
+  /** header variable the unit was taken from: "$INSUNITS", "$MEASUREMENT" or "none" */
+  source: string
+}
+export interface DxfImportReport {
+  /** `$ACADVER` of the file */
+  version?: string
+  units: DxfUnitReport
+  /** entity types that were ignored, e.g. TEXT or HATCH */
+  skippedEntities: Array<string>
+  /** number of open chains that could not be joined into closed contours */
+  openContours: number
+}
+export interface DxfImportResult {
+  /** closed contours in drawing coordinates, with holes as `children` */
+  polygons: Array<Polygon>
+  /** the contours of each of `polygons` before linearization, as SVG path data */
+  curves: Array<CurvePartData>
+  report: DxfImportReport
+}
+/** Imports the closed contours of an ASCII DXF document as polygons with holes. */
+export declare function loadDxfString(dxfData: string, options?: DxfImportOptions | undefined | null): DxfImportResult
+/** Imports the closed contours of a DXF file as polygons with holes. */
+export declare function loadDxfFile(dxfPath: string, options?: DxfImportOptions | undefined | null): DxfImportResult
+/** A closed contour to export. Arcs are kept as bulges, as in a DXF LWPOLYLINE. */
+export interface DxfContour {
+  points: Array<Point>
+  /**
+   * bulge of the segment starting at each point (tangent of a quarter of the
+   * included angle, positive for counter-clockwise arcs); straight if unset
+   */
+  bulges?: Array<number>
+}
+/** A part in its own coordinates: the outer contour followed by its holes. */
+export interface DxfPart {
+  /** name of the part, used for its layer and block */
+  name: string
+  contours: Array<DxfContour>
+}
+export interface DxfPlacement {
+  /** index of the part */
+  part: number
+  x: number
+  y: number
+  /** rotation in degrees around the part origin, applied before the translation */
+  rotation: number
+}
+export interface DxfSheet {
+  /** outline of the sheet, written to the SHEET layer */
+  outline?: DxfContour
+  placements: Array<DxfPlacement>
+}
+export interface DxfExportOptions {
+  /** define each part once as a block and place it with INSERT entities */
+  useBlocks?: boolean
+}
+/** Writes each nested sheet as a DXF document, with one layer per part. */
+export declare function exportDxf(parts: Array<DxfPart>, sheets: Array<DxfSheet>, options?: DxfExportOptions | undefined | null): Array<string>
+export interface SvgNestPlacement {
+  /** id of the part element in the source SVG */
+  partId: string
+  x: number
+  y: number
+  /**
+   * rotation in degrees around the origin of the source document, applied before
+   * the translation
+   */
+  rotation: number
+}
+export interface SvgNestSheet {
+  /** index written to `data-deepnest-sheet`, defaults to the position in the list */
+  index?: number
+  width: number
+  height: number
+  placements: Array<SvgNestPlacement>
+}
+export interface SvgNestExportOptions {
+  /** write all sheets into a single document, stacked from top to bottom */
+  combined?: boolean
+  /** gap between the sheets of a combined document, defaults to `DEFAULT_SHEET_SPACING` */
+  sheetSpacing?: number
+}
+/**
+ * Writes the nested sheets as SVG, one document per sheet or a single combined one.
+ * The parts are referenced by the ids of their elements in `svg_data`.
+ */
+export declare function exportNestSvg(svgData: string, sheets: Array<SvgNestSheet>, options?: SvgNestExportOptions | undefined | null): Array<string>
+/** Reads the sheets and placements back from an SVG written by `exportNestSvg`. */
+export declare function readNestSvg(svgData: string): Array<SvgNestSheet>
+/** Application that wrote an SVG document */
+export const enum SvgSource {
+  Inkscape = 0,
+  Illustrator = 1,
+  CorelDraw = 2
+}
+/** The units of a document and the scale of its normalized output. */
+export interface SvgUnitReport {
+  /**
+   * unit of the `width` attribute: "mm", "cm", "in", "pt", "pc" or "px" (also for
+   * unitless and missing widths)
+   */
+  unit: string
+  /** detected application: "inkscape", "illustrator", "coreldraw" or "unknown" */
+  source: string
+  /** DPI used for px and unitless lengths */
+  dpi: number
+  /** document width in millimeters, from `width` or the viewBox */
+  widthMm?: number
+  /** document height in millimeters, from `height` or the viewBox */
+  heightMm?: number
+  /** millimeters per unit of the normalized output */
+  scaleToMm: number
 }
-export declare function loadSvgString(svgData: string, dpiScale?: number | undefined | null): LoadSvgResult
-export declare function loadSvgFile(svgPath: string, dpiScale?: number | undefined | null): LoadSvgResult
 /** Information about a processed path including whether it's closed */
 export declare class PathResult {
   /** Sets of points that approximate the path */
@@ -27,3 +278,75 @@ export declare class PathResult {
   /** Whether the path is closed (ends with 'Z' command or first point equals last point) */
   closed: Array<boolean>
 }
+export const enum FlipDirection {
+  /** mirrors left to right, about the vertical center line of the page */
+  Horizontal = 0,
+  /** mirrors top to bottom, about the horizontal center line of the page */
+  Vertical = 1
+}
+/** Mirrors an SVG document left to right or top to bottom within its page. */
+export declare function flipSvg(svgData: string, direction: FlipDirection): string
+/**
+ * Mirrors an SVG document about the line through (`x1`, `y1`) and (`x2`, `y2`).
+ * The size and viewBox of the result enclose the mirrored page.
+ */
+export declare function mirrorSvg(svgData: string, x1: number, y1: number, x2: number, y2: number): string
+/**
+ * Rotates an SVG document by `degrees` (clockwise on screen) around (`cx`, `cy`),
+ * which defaults to the origin. The size and viewBox of the result enclose the
+ * rotated page.
+ */
+export declare function rotateSvg(svgData: string, degrees: number, cx?: number | undefined | null, cy?: number | undefined | null): string
+/** Scales an SVG document relative to the origin; `sy` defaults to `sx`. */
+export declare function scaleSvg(svgData: string, sx: number, sy?: number | undefined | null): string
+/** Rect used for polygon bounds */
+export interface Rect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+/**
+ * Parsed SVG path data. Every operation returns a new path and keeps curves and
+ * arcs as they are, so the result is as compact as the input.
+ */
+export declare class PathData {
+  /** Parses path data, see `FromStr` for use from Rust. */
+  static parse(d: string): PathData
+  /**
+   * Writes the path with as few characters as possible: repeated commands are
+   * implicit, and separators are left out where the next number cannot be joined
+   * with the previous one. Numbers are written exactly, so parsing the result gives
+   * the same path.
+   */
+  toString(): string
+  /** The path with absolute commands only. */
+  toAbsolute(): PathData
+  /** The path with relative commands only. */
+  toRelative(): PathData
+  /**
+   * Applies the affine transform `[a c e; b d f]`, as in the SVG `matrix()`.
+   * Arcs stay arcs, with the radii and rotation of the transformed ellipse.
+   * The result is absolute.
+   */
+  transform(a: number, b: number, c: number, d: number, e: number, f: number): PathData
+  /**
+   * Rotates the path by `degrees` (clockwise on screen) around (`cx`, `cy`), which
+   * defaults to the origin.
+   */
+  rotate(degrees: number, cx?: number | undefined | null, cy?: number | undefined | null): PathData
+  /**
+   * The path traversed in the opposite direction. Every subpath is reversed in
+   * place, and closed subpaths keep their start point. The result is absolute.
+   */
+  reverse(): PathData
+  /** The exact bounds of the path, including the extrema of curves and arcs. */
+  bounds(): Rect
+}
+/** How `Normalizer` treats elliptical arcs. */
+export const enum ArcMode {
+  /** approximates arcs with cubic Bézier curves */
+  Cubic = 0,
+  /** keeps arcs as absolute `A` segments, to be linearized with `Linearizer::arc` */
+  Exact = 1
+}
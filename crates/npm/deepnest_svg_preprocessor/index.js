@@ -310,12 +310,31 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { computeConvexHull, PathResult, simplifyPolygon, pointsOnSvgPath, pointsOnSvgPathWithClosedInfo, loadSvgString, loadSvgFile } = nativeBinding
+const { computeConvexHull, PathResult, simplifyPolygon, simplifyPolygonConservative, simplifyPart, pointsOnSvgPath, pointsOnSvgPathWithClosedInfo, loadSvgString, loadSvgFile, extractParts, joinPathFragments, fitCurveToPoints, catmullRomCurve, loadDxfString, loadDxfFile, exportDxf, exportNestSvg, readNestSvg, SvgSource, FlipDirection, flipSvg, mirrorSvg, rotateSvg, scaleSvg, PathData, ArcMode } = nativeBinding
 
 module.exports.computeConvexHull = computeConvexHull
 module.exports.PathResult = PathResult
 module.exports.simplifyPolygon = simplifyPolygon
+module.exports.simplifyPolygonConservative = simplifyPolygonConservative
+module.exports.simplifyPart = simplifyPart
 module.exports.pointsOnSvgPath = pointsOnSvgPath
 module.exports.pointsOnSvgPathWithClosedInfo = pointsOnSvgPathWithClosedInfo
 module.exports.loadSvgString = loadSvgString
 module.exports.loadSvgFile = loadSvgFile
+module.exports.extractParts = extractParts
+module.exports.joinPathFragments = joinPathFragments
+module.exports.fitCurveToPoints = fitCurveToPoints
+module.exports.catmullRomCurve = catmullRomCurve
+module.exports.loadDxfString = loadDxfString
+module.exports.loadDxfFile = loadDxfFile
+module.exports.exportDxf = exportDxf
+module.exports.exportNestSvg = exportNestSvg
+module.exports.readNestSvg = readNestSvg
+module.exports.SvgSource = SvgSource
+module.exports.FlipDirection = FlipDirection
+module.exports.flipSvg = flipSvg
+module.exports.mirrorSvg = mirrorSvg
+module.exports.rotateSvg = rotateSvg
+module.exports.scaleSvg = scaleSvg
+module.exports.PathData = PathData
+module.exports.ArcMode = ArcMode
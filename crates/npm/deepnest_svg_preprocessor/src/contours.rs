@@ -0,0 +1,350 @@
+//! Assembly of linearized paths into closed contours with holes, shared by the
+//! SVG and DXF import.
+
+use deepnest_types::types::{CurvePart, CurvePath, Point, Polygon};
+use geometry_utils::GeometryUtils;
+
+use crate::path_data::PathData;
+
+/// An open piece that `join_open` can chain with others.
+pub trait Chain {
+  fn first(&self) -> Point;
+  fn last(&self) -> Point;
+  /// Whether the piece has enough points to form a ring.
+  fn can_close(&self) -> bool;
+  fn reverse(&mut self);
+  /// Appends `other`, which starts where this piece ends.
+  fn append(&mut self, other: Self);
+  /// Turns the piece, which ends where it starts, into a ring.
+  fn close(&mut self);
+}
+
+impl Chain for Vec<Point> {
+  fn first(&self) -> Point {
+    self[0]
+  }
+
+  fn last(&self) -> Point {
+    self[self.len() - 1]
+  }
+
+  fn can_close(&self) -> bool {
+    self.len() > 2
+  }
+
+  fn reverse(&mut self) {
+    self.as_mut_slice().reverse();
+  }
+
+  fn append(&mut self, other: Self) {
+    self.extend(other.into_iter().skip(1));
+  }
+
+  fn close(&mut self) {
+    self.pop();
+  }
+}
+
+/// A linearized piece together with the curves it was linearized from, so that both
+/// are chained and nested alike. The linearized points decide.
+#[derive(Debug, Clone)]
+pub struct CurvePiece {
+  pub points: Vec<Point>,
+  pub path: CurvePath,
+}
+
+impl Chain for CurvePiece {
+  fn first(&self) -> Point {
+    Chain::first(&self.points)
+  }
+
+  fn last(&self) -> Point {
+    Chain::last(&self.points)
+  }
+
+  fn can_close(&self) -> bool {
+    self.points.can_close()
+  }
+
+  fn reverse(&mut self) {
+    Chain::reverse(&mut self.points);
+    self.path = self.path.reversed();
+  }
+
+  fn append(&mut self, other: Self) {
+    Chain::append(&mut self.points, other.points);
+    self.path.segments.extend(other.path.segments);
+  }
+
+  fn close(&mut self) {
+    self.points.close();
+    self.path.closed = true;
+  }
+}
+
+/// A curved part as SVG path data with absolute commands, see `PathData::from`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct CurvePartData {
+  /// outer contour, counter-clockwise like the polygon of the part
+  pub outer: String,
+  /// holes, clockwise
+  pub holes: Vec<String>,
+}
+
+impl From<&CurvePart> for CurvePartData {
+  fn from(part: &CurvePart) -> Self {
+    CurvePartData {
+      outer: PathData::from(&part.outer).to_string(),
+      holes: part
+        .holes
+        .iter()
+        .map(|hole| PathData::from(hole).to_string())
+        .collect(),
+    }
+  }
+}
+
+/// Removes repeated points and the closing point of a ring.
+pub fn clean_ring(mut ring: Vec<Point>, tol: f64) -> Vec<Point> {
+  ring.dedup_by(|a, b| GeometryUtils::almost_equal_points(*a, *b, Some(tol)));
+  while ring.len() > 1
+    && GeometryUtils::almost_equal_points(ring[0], ring[ring.len() - 1], Some(tol))
+  {
+    ring.pop();
+  }
+  ring
+}
+
+/// Result of stitching open path fragments.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct JoinPathsResult {
+  /// closed loops, without repeating the first point
+  pub closed: Vec<Vec<Point>>,
+  /// chains that could not be closed
+  pub open: Vec<Vec<Point>>,
+  /// both ends of every open chain, to locate the gaps in the drawing
+  pub unmatched_endpoints: Vec<Point>,
+}
+
+/// Stitches open fragments into closed loops, see `join_open`.
+pub fn join_fragments(fragments: Vec<Vec<Point>>, tol: f64) -> JoinPathsResult {
+  let fragments = fragments
+    .into_iter()
+    .map(|fragment| {
+      let mut fragment = fragment;
+      fragment.dedup_by(|a, b| GeometryUtils::almost_equal_points(*a, *b, Some(tol)));
+      fragment
+    })
+    .filter(|fragment| fragment.len() > 1)
+    .collect();
+  let (closed, open) = join_open(fragments, tol);
+  JoinPathsResult {
+    unmatched_endpoints: unmatched_endpoints(&open),
+    closed,
+    open,
+  }
+}
+
+/// The first and last point of every open chain.
+pub fn unmatched_endpoints(open: &[Vec<Point>]) -> Vec<Point> {
+  open
+    .iter()
+    .flat_map(|chain| [chain[0], chain[chain.len() - 1]])
+    .collect()
+}
+
+/// Chains open pieces whose ends meet within `tol`, reversing them as needed. Each
+/// chain is extended with the piece whose end is closest to its own end.
+/// Returns the closed contours and the chains that could not be closed.
+pub fn join_open<C: Chain>(mut open: Vec<C>, tol: f64) -> (Vec<C>, Vec<C>) {
+  let near = |a: Point, b: Point| GeometryUtils::almost_equal_points(a, b, Some(tol));
+  let mut closed = vec![];
+  let mut unmatched = vec![];
+  while let Some(mut chain) = open.pop() {
+    loop {
+      let start = chain.first();
+      let end = chain.last();
+      if chain.can_close() && near(start, end) {
+        chain.close();
+        closed.push(chain);
+        break;
+      }
+      if let Some((index, at_last)) = closest_end(&open, end, tol) {
+        let mut piece = open.swap_remove(index);
+        if at_last {
+          piece.reverse();
+        }
+        chain.append(piece);
+      } else if let Some((index, at_last)) = closest_end(&open, start, tol) {
+        let mut piece = open.swap_remove(index);
+        if !at_last {
+          piece.reverse();
+        }
+        piece.append(chain);
+        chain = piece;
+      } else {
+        unmatched.push(chain);
+        break;
+      }
+    }
+  }
+  (closed, unmatched)
+}
+
+/// The piece with the end closest to `p` within `tol`, and whether that end is its
+/// last point.
+fn closest_end<C: Chain>(pieces: &[C], p: Point, tol: f64) -> Option<(usize, bool)> {
+  let mut best: Option<(usize, bool, f64)> = None;
+  for (index, piece) in pieces.iter().enumerate() {
+    for (at_last, q) in [(false, piece.first()), (true, piece.last())] {
+      let distance = (q.x - p.x).hypot(q.y - p.y);
+      if distance < tol && best.is_none_or(|(_, _, closest)| distance < closest) {
+        best = Some((index, at_last, distance));
+      }
+    }
+  }
+  best.map(|(index, at_last, _)| (index, at_last))
+}
+
+/// A part assembled by `nest_rings`.
+#[derive(Debug, Clone)]
+pub struct NestedPart {
+  /// index of the outer ring in the input, and whether it was reversed
+  pub outer: (usize, bool),
+  /// the same for the holes, in the order of `polygon.children`
+  pub holes: Vec<(usize, bool)>,
+  pub polygon: Polygon,
+}
+
+impl NestedPart {
+  /// The curves the rings were linearized from, oriented like `polygon`.
+  pub fn curve_part(&self, paths: &[CurvePath]) -> CurvePart {
+    let path = |(index, reversed): (usize, bool)| {
+      if reversed {
+        paths[index].reversed()
+      } else {
+        paths[index].clone()
+      }
+    };
+    CurvePart {
+      outer: path(self.outer),
+      holes: self.holes.iter().copied().map(path).collect(),
+    }
+  }
+}
+
+/// Builds polygons from closed rings: rings nested at an even depth are parts, the
+/// ones directly inside them are their holes.
+/// Parts are counter-clockwise (negative `polygon_area`), holes clockwise. Each
+/// polygon comes with the indices of its rings in `rings`.
+pub fn nest_rings(rings: Vec<Vec<Point>>) -> Vec<NestedPart> {
+  let mut rings: Vec<(Vec<Point>, f64, usize)> = rings
+    .into_iter()
+    .enumerate()
+    .map(|(index, ring)| {
+      let area = GeometryUtils::polygon_area(ring.clone());
+      (ring, area, index)
+    })
+    .collect();
+  rings.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+
+  let mut parents: Vec<Option<usize>> = vec![];
+  let mut depths: Vec<usize> = vec![];
+  for i in 0..rings.len() {
+    // Rings are sorted by decreasing size, so the last container is the smallest one.
+    let parent = (0..i).rev().find(|&j| {
+      rings[i]
+        .0
+        .iter()
+        .find_map(|p| GeometryUtils::point_in_points(*p, &rings[j].0, 0.0, 0.0, None))
+        .unwrap_or(false)
+    });
+    depths.push(parent.map_or(0, |j| depths[j] + 1));
+    parents.push(parent);
+  }
+
+  let mut parts: Vec<NestedPart> = vec![];
+  let mut part_of: Vec<Option<usize>> = vec![None; rings.len()];
+  for (i, (ring, area, source)) in rings.into_iter().enumerate() {
+    let mut ring = ring;
+    if depths[i].is_multiple_of(2) {
+      if area > 0.0 {
+        ring.reverse();
+      }
+      part_of[i] = Some(parts.len());
+      parts.push(NestedPart {
+        outer: (source, area > 0.0),
+        holes: vec![],
+        polygon: Polygon {
+          points: ring,
+          children: None,
+          offsetx: None,
+          offsety: None,
+        },
+      });
+    } else if let Some(index) = parents[i].and_then(|parent| part_of[parent]) {
+      if area < 0.0 {
+        ring.reverse();
+      }
+      let part = &mut parts[index];
+      part.holes.push((source, area < 0.0));
+      part
+        .polygon
+        .children
+        .get_or_insert_with(Vec::new)
+        .push(ring);
+    }
+  }
+  parts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn p(x: f64, y: f64) -> Point {
+    Point { x, y }
+  }
+
+  #[test]
+  fn test_join_fragments_reverses_and_reports_gaps() {
+    let result = join_fragments(
+      vec![
+        vec![p(0.0, 0.0), p(10.0, 0.0)],
+        // Drawn in the opposite direction and slightly off.
+        vec![p(10.0, 10.0), p(10.0, 0.05)],
+        vec![p(10.0, 10.0), p(0.0, 10.0)],
+        vec![p(0.0, 10.0), p(0.0, 0.0)],
+        // A line with a gap larger than the tolerance.
+        vec![p(20.0, 0.0), p(30.0, 0.0)],
+        vec![p(30.0, 1.0), p(20.0, 0.0)],
+      ],
+      0.1,
+    );
+    assert_eq!(result.closed.len(), 1);
+    assert_eq!(result.closed[0].len(), 4);
+    assert_eq!(result.open.len(), 1);
+    let mut endpoints = result.unmatched_endpoints;
+    endpoints.sort_by(|a, b| a.y.total_cmp(&b.y));
+    assert_eq!(endpoints, vec![p(30.0, 0.0), p(30.0, 1.0)]);
+  }
+
+  #[test]
+  fn test_join_open_prefers_closest_end() {
+    // Both pieces start within the tolerance of (10, 0); the closer one is taken.
+    let (closed, open) = join_open(
+      vec![
+        vec![p(10.4, 0.0), p(20.0, 5.0)],
+        vec![p(10.1, 0.0), p(10.0, 10.0), p(0.0, 0.0)],
+        vec![p(0.0, 0.0), p(10.0, 0.0)],
+      ],
+      0.5,
+    );
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0][1], p(10.0, 0.0));
+    assert_eq!(closed[0][2], p(10.0, 10.0));
+    assert_eq!(open, vec![vec![p(10.4, 0.0), p(20.0, 5.0)]]);
+  }
+}
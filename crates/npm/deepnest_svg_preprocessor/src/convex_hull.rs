@@ -2,13 +2,13 @@ use crate::points_on_curve::Point;
 use parry2d_f64::math::Point as ParryPoint;
 use parry2d_f64::transformation::convex_hull;
 
-#[napi(object)]
+#[cfg_attr(feature = "node", napi(object))]
 pub struct ConvexHullResult {
   pub points: Vec<Point>,
 }
 
-#[napi]
-pub fn compute_convex_hull(points: Vec<Point>) -> napi::Result<ConvexHullResult> {
+#[cfg_attr(feature = "node", napi)]
+pub fn compute_convex_hull(points: Vec<Point>) -> ConvexHullResult {
   // Convert our Point type to parry2d's Point type
   let parry_points: Vec<ParryPoint<f64>> =
     points.iter().map(|p| ParryPoint::new(p.x, p.y)).collect();
@@ -22,9 +22,9 @@ pub fn compute_convex_hull(points: Vec<Point>) -> napi::Result<ConvexHullResult>
     .map(|&i| Point { x: i.x, y: i.y })
     .collect();
 
-  Ok(ConvexHullResult {
+  ConvexHullResult {
     points: hull_points,
-  })
+  }
 }
 
 #[cfg(test)]
@@ -56,7 +56,7 @@ mod tests {
       Point { x: 1.0, y: 0.0 },
     ];
 
-    let result = compute_convex_hull(points.clone()).unwrap();
+    let result = compute_convex_hull(points.clone());
 
     // The convex hull of a square should include all four corner points
     assert_eq!(result.points.len(), 4);
@@ -83,7 +83,7 @@ mod tests {
       Point { x: 1.0, y: 0.0 }, // Bottom-right
     ];
 
-    let result = compute_convex_hull(points).unwrap();
+    let result = compute_convex_hull(points);
 
     // The convex hull should have 4 points for this shape
     assert_eq!(result.points.len(), 4);
@@ -116,7 +116,7 @@ mod tests {
       Point { x: 4.0, y: 0.0 },
     ];
 
-    let result = compute_convex_hull(points).unwrap();
+    let result = compute_convex_hull(points);
 
     // For collinear points, the hull should only contain the endpoints
     assert_eq!(result.points.len(), 2);
@@ -137,7 +137,7 @@ mod tests {
       Point { x: 1.0, y: 1.0 },
     ];
 
-    let result = compute_convex_hull(points).unwrap();
+    let result = compute_convex_hull(points);
 
     // The hull should have 4 points (the duplicates should be handled)
     assert_eq!(result.points.len(), 4);
@@ -0,0 +1,618 @@
+//! Fitting of lines, circular arcs and cubic Bézier curves to dense polylines, such
+//! as scanned outlines or linearized DXF splines.
+//!
+//! Polylines are split at their corners first. Each span between two corners is then
+//! covered greedily: starting at the end of the previous piece, the longest run of
+//! points that a line, an arc or a cubic fits within the tolerance becomes the next
+//! piece. Cubics are fitted by least squares with fixed end tangents (P. J. Schneider,
+//! "An Algorithm for Automatically Fitting Digitized Curves", Graphics Gems, 1990),
+//! so consecutive cubics join smoothly.
+
+use deepnest_types::types::{CurvePath, Point, Segment};
+use geometry_utils::vector::{distance, distance_to_segment, dot, length, normalize, scale, sub};
+
+use crate::error::PreprocessorError;
+use crate::points_on_curve;
+
+/// Vertices where the direction turns by more than this many degrees are corners.
+const CORNER_ANGLE: f64 = 60.0;
+
+/// Newton steps that improve the parameters of the points before a cubic is rejected.
+const MAX_ITERATIONS: usize = 4;
+
+/// Replaces `points` with as few lines, circular arcs and cubic Bézier curves as
+/// possible, such that every point is within `tolerance` of the result. Curves join
+/// smoothly except at corners of the polyline. A closed path starts at its first
+/// corner, or at the first point if it has none.
+pub fn fit_curve(
+  points: &[Point],
+  tolerance: f64,
+  closed: bool,
+) -> Result<CurvePath, PreprocessorError> {
+  if !(tolerance > 0.0 && tolerance.is_finite()) {
+    return Err(invalid_argument("the tolerance must be a positive number"));
+  }
+  if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+    return Err(invalid_argument("the points must have finite coordinates"));
+  }
+  let mut points: Vec<Point> = points.to_vec();
+  points.dedup();
+  if closed && points.len() > 1 && points[0] == points[points.len() - 1] {
+    points.pop();
+  }
+  if points.len() < 2 {
+    return Err(invalid_argument(
+      "a curve must have at least two distinct points",
+    ));
+  }
+
+  let count = points.len();
+  let corners: Vec<usize> = if closed {
+    (0..count)
+      .filter(|&i| is_corner(&points, i, true, tolerance))
+      .collect()
+  } else {
+    (1..count - 1)
+      .filter(|&i| is_corner(&points, i, false, tolerance))
+      .collect()
+  };
+
+  // Closed paths are unrolled to start and end at the same point.
+  let points = if closed {
+    let first = corners.first().copied().unwrap_or(0);
+    let mut ring: Vec<Point> = points[first..]
+      .iter()
+      .chain(points[..first].iter())
+      .copied()
+      .collect();
+    ring.push(ring[0]);
+    ring
+  } else {
+    points
+  };
+  let mut breaks: Vec<usize> = if closed {
+    corners
+      .iter()
+      .map(|&i| (i + count - corners[0]) % count)
+      .collect()
+  } else {
+    std::iter::once(0).chain(corners.iter().copied()).collect()
+  };
+  if breaks.is_empty() {
+    breaks.push(0);
+  }
+  breaks.push(points.len() - 1);
+
+  // A closed path without corners continues smoothly through its start.
+  let smooth = closed && corners.is_empty();
+  let mut segments = vec![];
+  for span in breaks.windows(2) {
+    let span = &points[span[0]..=span[1]];
+    let (start, end) = if smooth {
+      let tangent = central_tangent(&points[..count], 0, true, tolerance);
+      (tangent, tangent)
+    } else {
+      (
+        end_tangent(span.iter(), tolerance),
+        scale(end_tangent(span.iter().rev(), tolerance), -1.0),
+      )
+    };
+    fit_span(span, start, end, tolerance, &mut segments);
+  }
+  Ok(CurvePath {
+    start: points[0],
+    segments,
+    closed,
+  })
+}
+
+/// The Catmull-Rom spline through `points` as cubic Bézier curves, one between each
+/// pair of neighbouring points. Unlike `curve_to_bezier`, three points give two curves
+/// through the middle point. `tightness` is 0 for a Catmull-Rom spline and 1 for
+/// straight lines.
+pub fn catmull_rom(points: &[Point], tightness: f64) -> Result<CurvePath, PreprocessorError> {
+  let points: Vec<points_on_curve::Point> = points
+    .iter()
+    .map(|p| points_on_curve::Point { x: p.x, y: p.y })
+    .collect();
+  let controls = points_on_curve::catmull_rom_to_bezier(&points, tightness)?;
+  let point = |p: &points_on_curve::Point| Point { x: p.x, y: p.y };
+  Ok(CurvePath {
+    start: point(&controls[0]),
+    segments: controls[1..]
+      .chunks_exact(3)
+      .map(|c| Segment::Cubic {
+        control1: point(&c[0]),
+        control2: point(&c[1]),
+        to: point(&c[2]),
+      })
+      .collect(),
+    closed: false,
+  })
+}
+
+fn invalid_argument(message: &str) -> PreprocessorError {
+  PreprocessorError::InvalidArgument {
+    message: message.to_string(),
+  }
+}
+
+/// Covers the points of a span with pieces, given the unit tangents in the direction
+/// of the span at its ends.
+fn fit_span(points: &[Point], start: Point, end: Point, tolerance: f64, out: &mut Vec<Segment>) {
+  let last = points.len() - 1;
+  let mut from = 0;
+  let mut tangent = start;
+  while from < last {
+    let fit = |to: usize| {
+      let end = if to == last {
+        end
+      } else {
+        central_tangent(points, to, false, tolerance)
+      };
+      fit_piece(&points[from..=to], tangent, end, tolerance)
+    };
+    // Two points always fit a line. The reach is found by doubling the length of the
+    // piece and then bisecting between the longest fit and the shortest miss.
+    let mut good = from + 1;
+    let mut best = fit(good);
+    let mut bad = None;
+    let mut step = 1;
+    while bad.is_none() && good < last {
+      let to = (good + step).min(last);
+      match fit(to) {
+        Some(piece) => {
+          good = to;
+          best = Some(piece);
+          step *= 2;
+        }
+        None => bad = Some(to),
+      }
+    }
+    if let Some(mut bad) = bad {
+      while bad - good > 1 {
+        let to = (good + bad) / 2;
+        match fit(to) {
+          Some(piece) => {
+            good = to;
+            best = Some(piece);
+          }
+          None => bad = to,
+        }
+      }
+    }
+    let (segment, exit) = best.unwrap_or((
+      Segment::Line { to: points[good] },
+      normalize(sub(points[good], points[from])),
+    ));
+    out.push(segment);
+    tangent = exit;
+    from = good;
+  }
+}
+
+/// A line, arc or cubic through the ends of `points` that passes all of them within
+/// `tolerance`, with the unit tangent at its end. Cubics start in the direction
+/// `start` and end in the direction `end`.
+fn fit_piece(
+  points: &[Point],
+  start: Point,
+  end: Point,
+  tolerance: f64,
+) -> Option<(Segment, Point)> {
+  let (first, last) = (points[0], points[points.len() - 1]);
+  let (chord_error, farthest) = farthest_from_chord(points);
+  if chord_error <= tolerance {
+    return Some((Segment::Line { to: last }, normalize(sub(last, first))));
+  }
+  if let Some(arc) = fit_arc(points, farthest, tolerance) {
+    return Some(arc);
+  }
+
+  let mut params = chord_length_params(points);
+  for iteration in 0..=MAX_ITERATIONS {
+    let (control1, control2) = fit_cubic(points, &params, start, scale(end, -1.0));
+    let bezier = [first, control1, control2, last];
+    let error = max_error(points, &params, &bezier);
+    if error <= tolerance {
+      let segment = Segment::Cubic {
+        control1,
+        control2,
+        to: last,
+      };
+      return Some((segment, end));
+    }
+    // Far off fits are given up right away, close ones are improved first.
+    if error > 4.0 * tolerance || iteration == MAX_ITERATIONS {
+      break;
+    }
+    reparameterize(points, &mut params, &bezier);
+  }
+  None
+}
+
+/// The largest distance of a point to the chord of the span, and its index.
+fn farthest_from_chord(points: &[Point]) -> (f64, usize) {
+  let (first, last) = (points[0], points[points.len() - 1]);
+  points
+    .iter()
+    .enumerate()
+    .map(|(i, p)| (distance_to_segment(*p, first, last), i))
+    .fold((0.0, 0), |a, b| if b.0 > a.0 { b } else { a })
+}
+
+/// A circular arc through the ends of the span and its point `middle`, if it passes
+/// all points in order within `tolerance`, with the unit tangent at its end.
+fn fit_arc(points: &[Point], middle: usize, tolerance: f64) -> Option<(Segment, Point)> {
+  let (a, b, c) = (points[0], points[middle], points[points.len() - 1]);
+  let (bx, by) = (b.x - a.x, b.y - a.y);
+  let (cx, cy) = (c.x - a.x, c.y - a.y);
+  let det = 2.0 * (bx * cy - by * cx);
+  if det == 0.0 {
+    return None;
+  }
+  let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+  let center = Point {
+    x: a.x + (cy * b2 - by * c2) / det,
+    y: a.y + (bx * c2 - cx * b2) / det,
+  };
+  let radius = (a.x - center.x).hypot(a.y - center.y);
+
+  let angle = |p: &Point| (p.y - center.y).atan2(p.x - center.x);
+  let mut extent = 0.0;
+  let mut direction = 0.0;
+  for pair in points.windows(2) {
+    if ((pair[1].x - center.x).hypot(pair[1].y - center.y) - radius).abs() > tolerance {
+      return None;
+    }
+    let mut step = angle(&pair[1]) - angle(&pair[0]);
+    if step > std::f64::consts::PI {
+      step -= 2.0 * std::f64::consts::PI;
+    } else if step < -std::f64::consts::PI {
+      step += 2.0 * std::f64::consts::PI;
+    }
+    // The points must run around the circle in one direction.
+    if step * direction < 0.0 {
+      return None;
+    }
+    if step != 0.0 {
+      direction = step.signum();
+    }
+    extent += step;
+  }
+  if extent.abs() >= 2.0 * std::f64::consts::PI {
+    return None;
+  }
+  let segment = Segment::Arc {
+    to: c,
+    rx: radius,
+    ry: radius,
+    x_axis_rotation: 0.0,
+    large_arc: extent.abs() > std::f64::consts::PI,
+    sweep: extent > 0.0,
+  };
+  let radial = scale(sub(c, center), direction / radius);
+  Some((
+    segment,
+    Point {
+      x: -radial.y,
+      y: radial.x,
+    },
+  ))
+}
+
+/// Parameters of the points proportional to the length of the polyline.
+fn chord_length_params(points: &[Point]) -> Vec<f64> {
+  let mut params = Vec::with_capacity(points.len());
+  let mut length = 0.0;
+  params.push(0.0);
+  for pair in points.windows(2) {
+    length += distance(pair[0], pair[1]);
+    params.push(length);
+  }
+  for param in params.iter_mut() {
+    *param /= length;
+  }
+  params
+}
+
+/// The inner control points of the cubic with the given end tangents that fits the
+/// points at `params` best in the least-squares sense.
+fn fit_cubic(points: &[Point], params: &[f64], start: Point, end: Point) -> (Point, Point) {
+  let (first, last) = (points[0], points[points.len() - 1]);
+  let mut c = [[0.0; 2]; 2];
+  let mut x = [0.0; 2];
+  for (p, &t) in points.iter().zip(params) {
+    let s = 1.0 - t;
+    let (b0, b1, b2, b3) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+    let a1 = scale(start, b1);
+    let a2 = scale(end, b2);
+    c[0][0] += dot(a1, a1);
+    c[0][1] += dot(a1, a2);
+    c[1][1] += dot(a2, a2);
+    let tmp = Point {
+      x: p.x - (b0 + b1) * first.x - (b2 + b3) * last.x,
+      y: p.y - (b0 + b1) * first.y - (b2 + b3) * last.y,
+    };
+    x[0] += dot(a1, tmp);
+    x[1] += dot(a2, tmp);
+  }
+  c[1][0] = c[0][1];
+
+  let det = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+  let (mut alpha1, mut alpha2) = if det != 0.0 {
+    (
+      (x[0] * c[1][1] - x[1] * c[0][1]) / det,
+      (c[0][0] * x[1] - c[1][0] * x[0]) / det,
+    )
+  } else {
+    (0.0, 0.0)
+  };
+  // Degenerate fits fall back to control points at a third of the chord.
+  let chord = distance(first, last);
+  if alpha1 < chord * 1e-6 || alpha2 < chord * 1e-6 {
+    alpha1 = chord / 3.0;
+    alpha2 = chord / 3.0;
+  }
+  (
+    Point {
+      x: first.x + start.x * alpha1,
+      y: first.y + start.y * alpha1,
+    },
+    Point {
+      x: last.x + end.x * alpha2,
+      y: last.y + end.y * alpha2,
+    },
+  )
+}
+
+/// The largest distance between a point and the curve at its parameter.
+fn max_error(points: &[Point], params: &[f64], bezier: &[Point; 4]) -> f64 {
+  points
+    .iter()
+    .zip(params)
+    .map(|(p, &t)| distance(*p, evaluate(bezier, t)))
+    .fold(0.0, f64::max)
+}
+
+/// Moves the parameters towards the closest points of the curve with a Newton step.
+fn reparameterize(points: &[Point], params: &mut [f64], bezier: &[Point; 4]) {
+  let first = [
+    scale(sub(bezier[1], bezier[0]), 3.0),
+    scale(sub(bezier[2], bezier[1]), 3.0),
+    scale(sub(bezier[3], bezier[2]), 3.0),
+  ];
+  let second = [
+    scale(sub(first[1], first[0]), 2.0),
+    scale(sub(first[2], first[1]), 2.0),
+  ];
+  for (p, t) in points.iter().zip(params.iter_mut()) {
+    let d = sub(evaluate(bezier, *t), *p);
+    let s = 1.0 - *t;
+    let d1 = Point {
+      x: s * s * first[0].x + 2.0 * s * *t * first[1].x + *t * *t * first[2].x,
+      y: s * s * first[0].y + 2.0 * s * *t * first[1].y + *t * *t * first[2].y,
+    };
+    let d2 = Point {
+      x: s * second[0].x + *t * second[1].x,
+      y: s * second[0].y + *t * second[1].y,
+    };
+    let denominator = dot(d1, d1) + dot(d, d2);
+    if denominator != 0.0 {
+      *t = (*t - dot(d, d1) / denominator).clamp(0.0, 1.0);
+    }
+  }
+}
+
+/// Whether the polyline turns by more than `CORNER_ANGLE` at point `i`.
+fn is_corner(points: &[Point], i: usize, closed: bool, tolerance: f64) -> bool {
+  let (before, after) = neighbours(points, i, closed, tolerance);
+  let incoming = sub(points[i], before);
+  let outgoing = sub(after, points[i]);
+  let cos = dot(incoming, outgoing) / (length(incoming) * length(outgoing));
+  cos < CORNER_ANGLE.to_radians().cos()
+}
+
+/// The nearest points before and after point `i` that are farther than `tolerance`
+/// from it, so noise does not decide the direction of the polyline. Falls back to
+/// the ends of the polyline, or its direct neighbours on a closed one.
+fn neighbours(points: &[Point], i: usize, closed: bool, tolerance: f64) -> (Point, Point) {
+  let count = points.len();
+  let p = points[i];
+  let far = |q: &&Point| distance(p, **q) > tolerance;
+  if closed {
+    let before = (1..count)
+      .map(|k| &points[(i + count - k) % count])
+      .find(far)
+      .unwrap_or(&points[(i + count - 1) % count]);
+    let after = (1..count)
+      .map(|k| &points[(i + k) % count])
+      .find(far)
+      .unwrap_or(&points[(i + 1) % count]);
+    (*before, *after)
+  } else {
+    let before = points[..i].iter().rev().find(far).unwrap_or(&points[0]);
+    let after = points[i + 1..]
+      .iter()
+      .find(far)
+      .unwrap_or(&points[count - 1]);
+    (*before, *after)
+  }
+}
+
+/// The unit tangent of the polyline through point `i`, pointing forward.
+fn central_tangent(points: &[Point], i: usize, closed: bool, tolerance: f64) -> Point {
+  let (before, after) = neighbours(points, i, closed, tolerance);
+  normalize(sub(after, before))
+}
+
+/// The unit tangent at the first of `points`, pointing towards the others.
+fn end_tangent<'a>(mut points: impl Iterator<Item = &'a Point> + Clone, tolerance: f64) -> Point {
+  let p = *points.next().unwrap();
+  let last = points.clone().last().copied().unwrap_or(p);
+  let next = points
+    .find(|q| distance(p, **q) > tolerance)
+    .copied()
+    .unwrap_or(last);
+  normalize(sub(next, p))
+}
+
+fn evaluate(bezier: &[Point; 4], t: f64) -> Point {
+  let s = 1.0 - t;
+  let (b0, b1, b2, b3) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+  Point {
+    x: b0 * bezier[0].x + b1 * bezier[1].x + b2 * bezier[2].x + b3 * bezier[3].x,
+    y: b0 * bezier[0].y + b1 * bezier[1].y + b2 * bezier[2].y + b3 * bezier[3].y,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn point(x: f64, y: f64) -> Point {
+    Point { x, y }
+  }
+
+  /// The largest distance of `points` to the linearized `path`.
+  fn max_distance(points: &[Point], path: &CurvePath) -> f64 {
+    let linearizer = geometry_utils::linearizer::Linearizer::new(1e-4);
+    let mut outline = geometry_utils::curve::linearize_path(path, &linearizer);
+    if path.closed {
+      outline.push(outline[0]);
+    }
+    points
+      .iter()
+      .map(|p| {
+        outline
+          .windows(2)
+          .map(|chord| distance_to_segment(*p, chord[0], chord[1]))
+          .fold(f64::INFINITY, f64::min)
+      })
+      .fold(0.0, f64::max)
+  }
+
+  /// Points every `step` units along the closed polyline through `corners`.
+  fn densify(corners: &[Point], step: f64) -> Vec<Point> {
+    let mut points = vec![];
+    for (i, a) in corners.iter().enumerate() {
+      let b = corners[(i + 1) % corners.len()];
+      let count = (distance(*a, b) / step).ceil() as usize;
+      for k in 0..count {
+        let t = k as f64 / count as f64;
+        points.push(point(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t));
+      }
+    }
+    points
+  }
+
+  #[test]
+  fn test_fit_lines_and_arcs() {
+    // A half disc of radius 50 with a little noise, sampled every 0.5 units.
+    let mut outline: Vec<Point> = (0..=180)
+      .map(|i| {
+        let (s, c) = (i as f64).to_radians().sin_cos();
+        point(50.0 * c, 50.0 * s)
+      })
+      .collect();
+    outline = densify(&outline, 0.5);
+    for (i, p) in outline.iter_mut().enumerate() {
+      p.y += 0.01 * ((i * 7919) % 13) as f64 / 13.0;
+    }
+
+    let path = fit_curve(&outline, 0.05, true).unwrap();
+    assert!(path.closed);
+    assert_eq!(path.start, outline[0]);
+    assert!(matches!(
+      path.segments[..],
+      [Segment::Arc { sweep: true, .. }, Segment::Line { .. }]
+    ));
+    assert!(max_distance(&outline, &path) <= 0.05 + 1e-6);
+  }
+
+  #[test]
+  fn test_fit_rounded_rectangle() {
+    // A 100 x 60 rectangle with corners of radius 10.
+    let mut corners = vec![];
+    for (k, (cx, cy)) in [(90.0, 10.0), (90.0, 50.0), (10.0, 50.0), (10.0, 10.0)]
+      .iter()
+      .enumerate()
+    {
+      for i in 0..=30 {
+        let (s, c) = (3.0 * i as f64 + 90.0 * k as f64 - 90.0)
+          .to_radians()
+          .sin_cos();
+        corners.push(point(cx + 10.0 * c, cy + 10.0 * s));
+      }
+    }
+    let points = densify(&corners, 0.5);
+
+    let path = fit_curve(&points, 0.05, true).unwrap();
+    assert!(max_distance(&points, &path) <= 0.05 + 1e-6);
+    // Four arcs joined by the sides.
+    assert_eq!(path.segments.len(), 8, "{:?}", path.segments);
+    let arcs = path
+      .segments
+      .iter()
+      .filter(|s| matches!(s, Segment::Arc { .. }))
+      .count();
+    assert_eq!(arcs, 4);
+  }
+
+  #[test]
+  fn test_fit_curve_errors() {
+    let line = [point(0.0, 0.0), point(1.0, 0.0), point(2.0, 0.0)];
+    let path = fit_curve(&line, 0.1, false).unwrap();
+    assert_eq!(
+      path.segments,
+      vec![Segment::Line {
+        to: point(2.0, 0.0)
+      }]
+    );
+    assert!(matches!(
+      fit_curve(&line, 0.0, false),
+      Err(PreprocessorError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+      fit_curve(&[point(1.0, 1.0), point(1.0, 1.0)], 0.1, false),
+      Err(PreprocessorError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+      catmull_rom(&line[..2], 0.0),
+      Err(PreprocessorError::InvalidArgument { .. })
+    ));
+    let zigzag = [
+      point(0.0, 0.0),
+      point(1.0, 1.0),
+      point(2.0, 0.0),
+      point(3.0, 1.0),
+    ];
+    let spline = catmull_rom(&zigzag, 0.0).unwrap();
+    assert_eq!(spline.start, zigzag[0]);
+    assert_eq!(
+      spline.segments.iter().map(Segment::to).collect::<Vec<_>>(),
+      zigzag[1..]
+    );
+    // Three points are two curves through the middle point, too.
+    let spline = catmull_rom(&zigzag[..3], 0.0).unwrap();
+    assert_eq!(spline.start, zigzag[0]);
+    assert_eq!(
+      spline.segments.iter().map(Segment::to).collect::<Vec<_>>(),
+      zigzag[1..3]
+    );
+  }
+
+  #[test]
+  fn test_fit_sine_wave_with_cubics() {
+    let points: Vec<Point> = (0..=400)
+      .map(|i| {
+        let x = i as f64 * 0.25;
+        point(x, 20.0 * (x / 10.0).sin())
+      })
+      .collect();
+    let path = fit_curve(&points, 0.1, false).unwrap();
+    assert!(max_distance(&points, &path) <= 0.1 + 1e-6);
+    assert!(path.segments.len() <= 10, "{}", path.segments.len());
+    assert_eq!(path.segments[path.segments.len() - 1].to(), points[400]);
+  }
+}
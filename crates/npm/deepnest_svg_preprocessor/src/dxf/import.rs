@@ -0,0 +1,670 @@
+//! Converts a parsed DXF document into closed contours with holes.
+
+use std::collections::BTreeSet;
+
+use deepnest_types::types::{CurvePart, CurvePath, Point, Polygon, Segment, Transform};
+use geometry_utils::curve::linearize_path;
+use geometry_utils::linearizer::Linearizer;
+use geometry_utils::GeometryUtils;
+
+use crate::contours::{clean_ring, join_open, nest_rings, CurvePiece};
+
+use super::reader::{Block, Document, Entity, Vertex};
+use super::{DxfImportReport, DxfUnitReport};
+
+/// Nested INSERTs deeper than this are ignored, which also stops recursive blocks.
+const MAX_INSERT_DEPTH: usize = 16;
+
+/// Mirrors object coordinates with a downward extrusion direction (`x -> -x`).
+const MIRROR_X: Transform = Transform {
+  a: -1.0,
+  ..Transform::IDENTITY
+};
+
+/// Maps a curved path. Arcs stay arcs under rotations, mirroring and uniform
+/// scaling; other transforms turn them into ellipses of a different shape, so they
+/// are linearized with `linearizer` in object coordinates first.
+fn transform_path(transform: &Transform, path: &CurvePath, linearizer: &Linearizer) -> CurvePath {
+  let (sx, sy) = (
+    transform.a.hypot(transform.b),
+    transform.c.hypot(transform.d),
+  );
+  let similar = (sx - sy).abs() <= 1e-9 * sx.max(sy)
+    && (transform.a * transform.c + transform.b * transform.d).abs() <= 1e-9 * sx * sy;
+  let mirrored = transform.determinant() < 0.0;
+  let rotation = GeometryUtils::radians_to_degrees(transform.b.atan2(transform.a));
+  let mut segments = Vec::with_capacity(path.segments.len());
+  let mut current = path.start;
+  for segment in path.segments.iter() {
+    match *segment {
+      Segment::Line { to } => segments.push(Segment::Line {
+        to: transform.apply(to),
+      }),
+      Segment::Cubic {
+        control1,
+        control2,
+        to,
+      } => segments.push(Segment::Cubic {
+        control1: transform.apply(control1),
+        control2: transform.apply(control2),
+        to: transform.apply(to),
+      }),
+      Segment::Arc {
+        to,
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+      } => {
+        if similar {
+          let x_axis_rotation = if mirrored {
+            rotation - x_axis_rotation
+          } else {
+            rotation + x_axis_rotation
+          };
+          segments.push(Segment::Arc {
+            to: transform.apply(to),
+            rx: rx * sx,
+            ry: ry * sx,
+            x_axis_rotation: x_axis_rotation.rem_euclid(360.0),
+            large_arc,
+            sweep: sweep != mirrored,
+          });
+        } else {
+          let mut points = vec![];
+          linearizer.arc(
+            current,
+            to,
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            &mut points,
+          );
+          segments.extend(points.into_iter().map(|p| Segment::Line {
+            to: transform.apply(p),
+          }));
+        }
+      }
+    }
+    current = segment.to();
+  }
+  CurvePath {
+    start: transform.apply(path.start),
+    segments,
+    closed: path.closed,
+  }
+}
+
+struct Importer<'a> {
+  document: &'a Document,
+  linearizer: Linearizer,
+  /// the entities as curves and linearized, already transformed into world coordinates
+  pieces: Vec<CurvePiece>,
+  skipped: BTreeSet<String>,
+}
+
+/// Linearizes all entities of `document` and assembles them into polygons.
+///
+/// Curves are linearized with `linearizer`, open entities are joined into closed
+/// contours where their ends are within `join_tolerance`, and contours nested inside
+/// others alternate between parts and holes.
+///
+/// Every polygon comes with the curves its contours were linearized from. Lines, arcs
+/// and polyline bulges are kept; splines are only available linearized.
+pub fn import_document(
+  document: &Document,
+  linearizer: &Linearizer,
+  join_tolerance: f64,
+) -> (Vec<Polygon>, Vec<CurvePart>, DxfImportReport) {
+  let mut importer = Importer {
+    document,
+    linearizer: *linearizer,
+    pieces: vec![],
+    skipped: BTreeSet::new(),
+  };
+  importer.add_entities(&document.entities, &Transform::IDENTITY, 0);
+
+  let (mut rings, open): (Vec<CurvePiece>, Vec<CurvePiece>) = importer
+    .pieces
+    .into_iter()
+    .partition(|piece| piece.path.closed);
+  let (joined, unmatched) = join_open(open, join_tolerance);
+  rings.extend(joined);
+
+  let (rings, paths): (Vec<Vec<Point>>, Vec<CurvePath>) = rings
+    .into_iter()
+    .map(|piece| (clean_ring(piece.points, join_tolerance), piece.path))
+    .filter(|(ring, _)| ring.len() >= 3)
+    .unzip();
+
+  let report = DxfImportReport {
+    version: document.header.version.clone(),
+    units: detect_units(document.header.insunits, document.header.measurement),
+    skipped_entities: importer.skipped.into_iter().collect(),
+    open_contours: unmatched.len() as u32,
+  };
+  let mut polygons = vec![];
+  let mut curves = vec![];
+  for part in nest_rings(rings) {
+    curves.push(part.curve_part(&paths));
+    polygons.push(part.polygon);
+  }
+  (polygons, curves, report)
+}
+
+impl Importer<'_> {
+  fn add_entities(&mut self, entities: &[Entity], transform: &Transform, depth: usize) {
+    for entity in entities {
+      self.add_entity(entity, transform, depth);
+    }
+  }
+
+  fn add_entity(&mut self, entity: &Entity, transform: &Transform, depth: usize) {
+    // Curves are linearized before transforming, so scale the limits accordingly.
+    let linearizer = self
+      .linearizer
+      .scaled(transform.stretch().max(f64::EPSILON));
+    let object = |mirrored: bool| {
+      if mirrored {
+        transform.then(&MIRROR_X)
+      } else {
+        *transform
+      }
+    };
+    let (path, transform) = match entity {
+      Entity::Line { start, end } => (CurvePath::from_points(&[*start, *end], false), *transform),
+      Entity::Polyline {
+        vertices,
+        closed,
+        mirrored,
+      } => (polyline_path(vertices, *closed), object(*mirrored)),
+      Entity::Arc {
+        center,
+        radius,
+        start_angle,
+        end_angle,
+        mirrored,
+      } => {
+        let extent = (end_angle - start_angle).rem_euclid(360.0);
+        let extent = if extent == 0.0 { 360.0 } else { extent };
+        let Some(path) = ellipse_path(*center, *radius, *radius, 0.0, *start_angle, extent) else {
+          return;
+        };
+        // An ARC entity is open even if it turns fully; `join_open` closes it.
+        let path = CurvePath {
+          closed: false,
+          ..path
+        };
+        (path, object(*mirrored))
+      }
+      Entity::Circle {
+        center,
+        radius,
+        mirrored,
+      } => {
+        let Some(path) = ellipse_path(*center, *radius, *radius, 0.0, 0.0, 360.0) else {
+          return;
+        };
+        (path, object(*mirrored))
+      }
+      Entity::Ellipse {
+        center,
+        major_axis,
+        ratio,
+        start_param,
+        end_param,
+        mirrored,
+      } => {
+        let rx = major_axis.x.hypot(major_axis.y);
+        let rotation = GeometryUtils::radians_to_degrees(major_axis.y.atan2(major_axis.x));
+        let extent = (end_param - start_param).rem_euclid(std::f64::consts::TAU);
+        let full = extent < 1e-9 || (std::f64::consts::TAU - extent) < 1e-9;
+        let extent = if full {
+          360.0
+        } else {
+          GeometryUtils::radians_to_degrees(extent)
+        };
+        let start = GeometryUtils::radians_to_degrees(*start_param);
+        let Some(path) = ellipse_path(*center, rx, rx * ratio, rotation, start, extent) else {
+          return;
+        };
+        (path, object(*mirrored))
+      }
+      Entity::Spline {
+        degree,
+        closed,
+        knots,
+        weights,
+        control_points,
+        fit_points,
+      } => {
+        let points = spline_points(
+          *degree,
+          knots,
+          weights,
+          control_points,
+          linearizer.max_deviation,
+        )
+        .or_else(|| (fit_points.len() >= 2).then(|| fit_points.clone()));
+        let Some(points) = points else {
+          self.skipped.insert("SPLINE".to_string());
+          return;
+        };
+        (CurvePath::from_points(&points, *closed), *transform)
+      }
+      Entity::Insert {
+        name,
+        position,
+        scale,
+        rotation,
+        columns,
+        rows,
+        column_spacing,
+        row_spacing,
+        mirrored,
+      } => {
+        match self.document.blocks.get(name) {
+          Some(block) if depth < MAX_INSERT_DEPTH => {
+            let base = object(*mirrored);
+            for row in 0..*rows {
+              for column in 0..*columns {
+                let offset = Point {
+                  x: column as f64 * column_spacing,
+                  y: row as f64 * row_spacing,
+                };
+                let placement = insert_transform(block, *position, offset, *scale, *rotation);
+                self.add_entities(&block.entities, &base.then(&placement), depth + 1);
+              }
+            }
+          }
+          _ => {
+            self.skipped.insert(format!("INSERT {}", name));
+          }
+        }
+        return;
+      }
+      Entity::Other(kind) => {
+        self.skipped.insert(kind.clone());
+        return;
+      }
+    };
+    let points = linearize_path(&path, &linearizer);
+    if points.len() < 2 {
+      return;
+    }
+    self.pieces.push(CurvePiece {
+      points: points.into_iter().map(|p| transform.apply(p)).collect(),
+      path: transform_path(&transform, &path, &linearizer),
+    });
+  }
+}
+
+/// Maps block coordinates to the coordinates of the INSERT entity.
+fn insert_transform(
+  block: &Block,
+  position: Point,
+  offset: Point,
+  scale: Point,
+  rotation: f64,
+) -> Transform {
+  let (sin, cos) = GeometryUtils::degrees_to_radians(rotation).sin_cos();
+  let scale_and_move = Transform {
+    a: scale.x,
+    b: 0.0,
+    c: 0.0,
+    d: scale.y,
+    e: -block.base.x * scale.x + offset.x,
+    f: -block.base.y * scale.y + offset.y,
+  };
+  Transform {
+    a: cos,
+    b: sin,
+    c: -sin,
+    d: cos,
+    e: position.x,
+    f: position.y,
+  }
+  .then(&scale_and_move)
+}
+
+/// A polyline with its bulged segments as circular arcs.
+fn polyline_path(vertices: &[Vertex], closed: bool) -> CurvePath {
+  let n = vertices.len();
+  let segments = if closed { n } else { n.saturating_sub(1) };
+  CurvePath {
+    start: vertices
+      .first()
+      .map_or(Point { x: 0.0, y: 0.0 }, |v| v.point),
+    segments: (0..segments)
+      .map(|i| {
+        bulge_segment(
+          vertices[i].point,
+          vertices[(i + 1) % n].point,
+          vertices[i].bulge,
+        )
+      })
+      .collect(),
+    closed,
+  }
+}
+
+/// A bulged polyline segment as a circular arc, or a line without bulge.
+fn bulge_segment(start: Point, end: Point, bulge: f64) -> Segment {
+  if bulge.abs() < 1e-12 || start == end {
+    return Segment::Line { to: end };
+  }
+  let angle = 4.0 * bulge.atan();
+  let chord = (end.x - start.x).hypot(end.y - start.y);
+  let radius = chord / (2.0 * (angle / 2.0).sin().abs());
+  Segment::Arc {
+    to: end,
+    rx: radius,
+    ry: radius,
+    x_axis_rotation: 0.0,
+    large_arc: angle.abs() > std::f64::consts::PI,
+    sweep: angle > 0.0,
+  }
+}
+
+/// An elliptical arc given by its center, radii, rotation (degrees) and start angle
+/// and counter-clockwise extent (degrees, parametric for ellipses).
+/// Full turns are split in two halves, since SVG arcs need distinct ends, and closed.
+fn ellipse_path(
+  center: Point,
+  rx: f64,
+  ry: f64,
+  rotation: f64,
+  start: f64,
+  extent: f64,
+) -> Option<CurvePath> {
+  if rx <= 0.0 || ry <= 0.0 {
+    return None;
+  }
+  let (sin, cos) = GeometryUtils::degrees_to_radians(rotation).sin_cos();
+  let at = |angle: f64| {
+    let (s, c) = GeometryUtils::degrees_to_radians(angle).sin_cos();
+    Point {
+      x: center.x + cos * rx * c - sin * ry * s,
+      y: center.y + sin * rx * c + cos * ry * s,
+    }
+  };
+  let halves = if extent >= 360.0 { 2 } else { 1 };
+  let step = extent / halves as f64;
+  Some(CurvePath {
+    start: at(start),
+    segments: (0..halves)
+      .map(|half| Segment::Arc {
+        to: at(start + step * (half + 1) as f64),
+        rx,
+        ry,
+        x_axis_rotation: rotation,
+        large_arc: step > 180.0,
+        sweep: true,
+      })
+      .collect(),
+    closed: halves == 2,
+  })
+}
+
+/// Evaluates a (rational) B-spline, subdividing each knot span until the chord of
+/// every step lies within `tol` of the curve.
+/// Returns `None` if the spline has no usable control points.
+fn spline_points(
+  degree: usize,
+  knots: &[f64],
+  weights: &[f64],
+  control_points: &[Point],
+  tol: f64,
+) -> Option<Vec<Point>> {
+  let n = control_points.len();
+  if n <= degree || knots.len() != n + degree + 1 {
+    return None;
+  }
+  let weight = |i: usize| weights.get(i).copied().unwrap_or(1.0);
+  let evaluate = |t: f64| -> Point {
+    // De Boor's algorithm in homogeneous coordinates.
+    let span = (degree..n).rev().find(|&k| knots[k] <= t).unwrap_or(degree);
+    let mut d: Vec<(f64, f64, f64)> = (0..=degree)
+      .map(|j| {
+        let p = control_points[j + span - degree];
+        let w = weight(j + span - degree);
+        (p.x * w, p.y * w, w)
+      })
+      .collect();
+    for r in 1..=degree {
+      for j in (r..=degree).rev() {
+        let i = j + span - degree;
+        let denominator = knots[i + degree + 1 - r] - knots[i];
+        let alpha = if denominator == 0.0 {
+          0.0
+        } else {
+          (t - knots[i]) / denominator
+        };
+        d[j] = (
+          (1.0 - alpha) * d[j - 1].0 + alpha * d[j].0,
+          (1.0 - alpha) * d[j - 1].1 + alpha * d[j].1,
+          (1.0 - alpha) * d[j - 1].2 + alpha * d[j].2,
+        );
+      }
+    }
+    let (x, y, w) = d[degree];
+    Point { x: x / w, y: y / w }
+  };
+
+  let mut points = vec![evaluate(knots[degree])];
+  for span in degree..n {
+    let (t0, t1) = (knots[span], knots[span + 1]);
+    if t1 <= t0 {
+      continue;
+    }
+    let mut stack = vec![(t0, t1, 0)];
+    while let Some((a, b, level)) = stack.pop() {
+      let pa = evaluate(a);
+      let pb = evaluate(b);
+      let mid = evaluate((a + b) / 2.0);
+      let chord_mid = Point {
+        x: (pa.x + pb.x) / 2.0,
+        y: (pa.y + pb.y) / 2.0,
+      };
+      // Always split at least twice per span so that s-shaped spans are not missed.
+      if level < 2 || (level < 16 && !GeometryUtils::within_distance(mid, chord_mid, tol)) {
+        stack.push(((a + b) / 2.0, b, level + 1));
+        stack.push((a, (a + b) / 2.0, level + 1));
+      } else {
+        points.push(pb);
+      }
+    }
+  }
+  Some(points)
+}
+
+/// Derives the drawing units from `$INSUNITS`, falling back to `$MEASUREMENT`.
+pub fn detect_units(insunits: Option<i32>, measurement: Option<i32>) -> DxfUnitReport {
+  let unit = |name: &str, scale_to_mm: Option<f64>, source: &str| DxfUnitReport {
+    insunits,
+    unit: name.to_string(),
+    scale_to_mm,
+    source: source.to_string(),
+  };
+  let from_insunits = match insunits {
+    Some(1) => Some(("in", Some(25.4))),
+    Some(2) => Some(("ft", Some(304.8))),
+    Some(3) => Some(("mi", Some(1_609_344.0))),
+    Some(4) => Some(("mm", Some(1.0))),
+    Some(5) => Some(("cm", Some(10.0))),
+    Some(6) => Some(("m", Some(1000.0))),
+    Some(7) => Some(("km", Some(1_000_000.0))),
+    Some(8) => Some(("microinch", Some(25.4e-6))),
+    Some(9) => Some(("mil", Some(0.0254))),
+    Some(10) => Some(("yd", Some(914.4))),
+    Some(11) => Some(("angstrom", Some(1e-7))),
+    Some(12) => Some(("nm", Some(1e-6))),
+    Some(13) => Some(("um", Some(1e-3))),
+    Some(14) => Some(("dm", Some(100.0))),
+    Some(15) => Some(("dam", Some(10_000.0))),
+    Some(16) => Some(("hm", Some(100_000.0))),
+    Some(17) => Some(("Gm", Some(1e12))),
+    Some(18) => Some(("au", None)),
+    Some(19) => Some(("ly", None)),
+    Some(20) => Some(("pc", None)),
+    _ => None,
+  };
+  if let Some((name, scale_to_mm)) = from_insunits {
+    return unit(name, scale_to_mm, "$INSUNITS");
+  }
+  match measurement {
+    Some(0) => unit("in", Some(25.4), "$MEASUREMENT"),
+    Some(1) => unit("mm", Some(1.0), "$MEASUREMENT"),
+    _ => unit("unitless", None, "none"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dxf::reader::parse_dxf;
+
+  fn import(dxf: &str) -> (Vec<Polygon>, DxfImportReport) {
+    let (polygons, _, report) =
+      import_document(&parse_dxf(dxf).unwrap(), &Linearizer::new(0.01), 1e-6);
+    (polygons, report)
+  }
+
+  fn area(points: &[Point]) -> f64 {
+    GeometryUtils::polygon_area(points.to_vec())
+  }
+
+  #[test]
+  fn test_lines_and_arcs_form_part_with_hole() {
+    // A 10x10 square made of lines, with a rounded slot made of two arcs and two lines.
+    let dxf = "0\nSECTION\n2\nENTITIES\n\
+               0\nLINE\n10\n0\n20\n0\n11\n10\n21\n0\n\
+               0\nLINE\n10\n10\n20\n10\n11\n10\n21\n0\n\
+               0\nLINE\n10\n10\n20\n10\n11\n0\n21\n10\n\
+               0\nLINE\n10\n0\n20\n0\n11\n0\n21\n10\n\
+               0\nARC\n10\n3\n20\n5\n40\n1\n50\n90\n51\n270\n\
+               0\nARC\n10\n7\n20\n5\n40\n1\n50\n270\n51\n90\n\
+               0\nLINE\n10\n3\n20\n4\n11\n7\n21\n4\n\
+               0\nLINE\n10\n3\n20\n6\n11\n7\n21\n6\n\
+               0\nENDSEC\n0\nEOF\n";
+    let (polygons, report) = import(dxf);
+    assert_eq!(report.open_contours, 0);
+    assert_eq!(polygons.len(), 1);
+    assert!(GeometryUtils::almost_equal(
+      area(&polygons[0].points),
+      -100.0,
+      Some(1e-9)
+    ));
+    let holes = polygons[0].children.as_ref().unwrap();
+    assert_eq!(holes.len(), 1);
+    let slot = 8.0 + std::f64::consts::PI;
+    assert!(area(&holes[0]) > 0.0);
+    assert!((area(&holes[0]) - slot).abs() < 0.05);
+  }
+
+  #[test]
+  fn test_bulged_polyline_and_insert() {
+    // A closed LWPOLYLINE: a 2x2 square whose top edge is a half circle, inserted twice.
+    let dxf = "0\nSECTION\n2\nBLOCKS\n0\nBLOCK\n2\nD\n10\n0\n20\n0\n\
+               0\nLWPOLYLINE\n90\n4\n70\n1\n10\n0\n20\n0\n10\n2\n20\n0\n10\n2\n20\n2\n42\n1\n\
+               10\n0\n20\n2\n0\nENDBLK\n0\nENDSEC\n\
+               0\nSECTION\n2\nENTITIES\n\
+               0\nINSERT\n2\nD\n10\n0\n20\n0\n\
+               0\nINSERT\n2\nD\n10\n10\n20\n0\n41\n2\n42\n2\n50\n90\n\
+               0\nSPLINE\n0\nENDSEC\n0\nEOF\n";
+    let (polygons, report) = import(dxf);
+    assert_eq!(polygons.len(), 2);
+    assert_eq!(report.skipped_entities, vec!["SPLINE".to_string()]);
+    let expected = 4.0 + std::f64::consts::PI / 2.0;
+    let mut areas: Vec<f64> = polygons.iter().map(|p| -area(&p.points)).collect();
+    areas.sort_by(f64::total_cmp);
+    assert!((areas[0] - expected).abs() < 0.05);
+    assert!((areas[1] - 4.0 * expected).abs() < 0.2);
+    // The scaled copy is rotated by 90 degrees, so it lies left of its insertion point.
+    let scaled = polygons
+      .iter()
+      .max_by(|a, b| area(&b.points).total_cmp(&area(&a.points)));
+    let bounds = GeometryUtils::get_polygon_bounds(scaled.unwrap().points.clone()).unwrap();
+    assert!((bounds.x + bounds.width - 10.0).abs() < 1e-9);
+    assert!((bounds.height - 4.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_curve_parts_keep_arcs() {
+    // The rounded square of the previous test, scaled uniformly and non-uniformly.
+    let dxf = "0\nSECTION\n2\nBLOCKS\n0\nBLOCK\n2\nD\n10\n0\n20\n0\n\
+               0\nLWPOLYLINE\n90\n4\n70\n1\n10\n0\n20\n0\n10\n2\n20\n0\n10\n2\n20\n2\n42\n1\n\
+               10\n0\n20\n2\n0\nENDBLK\n0\nENDSEC\n\
+               0\nSECTION\n2\nENTITIES\n\
+               0\nINSERT\n2\nD\n10\n10\n20\n0\n41\n2\n42\n2\n50\n90\n\
+               0\nINSERT\n2\nD\n10\n20\n20\n0\n41\n2\n42\n1\n\
+               0\nENDSEC\n0\nEOF\n";
+    let linearizer = Linearizer::new(0.01);
+    let (polygons, curves, _) = import_document(&parse_dxf(dxf).unwrap(), &linearizer, 1e-6);
+    assert_eq!(curves.len(), polygons.len());
+    for (polygon, curve) in polygons.iter().zip(curves.iter()) {
+      let points = linearize_path(&curve.outer, &linearizer);
+      assert!((area(&points) - area(&polygon.points)).abs() < 0.1);
+      let arcs: Vec<&Segment> = curve
+        .outer
+        .segments
+        .iter()
+        .filter(|segment| matches!(segment, Segment::Arc { .. }))
+        .collect();
+      if polygon.points.iter().all(|p| p.x <= 10.0) {
+        // Rotated and scaled by 2: the half circle keeps its radius of 2.
+        assert!(matches!(arcs[..], [Segment::Arc { rx, ry, .. }] if *rx == 2.0 && *ry == 2.0));
+      } else {
+        // Stretched in x only: the arc is no longer circular and was linearized.
+        assert!(arcs.is_empty());
+      }
+    }
+  }
+
+  #[test]
+  fn test_circle_ellipse_and_spline() {
+    let dxf = "0\nSECTION\n2\nENTITIES\n\
+               0\nCIRCLE\n10\n0\n20\n0\n40\n5\n\
+               0\nELLIPSE\n10\n20\n20\n0\n11\n4\n21\n0\n40\n0.5\n41\n0\n42\n6.283185307179586\n\
+               0\nSPLINE\n70\n1\n71\n1\n40\n0\n40\n0\n40\n1\n40\n2\n40\n3\n40\n3\n\
+               10\n40\n20\n0\n10\n42\n20\n0\n10\n42\n20\n2\n10\n40\n20\n0\n\
+               0\nENDSEC\n0\nEOF\n";
+    let (polygons, _) = import(dxf);
+    assert_eq!(polygons.len(), 3);
+    let mut areas: Vec<f64> = polygons.iter().map(|p| -area(&p.points)).collect();
+    areas.sort_by(f64::total_cmp);
+    assert!((areas[0] - 2.0).abs() < 1e-9);
+    assert!((areas[1] - std::f64::consts::PI * 8.0).abs() < 0.1);
+    // The chords are at most 0.01 inside the circle.
+    assert!((areas[2] - std::f64::consts::PI * 25.0).abs() < 0.01 * std::f64::consts::PI * 10.0);
+  }
+
+  #[test]
+  fn test_mirrored_ellipse() {
+    // A downward extrusion mirrors the object coordinates, so the center moves to x = -3.
+    let dxf = "0\nSECTION\n2\nENTITIES\n\
+               0\nELLIPSE\n10\n3\n20\n0\n11\n0\n21\n4\n40\n0.5\n\
+               41\n0\n42\n6.283185307179586\n230\n-1\n\
+               0\nENDSEC\n0\nEOF\n";
+    let (polygons, _) = import(dxf);
+    assert_eq!(polygons.len(), 1);
+    let bounds = GeometryUtils::get_polygon_bounds(polygons[0].points.clone()).unwrap();
+    assert!((bounds.x + 5.0).abs() < 0.01);
+    assert!((bounds.x + bounds.width + 1.0).abs() < 0.01);
+  }
+
+  #[test]
+  fn test_detect_units() {
+    let units = detect_units(Some(1), Some(1));
+    assert_eq!(units.unit, "in");
+    assert_eq!(units.scale_to_mm, Some(25.4));
+    assert_eq!(units.source, "$INSUNITS");
+    let units = detect_units(Some(0), Some(1));
+    assert_eq!(units.unit, "mm");
+    assert_eq!(units.source, "$MEASUREMENT");
+    assert_eq!(detect_units(None, None).unit, "unitless");
+  }
+}
@@ -0,0 +1,297 @@
+//! DXF import and export for nesting.
+//!
+//! `reader` decodes the ASCII DXF group codes into entities, `import` linearizes them
+//! and assembles closed contours with holes as `deepnest_types::Polygon`, keeping the
+//! unlinearized contours as `deepnest_types::CurvePart`. `writer` writes nested sheets
+//! back to DXF.
+
+mod import;
+mod reader;
+mod writer;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use deepnest_types::types::{CurvePart, CurvePath, Point, Polygon, Segment};
+use geometry_utils::arc::Arc;
+use geometry_utils::linearizer::Linearizer;
+use geometry_utils::GeometryUtils;
+
+use crate::contours::CurvePartData;
+use crate::points_on_path::linearizer;
+use import::import_document;
+use reader::{parse_dxf, parse_dxf_bytes, Document};
+use writer::write_sheet;
+
+/// Default distance within which the ends of open entities are joined.
+pub const DEFAULT_JOIN_TOLERANCE: f64 = 1e-4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DxfError {
+  Io(String),
+  Parse { line: usize, message: String },
+  BinaryUnsupported,
+}
+
+impl fmt::Display for DxfError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DxfError::Io(message) => write!(f, "Failed to read DXF file: {}", message),
+      DxfError::Parse { line, message } => {
+        write!(f, "DXF parse error at line {}: {}", line, message)
+      }
+      DxfError::BinaryUnsupported => write!(f, "Binary DXF files are not supported"),
+    }
+  }
+}
+
+impl std::error::Error for DxfError {}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfImportOptions {
+  /// maximum deviation of linearized curves, defaults to `DEFAULT_CURVE_TOLERANCE`
+  pub tolerance: Option<f64>,
+  /// minimum length of the segments of linearized curves, 0 by default
+  pub min_segment_length: Option<f64>,
+  /// distance within which open entities are joined, defaults to 1e-4 drawing units
+  pub join_tolerance: Option<f64>,
+}
+
+/// The drawing units as declared in the DXF header.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfUnitReport {
+  /// raw `$INSUNITS` value, if present
+  pub insunits: Option<i32>,
+  /// unit name such as "mm" or "in", "unitless" if undeclared
+  pub unit: String,
+  /// factor converting drawing units to millimeters, if known
+  pub scale_to_mm: Option<f64>,
+  /// header variable the unit was taken from: "$INSUNITS", "$MEASUREMENT" or "none"
+  pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfImportReport {
+  /// `$ACADVER` of the file
+  pub version: Option<String>,
+  pub units: DxfUnitReport,
+  /// entity types that were ignored, e.g. TEXT or HATCH
+  pub skipped_entities: Vec<String>,
+  /// number of open chains that could not be joined into closed contours
+  pub open_contours: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfImportResult {
+  /// closed contours in drawing coordinates, with holes as `children`
+  pub polygons: Vec<Polygon>,
+  /// the contours of each of `polygons` before linearization, as SVG path data
+  pub curves: Vec<CurvePartData>,
+  pub report: DxfImportReport,
+}
+
+/// A closed contour to export. Arcs are kept as bulges, as in a DXF LWPOLYLINE.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfContour {
+  pub points: Vec<Point>,
+  /// bulge of the segment starting at each point (tangent of a quarter of the
+  /// included angle, positive for counter-clockwise arcs); straight if unset
+  pub bulges: Option<Vec<f64>>,
+}
+
+/// A part in its own coordinates: the outer contour followed by its holes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfPart {
+  /// name of the part, used for its layer and block
+  pub name: String,
+  pub contours: Vec<DxfContour>,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfPlacement {
+  /// index of the part
+  pub part: u32,
+  pub x: f64,
+  pub y: f64,
+  /// rotation in degrees around the part origin, applied before the translation
+  pub rotation: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfSheet {
+  /// outline of the sheet, written to the SHEET layer
+  pub outline: Option<DxfContour>,
+  pub placements: Vec<DxfPlacement>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct DxfExportOptions {
+  /// define each part once as a block and place it with INSERT entities
+  pub use_blocks: Option<bool>,
+}
+
+/// Imports the parts of an ASCII DXF document.
+pub fn import_dxf_string(
+  dxf_data: &str,
+  options: Option<DxfImportOptions>,
+) -> Result<DxfImportResult, DxfError> {
+  import_parsed(parse_dxf(dxf_data)?, options).map(|(result, _)| result)
+}
+
+/// Imports the parts of an ASCII DXF document with their lines and arcs, in the same
+/// order as `DxfImportResult::polygons`.
+pub fn import_dxf_curve_parts(
+  dxf_data: &str,
+  options: Option<DxfImportOptions>,
+) -> Result<Vec<CurvePart>, DxfError> {
+  import_parsed(parse_dxf(dxf_data)?, options).map(|(_, curves)| curves)
+}
+
+/// Imports the parts of a DXF file.
+pub fn import_dxf_file(
+  dxf_path: &Path,
+  options: Option<DxfImportOptions>,
+) -> Result<DxfImportResult, DxfError> {
+  let data = fs::read(dxf_path).map_err(|err| DxfError::Io(err.to_string()))?;
+  import_parsed(parse_dxf_bytes(&data)?, options).map(|(result, _)| result)
+}
+
+fn import_parsed(
+  document: Document,
+  options: Option<DxfImportOptions>,
+) -> Result<(DxfImportResult, Vec<CurvePart>), DxfError> {
+  let options = options.unwrap_or_default();
+  let (polygons, curves, report) = import_document(
+    &document,
+    &linearizer(options.tolerance, options.min_segment_length),
+    options.join_tolerance.unwrap_or(DEFAULT_JOIN_TOLERANCE),
+  );
+  let result = DxfImportResult {
+    polygons,
+    curves: curves.iter().map(CurvePartData::from).collect(),
+    report,
+  };
+  Ok((result, curves))
+}
+
+impl From<&Polygon> for DxfPart {
+  /// Converts a linearized polygon (without offsets) into a part with straight edges.
+  fn from(polygon: &Polygon) -> Self {
+    let contour = |points: &Vec<Point>| DxfContour {
+      points: points.clone(),
+      bulges: None,
+    };
+    DxfPart {
+      name: String::new(),
+      contours: std::iter::once(&polygon.points)
+        .chain(polygon.children.iter().flatten())
+        .map(contour)
+        .collect(),
+    }
+  }
+}
+
+impl DxfContour {
+  /// Converts a curved path, keeping circular arcs as bulges. Elliptical arcs and
+  /// cubic curves have no R12 equivalent and are linearized with `linearizer`.
+  pub fn from_curve_path(path: &CurvePath, linearizer: &Linearizer) -> DxfContour {
+    let mut points = vec![path.start];
+    let mut bulges = vec![];
+    for segment in path.segments.iter() {
+      let from = points[points.len() - 1];
+      let to = segment.to();
+      let (bulge, curve) = match *segment {
+        Segment::Line { .. } => (0.0, vec![to]),
+        Segment::Arc {
+          rx,
+          ry,
+          x_axis_rotation,
+          large_arc,
+          sweep,
+          ..
+        } => {
+          if rx == 0.0 || ry == 0.0 || GeometryUtils::almost_equal_points(from, to, None) {
+            (0.0, vec![to])
+          } else if GeometryUtils::almost_equal(rx, ry, None) {
+            let arc = Arc::svg_to_center(from, to, rx, ry, x_axis_rotation, large_arc, sweep);
+            // The extent follows the y-down convention of Deepnest, so only its size is used.
+            let bulge = (arc.extent.abs().to_radians() / 4.0).tan();
+            (if sweep { bulge } else { -bulge }, vec![to])
+          } else {
+            let mut curve = vec![];
+            linearizer.arc(
+              from,
+              to,
+              rx,
+              ry,
+              x_axis_rotation,
+              large_arc,
+              sweep,
+              &mut curve,
+            );
+            (0.0, curve)
+          }
+        }
+        Segment::Cubic {
+          control1, control2, ..
+        } => {
+          let mut curve = vec![];
+          linearizer.cubic(from, control1, control2, to, &mut curve);
+          (0.0, curve)
+        }
+      };
+      for p in curve {
+        bulges.push(bulge);
+        points.push(p);
+      }
+    }
+    // The contour is always closed; drop the end point if it already returns to the start.
+    if points.len() > 1
+      && GeometryUtils::almost_equal_points(points[0], points[points.len() - 1], None)
+    {
+      points.pop();
+    } else {
+      bulges.push(0.0);
+    }
+    DxfContour {
+      points,
+      bulges: Some(bulges),
+    }
+  }
+}
+
+impl DxfPart {
+  /// Converts a curved part in its own coordinates, see `DxfContour::from_curve_path`.
+  pub fn from_curve_part(name: &str, part: &CurvePart, linearizer: &Linearizer) -> DxfPart {
+    DxfPart {
+      name: name.to_string(),
+      contours: std::iter::once(&part.outer)
+        .chain(part.holes.iter())
+        .map(|path| DxfContour::from_curve_path(path, linearizer))
+        .collect(),
+    }
+  }
+}
+
+/// Writes every sheet with its placed parts as a separate DXF document.
+pub fn export_dxf_sheets(
+  parts: &[DxfPart],
+  sheets: &[DxfSheet],
+  options: Option<DxfExportOptions>,
+) -> Vec<String> {
+  let options = options.unwrap_or_default();
+  sheets
+    .iter()
+    .map(|sheet| write_sheet(parts, sheet, &options))
+    .collect()
+}
@@ -0,0 +1,485 @@
+//! Reader for ASCII DXF files (R12 through 2018).
+//!
+//! The file is read as a flat list of group code / value pairs, which is then split
+//! into the HEADER, BLOCKS and ENTITIES sections. Only the entities needed for
+//! nesting are decoded; everything else is kept as `Entity::Other`.
+
+use std::collections::HashMap;
+
+use deepnest_types::types::Point;
+
+use super::DxfError;
+
+const BINARY_SENTINEL: &[u8] = b"AutoCAD Binary DXF";
+
+/// A group code and its (trimmed) value.
+#[derive(Debug, Clone)]
+struct Group {
+  code: i32,
+  value: String,
+  line: usize,
+}
+
+/// A vertex of a LWPOLYLINE or POLYLINE with the bulge of the following segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+  pub point: Point,
+  /// tangent of a quarter of the included angle, positive for counter-clockwise arcs
+  pub bulge: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entity {
+  Line {
+    start: Point,
+    end: Point,
+  },
+  /// LWPOLYLINE or 2D POLYLINE, in object coordinates
+  Polyline {
+    vertices: Vec<Vertex>,
+    closed: bool,
+    mirrored: bool,
+  },
+  /// angles in degrees, counter-clockwise from start to end, in object coordinates
+  Arc {
+    center: Point,
+    radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+    mirrored: bool,
+  },
+  Circle {
+    center: Point,
+    radius: f64,
+    mirrored: bool,
+  },
+  /// parameters in radians, counter-clockwise from start to end, in object coordinates
+  Ellipse {
+    center: Point,
+    major_axis: Point,
+    ratio: f64,
+    start_param: f64,
+    end_param: f64,
+    mirrored: bool,
+  },
+  Spline {
+    degree: usize,
+    closed: bool,
+    knots: Vec<f64>,
+    weights: Vec<f64>,
+    control_points: Vec<Point>,
+    fit_points: Vec<Point>,
+  },
+  /// reference to a block, in object coordinates
+  Insert {
+    name: String,
+    position: Point,
+    scale: Point,
+    rotation: f64,
+    columns: u32,
+    rows: u32,
+    column_spacing: f64,
+    row_spacing: f64,
+    mirrored: bool,
+  },
+  /// any entity type the importer does not handle
+  Other(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Header {
+  /// `$ACADVER`, e.g. `AC1032` for DXF 2018
+  pub version: Option<String>,
+  /// `$INSUNITS`
+  pub insunits: Option<i32>,
+  /// `$MEASUREMENT`, 0 for imperial and 1 for metric drawings
+  pub measurement: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+  pub base: Point,
+  pub entities: Vec<Entity>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+  pub header: Header,
+  pub blocks: HashMap<String, Block>,
+  pub entities: Vec<Entity>,
+}
+
+/// The groups of a single entity or header variable.
+struct Record<'a>(&'a [Group]);
+
+impl Record<'_> {
+  fn find(&self, code: i32) -> Option<&Group> {
+    self.0.iter().find(|group| group.code == code)
+  }
+
+  fn text(&self, code: i32) -> Option<&str> {
+    self.find(code).map(|group| group.value.as_str())
+  }
+
+  fn f64_or(&self, code: i32, default: f64) -> Result<f64, DxfError> {
+    self.find(code).map_or(Ok(default), number)
+  }
+
+  fn i32_or(&self, code: i32, default: i32) -> Result<i32, DxfError> {
+    self.find(code).map_or(Ok(default), integer)
+  }
+
+  /// The point stored in group `code` (x) and `code + 10` (y).
+  fn point(&self, code: i32) -> Result<Point, DxfError> {
+    Ok(Point {
+      x: self.f64_or(code, 0.0)?,
+      y: self.f64_or(code + 10, 0.0)?,
+    })
+  }
+
+  fn all_f64(&self, code: i32) -> Result<Vec<f64>, DxfError> {
+    self
+      .0
+      .iter()
+      .filter(|group| group.code == code)
+      .map(number)
+      .collect()
+  }
+
+  /// All points stored in repeated `code` / `code + 10` groups.
+  fn all_points(&self, code: i32) -> Result<Vec<Point>, DxfError> {
+    let xs = self.all_f64(code)?;
+    let ys = self.all_f64(code + 10)?;
+    Ok(
+      xs.into_iter()
+        .zip(ys)
+        .map(|(x, y)| Point { x, y })
+        .collect(),
+    )
+  }
+
+  /// Whether the extrusion direction points down, which mirrors object coordinates in x.
+  fn mirrored(&self) -> Result<bool, DxfError> {
+    Ok(self.f64_or(230, 1.0)? < 0.0)
+  }
+}
+
+fn number(group: &Group) -> Result<f64, DxfError> {
+  group.value.parse().map_err(|_| DxfError::Parse {
+    line: group.line,
+    message: format!(
+      "invalid number `{}` for group code {}",
+      group.value, group.code
+    ),
+  })
+}
+
+fn integer(group: &Group) -> Result<i32, DxfError> {
+  group.value.parse().map_err(|_| DxfError::Parse {
+    line: group.line,
+    message: format!(
+      "invalid integer `{}` for group code {}",
+      group.value, group.code
+    ),
+  })
+}
+
+/// Parses a DXF file. Binary DXF is detected and rejected.
+pub fn parse_dxf_bytes(data: &[u8]) -> Result<Document, DxfError> {
+  if data.starts_with(BINARY_SENTINEL) {
+    return Err(DxfError::BinaryUnsupported);
+  }
+  // Files before DXF 2007 use the code page of the drawing; only ASCII matters here.
+  parse_dxf(&String::from_utf8_lossy(data))
+}
+
+/// Parses an ASCII DXF document.
+pub fn parse_dxf(data: &str) -> Result<Document, DxfError> {
+  let groups = tokenize(data)?;
+  let mut document = Document::default();
+  let mut i = 0;
+  while i < groups.len() {
+    if !(groups[i].code == 0 && groups[i].value == "SECTION") {
+      i += 1;
+      continue;
+    }
+    let name = groups.get(i + 1).filter(|group| group.code == 2);
+    i += 2;
+    match name.map(|group| group.value.as_str()) {
+      Some("HEADER") => document.header = parse_header(&groups, &mut i)?,
+      Some("BLOCKS") => parse_blocks(&groups, &mut i, &mut document.blocks)?,
+      Some("ENTITIES") => document.entities = parse_entities(&groups, &mut i, "ENDSEC")?,
+      _ => skip_to(&groups, &mut i, "ENDSEC"),
+    }
+  }
+  Ok(document)
+}
+
+fn tokenize(data: &str) -> Result<Vec<Group>, DxfError> {
+  let mut groups = vec![];
+  let mut lines = data.lines().enumerate();
+  while let Some((index, code)) = lines.next() {
+    let code = code.trim();
+    if code.is_empty() {
+      continue;
+    }
+    let line = index + 1;
+    let code: i32 = code.parse().map_err(|_| DxfError::Parse {
+      line,
+      message: format!("invalid group code `{}`", code),
+    })?;
+    let Some((_, value)) = lines.next() else {
+      return Err(DxfError::Parse {
+        line,
+        message: format!("missing value for group code {}", code),
+      });
+    };
+    groups.push(Group {
+      code,
+      value: value.trim().to_string(),
+      line: line + 1,
+    });
+  }
+  Ok(groups)
+}
+
+/// Advances `i` past the `(0, end)` group.
+fn skip_to(groups: &[Group], i: &mut usize, end: &str) {
+  while *i < groups.len() && !(groups[*i].code == 0 && groups[*i].value == end) {
+    *i += 1;
+  }
+  *i += 1;
+}
+
+/// The groups from `i` up to the next group with code 0.
+fn record<'a>(groups: &'a [Group], i: &mut usize) -> Record<'a> {
+  let start = *i;
+  while *i < groups.len() && groups[*i].code != 0 {
+    *i += 1;
+  }
+  Record(&groups[start..*i])
+}
+
+fn parse_header(groups: &[Group], i: &mut usize) -> Result<Header, DxfError> {
+  let mut header = Header::default();
+  while *i < groups.len() && groups[*i].code != 0 {
+    if groups[*i].code != 9 {
+      *i += 1;
+      continue;
+    }
+    let name = groups[*i].value.clone();
+    *i += 1;
+    let start = *i;
+    while *i < groups.len() && groups[*i].code != 9 && groups[*i].code != 0 {
+      *i += 1;
+    }
+    let variable = Record(&groups[start..*i]);
+    match name.as_str() {
+      "$ACADVER" => header.version = variable.text(1).map(str::to_string),
+      "$INSUNITS" => header.insunits = variable.find(70).map(integer).transpose()?,
+      "$MEASUREMENT" => header.measurement = variable.find(70).map(integer).transpose()?,
+      _ => {}
+    }
+  }
+  skip_to(groups, i, "ENDSEC");
+  Ok(header)
+}
+
+fn parse_blocks(
+  groups: &[Group],
+  i: &mut usize,
+  blocks: &mut HashMap<String, Block>,
+) -> Result<(), DxfError> {
+  while *i < groups.len() {
+    let kind = groups[*i].value.as_str();
+    *i += 1;
+    match kind {
+      "ENDSEC" => break,
+      "BLOCK" => {
+        let definition = record(groups, i);
+        let name = definition.text(2).unwrap_or_default().to_string();
+        let base = definition.point(10)?;
+        let entities = parse_entities(groups, i, "ENDBLK")?;
+        blocks.insert(name, Block { base, entities });
+      }
+      _ => {
+        record(groups, i);
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Parses entities until the `(0, end)` group, which is consumed.
+fn parse_entities(groups: &[Group], i: &mut usize, end: &str) -> Result<Vec<Entity>, DxfError> {
+  let mut entities = vec![];
+  while *i < groups.len() {
+    let kind = groups[*i].value.clone();
+    *i += 1;
+    let data = record(groups, i);
+    if kind == end {
+      break;
+    }
+    let entity = match kind.as_str() {
+      "LINE" => Entity::Line {
+        start: data.point(10)?,
+        end: data.point(11)?,
+      },
+      "LWPOLYLINE" => parse_lwpolyline(&data)?,
+      "POLYLINE" => parse_polyline(&data, groups, i)?,
+      "ARC" => Entity::Arc {
+        center: data.point(10)?,
+        radius: data.f64_or(40, 0.0)?,
+        start_angle: data.f64_or(50, 0.0)?,
+        end_angle: data.f64_or(51, 360.0)?,
+        mirrored: data.mirrored()?,
+      },
+      "CIRCLE" => Entity::Circle {
+        center: data.point(10)?,
+        radius: data.f64_or(40, 0.0)?,
+        mirrored: data.mirrored()?,
+      },
+      "ELLIPSE" => Entity::Ellipse {
+        center: data.point(10)?,
+        major_axis: data.point(11)?,
+        ratio: data.f64_or(40, 1.0)?,
+        start_param: data.f64_or(41, 0.0)?,
+        end_param: data.f64_or(42, std::f64::consts::TAU)?,
+        mirrored: data.mirrored()?,
+      },
+      "SPLINE" => Entity::Spline {
+        degree: data.i32_or(71, 3)?.max(1) as usize,
+        closed: data.i32_or(70, 0)? & 1 != 0,
+        knots: data.all_f64(40)?,
+        weights: data.all_f64(41)?,
+        control_points: data.all_points(10)?,
+        fit_points: data.all_points(11)?,
+      },
+      "INSERT" => Entity::Insert {
+        name: data.text(2).unwrap_or_default().to_string(),
+        position: data.point(10)?,
+        scale: Point {
+          x: data.f64_or(41, 1.0)?,
+          y: data.f64_or(42, 1.0)?,
+        },
+        rotation: data.f64_or(50, 0.0)?,
+        columns: data.i32_or(70, 1)?.max(1) as u32,
+        rows: data.i32_or(71, 1)?.max(1) as u32,
+        column_spacing: data.f64_or(44, 0.0)?,
+        row_spacing: data.f64_or(45, 0.0)?,
+        mirrored: data.mirrored()?,
+      },
+      _ => Entity::Other(kind),
+    };
+    entities.push(entity);
+  }
+  Ok(entities)
+}
+
+fn parse_lwpolyline(data: &Record) -> Result<Entity, DxfError> {
+  let mut vertices: Vec<Vertex> = vec![];
+  let mut x = None;
+  for group in data.0 {
+    match group.code {
+      10 => x = Some(number(group)?),
+      20 => {
+        let point = Point {
+          x: x.take().unwrap_or(0.0),
+          y: number(group)?,
+        };
+        vertices.push(Vertex { point, bulge: 0.0 });
+      }
+      // The bulge follows the vertex it belongs to.
+      42 => {
+        if let Some(vertex) = vertices.last_mut() {
+          vertex.bulge = number(group)?;
+        }
+      }
+      _ => {}
+    }
+  }
+  Ok(Entity::Polyline {
+    vertices,
+    closed: data.i32_or(70, 0)? & 1 != 0,
+    mirrored: data.mirrored()?,
+  })
+}
+
+/// Parses a POLYLINE header and its VERTEX entities up to SEQEND.
+fn parse_polyline(data: &Record, groups: &[Group], i: &mut usize) -> Result<Entity, DxfError> {
+  let flags = data.i32_or(70, 0)?;
+  let mut vertices = vec![];
+  while *i < groups.len() && groups[*i].value == "VERTEX" {
+    *i += 1;
+    let vertex = record(groups, i);
+    // Skip spline frame control points; the fitted vertices follow them.
+    if vertex.i32_or(70, 0)? & 16 != 0 {
+      continue;
+    }
+    vertices.push(Vertex {
+      point: vertex.point(10)?,
+      bulge: vertex.f64_or(42, 0.0)?,
+    });
+  }
+  if *i < groups.len() && groups[*i].value == "SEQEND" {
+    *i += 1;
+    record(groups, i);
+  }
+  // 3D polylines and polyface meshes are not flat contours.
+  if flags & (8 | 16 | 64) != 0 {
+    return Ok(Entity::Other("POLYLINE".to_string()));
+  }
+  Ok(Entity::Polyline {
+    vertices,
+    closed: flags & 1 != 0,
+    mirrored: data.mirrored()?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_sections() {
+    let dxf = "0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1032\n9\n$INSUNITS\n70\n4\n0\nENDSEC\n\
+               0\nSECTION\n2\nBLOCKS\n0\nBLOCK\n2\nPART\n10\n1.0\n20\n2.0\n\
+               0\nLINE\n10\n0\n20\n0\n11\n1\n21\n0\n0\nENDBLK\n0\nENDSEC\n\
+               0\nSECTION\n2\nENTITIES\n\
+               0\nLWPOLYLINE\n90\n2\n70\n1\n10\n0\n20\n0\n42\n1\n10\n2\n20\n0\n\
+               0\nPOLYLINE\n70\n1\n0\nVERTEX\n10\n0\n20\n0\n0\nVERTEX\n10\n1\n20\n1\n0\nSEQEND\n\
+               0\nTEXT\n1\nhello\n0\nENDSEC\n0\nEOF\n";
+    let document = parse_dxf(dxf).unwrap();
+    assert_eq!(document.header.version.as_deref(), Some("AC1032"));
+    assert_eq!(document.header.insunits, Some(4));
+    assert_eq!(document.blocks["PART"].base, Point { x: 1.0, y: 2.0 });
+    assert_eq!(document.blocks["PART"].entities.len(), 1);
+    assert_eq!(document.entities.len(), 3);
+    match &document.entities[0] {
+      Entity::Polyline {
+        vertices, closed, ..
+      } => {
+        assert!(closed);
+        assert_eq!(vertices[0].bulge, 1.0);
+        assert_eq!(vertices[1].point, Point { x: 2.0, y: 0.0 });
+      }
+      entity => panic!("unexpected entity {:?}", entity),
+    }
+    assert!(
+      matches!(&document.entities[1], Entity::Polyline { vertices, .. } if vertices.len() == 2)
+    );
+    assert_eq!(document.entities[2], Entity::Other("TEXT".to_string()));
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert!(matches!(
+      parse_dxf("0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nabc\n"),
+      Err(DxfError::Parse { line: 8, .. })
+    ));
+    assert!(matches!(
+      parse_dxf_bytes(b"AutoCAD Binary DXF\r\n\x1a\0"),
+      Err(DxfError::BinaryUnsupported)
+    ));
+  }
+}
@@ -0,0 +1,428 @@
+//! Writer for nested sheets as ASCII DXF (R12).
+//!
+//! Contours are written as POLYLINE entities with bulges, so arcs of the source
+//! contours survive. R12 is used because every laser and plasma package reads it.
+//! It has no `$INSUNITS` header variable, so the drawing units are not declared.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use deepnest_types::types::{Point, Polygon};
+
+use super::{DxfContour, DxfExportOptions, DxfPart, DxfSheet};
+
+/// Layer of the sheet outlines.
+const SHEET_LAYER: &str = "SHEET";
+
+/// Collects group code / value pairs.
+struct DxfWriter {
+  out: String,
+}
+
+impl DxfWriter {
+  fn pair(&mut self, code: i32, value: impl std::fmt::Display) {
+    // Writing to a String never fails.
+    let _ = write!(self.out, "{}\n{}\n", code, value);
+  }
+
+  fn point(&mut self, code: i32, p: Point) {
+    self.pair(code, p.x);
+    self.pair(code + 10, p.y);
+  }
+
+  /// A closed POLYLINE with its VERTEX entities and SEQEND.
+  fn polyline(&mut self, layer: &str, points: &[Point], bulges: &[f64]) {
+    self.pair(0, "POLYLINE");
+    self.pair(8, layer);
+    self.pair(66, 1);
+    self.point(10, Point { x: 0.0, y: 0.0 });
+    self.pair(70, 1);
+    for (i, p) in points.iter().enumerate() {
+      self.pair(0, "VERTEX");
+      self.pair(8, layer);
+      self.point(10, *p);
+      let bulge = bulges.get(i).copied().unwrap_or(0.0);
+      if bulge != 0.0 {
+        self.pair(42, bulge);
+      }
+    }
+    self.pair(0, "SEQEND");
+    self.pair(8, layer);
+  }
+
+  fn contour(&mut self, layer: &str, contour: &DxfContour, rotation: f64, offset: Point) {
+    let mut points = contour.points.clone();
+    if points.len() > 1 && points[0] == points[points.len() - 1] {
+      points.pop();
+    }
+    // Rotation and translation keep the bulges unchanged.
+    let rotated = Polygon {
+      points,
+      children: None,
+      offsetx: None,
+      offsety: None,
+    }
+    .rotate(rotation);
+    let points: Vec<Point> = rotated
+      .points
+      .iter()
+      .map(|p| Point {
+        x: p.x + offset.x,
+        y: p.y + offset.y,
+      })
+      .collect();
+    self.polyline(
+      layer,
+      &points,
+      contour.bulges.as_deref().unwrap_or_default(),
+    );
+  }
+}
+
+/// Writes a single sheet with its placed parts as a DXF document.
+///
+/// Every part is written to its own layer named after the part. With `use_blocks`,
+/// each part is defined once as a block and placed with INSERT entities carrying the
+/// rotation; otherwise the transformed contours are written directly.
+pub fn write_sheet(parts: &[DxfPart], sheet: &DxfSheet, options: &DxfExportOptions) -> String {
+  let use_blocks = options.use_blocks.unwrap_or(false);
+  let layers = layer_names(parts);
+  let blocks = block_names(parts);
+  let placed: BTreeSet<usize> = sheet
+    .placements
+    .iter()
+    .map(|placement| placement.part as usize)
+    .filter(|part| *part < parts.len())
+    .collect();
+
+  let mut writer = DxfWriter { out: String::new() };
+  writer.pair(0, "SECTION");
+  writer.pair(2, "HEADER");
+  writer.pair(9, "$ACADVER");
+  writer.pair(1, "AC1009");
+  writer.pair(0, "ENDSEC");
+
+  writer.pair(0, "SECTION");
+  writer.pair(2, "TABLES");
+  // The layers reference the CONTINUOUS line type, which has to be defined first.
+  writer.pair(0, "TABLE");
+  writer.pair(2, "LTYPE");
+  writer.pair(70, 1);
+  writer.pair(0, "LTYPE");
+  writer.pair(2, "CONTINUOUS");
+  writer.pair(70, 0);
+  writer.pair(3, "Solid line");
+  writer.pair(72, 65);
+  writer.pair(73, 0);
+  writer.pair(40, 0.0);
+  writer.pair(0, "ENDTAB");
+  writer.pair(0, "TABLE");
+  writer.pair(2, "LAYER");
+  let mut layer_list: Vec<&str> = placed.iter().map(|part| layers[*part].as_str()).collect();
+  if sheet.outline.is_some() {
+    layer_list.push(SHEET_LAYER);
+  }
+  layer_list.sort_unstable();
+  layer_list.dedup();
+  writer.pair(70, layer_list.len());
+  for (index, layer) in layer_list.iter().enumerate() {
+    writer.pair(0, "LAYER");
+    writer.pair(2, layer);
+    writer.pair(70, 0);
+    // Cycle through the standard colors 1..=6 so parts are easy to tell apart.
+    writer.pair(62, index % 6 + 1);
+    writer.pair(6, "CONTINUOUS");
+  }
+  writer.pair(0, "ENDTAB");
+  writer.pair(0, "ENDSEC");
+
+  if use_blocks {
+    writer.pair(0, "SECTION");
+    writer.pair(2, "BLOCKS");
+    for part in placed.iter() {
+      writer.pair(0, "BLOCK");
+      // Entities on layer 0 take the layer of the INSERT.
+      writer.pair(8, "0");
+      writer.pair(2, &blocks[*part]);
+      writer.pair(70, 0);
+      writer.point(10, Point { x: 0.0, y: 0.0 });
+      writer.pair(3, &blocks[*part]);
+      for contour in parts[*part].contours.iter() {
+        writer.contour("0", contour, 0.0, Point { x: 0.0, y: 0.0 });
+      }
+      writer.pair(0, "ENDBLK");
+      writer.pair(8, "0");
+    }
+    writer.pair(0, "ENDSEC");
+  }
+
+  writer.pair(0, "SECTION");
+  writer.pair(2, "ENTITIES");
+  if let Some(outline) = &sheet.outline {
+    writer.contour(SHEET_LAYER, outline, 0.0, Point { x: 0.0, y: 0.0 });
+  }
+  for placement in sheet.placements.iter() {
+    let index = placement.part as usize;
+    let Some(part) = parts.get(index) else {
+      continue;
+    };
+    let offset = Point {
+      x: placement.x,
+      y: placement.y,
+    };
+    if use_blocks {
+      writer.pair(0, "INSERT");
+      writer.pair(8, &layers[index]);
+      writer.pair(2, &blocks[index]);
+      writer.point(10, offset);
+      if placement.rotation != 0.0 {
+        writer.pair(50, placement.rotation);
+      }
+    } else {
+      for contour in part.contours.iter() {
+        writer.contour(&layers[index], contour, placement.rotation, offset);
+      }
+    }
+  }
+  writer.pair(0, "ENDSEC");
+  writer.pair(0, "EOF");
+  writer.out
+}
+
+/// Replaces the characters DXF does not allow in layer and block names.
+fn sanitize(name: &str) -> String {
+  let name: String = name
+    .chars()
+    .map(|c| match c {
+      '<' | '>' | '/' | '\\' | '"' | ':' | ';' | '?' | '*' | '|' | '=' | '`' | ',' => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .collect();
+  if name.trim().is_empty() {
+    "PART".to_string()
+  } else {
+    name
+  }
+}
+
+fn layer_names(parts: &[DxfPart]) -> Vec<String> {
+  parts.iter().map(|part| sanitize(&part.name)).collect()
+}
+
+/// Block names must be unique, so parts sharing a name get their index appended.
+fn block_names(parts: &[DxfPart]) -> Vec<String> {
+  let mut counts: HashMap<String, usize> = HashMap::new();
+  for part in parts {
+    *counts.entry(sanitize(&part.name)).or_default() += 1;
+  }
+  parts
+    .iter()
+    .enumerate()
+    .map(|(index, part)| {
+      let name = sanitize(&part.name);
+      if counts[&name] > 1 {
+        format!("{}_{}", name, index)
+      } else {
+        name
+      }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dxf::reader::{parse_dxf, Entity};
+  use crate::dxf::{import_document, DxfPlacement};
+  use deepnest_types::types::{CurvePart, CurvePath, Segment};
+  use geometry_utils::linearizer::Linearizer;
+
+  fn parts() -> Vec<DxfPart> {
+    // A 2x2 square whose top edge is a half circle, and a plain 1x1 square.
+    let rounded = DxfContour {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 2.0, y: 0.0 },
+        Point { x: 2.0, y: 2.0 },
+        Point { x: 0.0, y: 2.0 },
+      ],
+      bulges: Some(vec![0.0, 0.0, 1.0, 0.0]),
+    };
+    let square = DxfContour {
+      points: vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 1.0, y: 0.0 },
+        Point { x: 1.0, y: 1.0 },
+        Point { x: 0.0, y: 1.0 },
+      ],
+      bulges: None,
+    };
+    vec![
+      DxfPart {
+        name: "rounded".to_string(),
+        contours: vec![rounded],
+      },
+      DxfPart {
+        name: "square/1".to_string(),
+        contours: vec![square],
+      },
+    ]
+  }
+
+  fn sheet() -> DxfSheet {
+    DxfSheet {
+      outline: None,
+      placements: vec![
+        DxfPlacement {
+          part: 0,
+          x: 10.0,
+          y: 0.0,
+          rotation: 90.0,
+        },
+        DxfPlacement {
+          part: 1,
+          x: 20.0,
+          y: 0.0,
+          rotation: 0.0,
+        },
+      ],
+    }
+  }
+
+  fn areas(dxf: &str) -> Vec<f64> {
+    let (polygons, _, _) = import_document(&parse_dxf(dxf).unwrap(), &Linearizer::new(0.001), 1e-6);
+    let mut areas: Vec<f64> = polygons
+      .iter()
+      .map(|p| -geometry_utils::GeometryUtils::polygon_area(p.points.clone()))
+      .collect();
+    areas.sort_by(f64::total_cmp);
+    areas
+  }
+
+  #[test]
+  fn test_write_sheet_preserves_arcs() {
+    let dxf = write_sheet(&parts(), &sheet(), &DxfExportOptions::default());
+    let document = parse_dxf(&dxf).unwrap();
+    assert_eq!(document.entities.len(), 2);
+    match &document.entities[0] {
+      Entity::Polyline {
+        vertices, closed, ..
+      } => {
+        assert!(closed);
+        assert_eq!(vertices[2].bulge, 1.0);
+        // Rotated by 90 degrees and moved to (10, 0).
+        assert_eq!(vertices[1].point, Point { x: 10.0, y: 2.0 });
+      }
+      entity => panic!("unexpected entity {:?}", entity),
+    }
+    assert!(dxf.contains("\n8\nsquare_1\n"));
+    let areas = areas(&dxf);
+    assert!((areas[0] - 1.0).abs() < 1e-9);
+    assert!((areas[1] - (4.0 + std::f64::consts::PI / 2.0)).abs() < 0.01);
+  }
+
+  #[test]
+  fn test_write_sheet_with_blocks() {
+    let options = DxfExportOptions {
+      use_blocks: Some(true),
+    };
+    let dxf = write_sheet(&parts(), &sheet(), &options);
+    let document = parse_dxf(&dxf).unwrap();
+    assert_eq!(document.header.version.as_deref(), Some("AC1009"));
+    assert_eq!(document.header.insunits, None);
+    assert!(dxf.contains("\n0\nLTYPE\n2\nCONTINUOUS\n"));
+    assert_eq!(document.blocks.len(), 2);
+    assert!(matches!(
+      &document.entities[0],
+      Entity::Insert { name, rotation, .. } if name == "rounded" && *rotation == 90.0
+    ));
+    // Both ways of writing describe the same geometry.
+    let direct = write_sheet(&parts(), &sheet(), &DxfExportOptions::default());
+    let (a, b) = (areas(&dxf), areas(&direct));
+    assert!(a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-9));
+  }
+
+  #[test]
+  fn test_write_sheet_blocks_round_trip() {
+    // Place the rounded part twice, rotated differently, next to the square.
+    let mut sheet = sheet();
+    sheet.placements.push(DxfPlacement {
+      part: 0,
+      x: 30.0,
+      y: 5.0,
+      rotation: 180.0,
+    });
+    let bounds = |dxf: &str| {
+      let (polygons, _, _) =
+        import_document(&parse_dxf(dxf).unwrap(), &Linearizer::new(0.001), 1e-6);
+      let mut bounds: Vec<_> = polygons
+        .iter()
+        .map(|p| geometry_utils::GeometryUtils::get_polygon_bounds(p.points.clone()).unwrap())
+        .map(|b| [b.x, b.y, b.width, b.height])
+        .collect();
+      bounds.sort_by(|a, b| a[0].total_cmp(&b[0]));
+      bounds
+    };
+    let options = DxfExportOptions {
+      use_blocks: Some(true),
+    };
+    let inserted = bounds(&write_sheet(&parts(), &sheet, &options));
+    let direct = bounds(&write_sheet(&parts(), &sheet, &DxfExportOptions::default()));
+    assert_eq!(inserted.len(), 3);
+    assert_eq!(inserted.len(), direct.len());
+    for (a, b) in inserted.iter().zip(direct.iter()) {
+      assert!(a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+    // The copy rotated by 180 degrees lies left of and below its insertion point.
+    let rotated = inserted.iter().find(|b| b[0] > 25.0).unwrap();
+    assert!((rotated[0] - 28.0).abs() < 1e-6);
+    assert!((rotated[1] - 2.0).abs() < 1e-2);
+    assert!((rotated[1] + rotated[3] - 5.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn test_write_curve_part_keeps_circular_arcs() {
+    // A 2x1 rectangle with a half circle on its right side.
+    let slot = CurvePath {
+      start: Point { x: 0.0, y: 0.0 },
+      segments: vec![
+        Segment::Line {
+          to: Point { x: 2.0, y: 0.0 },
+        },
+        Segment::Arc {
+          to: Point { x: 2.0, y: 1.0 },
+          rx: 0.5,
+          ry: 0.5,
+          x_axis_rotation: 0.0,
+          large_arc: false,
+          sweep: true,
+        },
+        Segment::Line {
+          to: Point { x: 0.0, y: 1.0 },
+        },
+      ],
+      closed: true,
+    };
+    let part = CurvePart {
+      outer: slot,
+      holes: vec![],
+    };
+    let dxf_part = DxfPart::from_curve_part("slot", &part, &Linearizer::new(0.01));
+    let bulges = dxf_part.contours[0].bulges.clone().unwrap();
+    assert_eq!(bulges.len(), 4);
+    assert!((bulges[1] - 1.0).abs() < 1e-9);
+    let sheet = DxfSheet {
+      outline: None,
+      placements: vec![DxfPlacement {
+        part: 0,
+        x: 0.0,
+        y: 0.0,
+        rotation: 45.0,
+      }],
+    };
+    let dxf = write_sheet(&[dxf_part], &sheet, &DxfExportOptions::default());
+    let areas = areas(&dxf);
+    assert!((areas[0] - (2.0 + std::f64::consts::PI / 8.0)).abs() < 0.01);
+  }
+}
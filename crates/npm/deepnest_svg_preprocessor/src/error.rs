@@ -0,0 +1,289 @@
+//! Errors of the preprocessor API.
+//!
+//! Every error has a stable code, which is thrown to JavaScript as the `code` of the
+//! error so the application can react to it and localize the message.
+
+use std::fmt;
+use std::fs;
+use std::panic::UnwindSafe;
+use std::path::Path;
+
+use usvg::roxmltree;
+
+use crate::dxf::DxfError;
+use crate::path_data_parser::PathDataError;
+use crate::svg_export::SvgExportError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreprocessorError {
+  FileNotFound {
+    path: String,
+  },
+  /// The file exists but could not be read, or its content is not valid text.
+  Io {
+    path: String,
+    message: String,
+  },
+  /// Malformed document; the position is 1-based where it is known.
+  Parse {
+    line: Option<u32>,
+    column: Option<u32>,
+    message: String,
+  },
+  /// A valid element or format that cannot be processed, such as binary DXF.
+  UnsupportedElement {
+    element: String,
+  },
+  /// An element referenced by id does not exist.
+  MissingElement {
+    id: String,
+  },
+  /// Invalid path data; `offset` is the byte offset in the `d` attribute if known.
+  PathData {
+    offset: Option<usize>,
+    message: String,
+  },
+  /// An argument that cannot be used, such as a mirror line through a single point.
+  InvalidArgument {
+    message: String,
+  },
+  /// A panic caught at the API boundary.
+  Internal {
+    message: String,
+  },
+}
+
+impl PreprocessorError {
+  /// The code of the error, thrown to JavaScript as `error.code`.
+  pub fn code(&self) -> &'static str {
+    match self {
+      PreprocessorError::FileNotFound { .. } => "FILE_NOT_FOUND",
+      PreprocessorError::Io { .. } => "IO_ERROR",
+      PreprocessorError::Parse { .. } => "PARSE_ERROR",
+      PreprocessorError::UnsupportedElement { .. } => "UNSUPPORTED_ELEMENT",
+      PreprocessorError::MissingElement { .. } => "MISSING_ELEMENT",
+      PreprocessorError::PathData { .. } => "PATH_DATA_ERROR",
+      PreprocessorError::InvalidArgument { .. } => "INVALID_ARGUMENT",
+      PreprocessorError::Internal { .. } => "INTERNAL_ERROR",
+    }
+  }
+}
+
+impl fmt::Display for PreprocessorError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PreprocessorError::FileNotFound { path } => write!(f, "File not found: {}", path),
+      PreprocessorError::Io { path, message } if path.is_empty() => {
+        write!(f, "Error reading file: {}", message)
+      }
+      PreprocessorError::Io { path, message } => {
+        write!(f, "Error reading file {}: {}", path, message)
+      }
+      PreprocessorError::Parse {
+        line: Some(line),
+        column: Some(column),
+        message,
+      } => write!(
+        f,
+        "Parse error at line {}, column {}: {}",
+        line, column, message
+      ),
+      PreprocessorError::Parse {
+        line: Some(line),
+        column: None,
+        message,
+      } => write!(f, "Parse error at line {}: {}", line, message),
+      PreprocessorError::Parse {
+        line: None,
+        message,
+        ..
+      } => write!(f, "Parse error: {}", message),
+      PreprocessorError::UnsupportedElement { element } => {
+        write!(f, "Unsupported element: {}", element)
+      }
+      PreprocessorError::MissingElement { id } => {
+        write!(f, "No element with id \"{}\" in the document", id)
+      }
+      PreprocessorError::PathData {
+        offset: Some(offset),
+        message,
+      } => write!(f, "Invalid path data at offset {}: {}", offset, message),
+      PreprocessorError::PathData {
+        offset: None,
+        message,
+      } => write!(f, "Invalid path data: {}", message),
+      PreprocessorError::InvalidArgument { message } => {
+        write!(f, "Invalid argument: {}", message)
+      }
+      PreprocessorError::Internal { message } => write!(f, "Internal error: {}", message),
+    }
+  }
+}
+
+impl std::error::Error for PreprocessorError {}
+
+#[cfg(feature = "node")]
+impl From<PreprocessorError> for napi::Error<&'static str> {
+  fn from(err: PreprocessorError) -> Self {
+    napi::Error::new(err.code(), err.to_string())
+  }
+}
+
+impl From<roxmltree::Error> for PreprocessorError {
+  fn from(err: roxmltree::Error) -> Self {
+    let pos = err.pos();
+    // The message ends with the position, which is shown separately.
+    let message = err.to_string();
+    let suffix = format!(" at {}:{}", pos.row, pos.col);
+    PreprocessorError::Parse {
+      line: Some(pos.row),
+      column: Some(pos.col),
+      message: message
+        .strip_suffix(&suffix)
+        .unwrap_or(&message)
+        .to_string(),
+    }
+  }
+}
+
+impl From<usvg::Error> for PreprocessorError {
+  fn from(err: usvg::Error) -> Self {
+    match err {
+      usvg::Error::ParsingFailed(err) => err.into(),
+      err => PreprocessorError::Parse {
+        line: None,
+        column: None,
+        message: err.to_string(),
+      },
+    }
+  }
+}
+
+impl From<PathDataError> for PreprocessorError {
+  fn from(err: PathDataError) -> Self {
+    PreprocessorError::PathData {
+      offset: Some(err.offset),
+      message: err.message,
+    }
+  }
+}
+
+impl From<DxfError> for PreprocessorError {
+  fn from(err: DxfError) -> Self {
+    match err {
+      DxfError::Io(message) => PreprocessorError::Io {
+        path: String::new(),
+        message,
+      },
+      DxfError::Parse { line, message } => PreprocessorError::Parse {
+        line: Some(line as u32),
+        column: None,
+        message,
+      },
+      DxfError::BinaryUnsupported => PreprocessorError::UnsupportedElement {
+        element: "binary DXF".to_string(),
+      },
+    }
+  }
+}
+
+impl From<SvgExportError> for PreprocessorError {
+  fn from(err: SvgExportError) -> Self {
+    match err {
+      SvgExportError::MissingPart(id) => PreprocessorError::MissingElement { id },
+      err => PreprocessorError::Parse {
+        line: None,
+        column: None,
+        message: err.to_string(),
+      },
+    }
+  }
+}
+
+/// Reads a file, telling a missing file apart from other read errors.
+pub fn read_file(path: &str) -> Result<Vec<u8>, PreprocessorError> {
+  if !Path::new(path).exists() {
+    return Err(PreprocessorError::FileNotFound {
+      path: path.to_string(),
+    });
+  }
+  fs::read(path).map_err(|err| PreprocessorError::Io {
+    path: path.to_string(),
+    message: err.to_string(),
+  })
+}
+
+/// Runs `f`, turning a panic into `PreprocessorError::Internal` so it does not take
+/// down the Node.js process.
+#[cfg_attr(not(feature = "node"), allow(dead_code))]
+pub fn catch_internal<T, F>(f: F) -> Result<T, PreprocessorError>
+where
+  F: FnOnce() -> Result<T, PreprocessorError> + UnwindSafe,
+{
+  std::panic::catch_unwind(f).unwrap_or_else(|payload| {
+    let message = if let Some(s) = payload.downcast_ref::<String>() {
+      s.clone()
+    } else if let Some(s) = payload.downcast_ref::<&str>() {
+      s.to_string()
+    } else {
+      "unknown panic".to_string()
+    };
+    Err(PreprocessorError::Internal { message })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_error_position_and_code() {
+    let err: PreprocessorError = roxmltree::Document::parse("<svg>\n  <g>\n</svg>")
+      .unwrap_err()
+      .into();
+    assert!(matches!(
+      err,
+      PreprocessorError::Parse {
+        line: Some(3),
+        column: Some(1),
+        ..
+      }
+    ));
+    assert!(err
+      .to_string()
+      .starts_with("Parse error at line 3, column 1: "));
+    assert!(!err.to_string().ends_with("3:1"));
+    #[cfg(feature = "node")]
+    {
+      let napi_err: napi::Error<&'static str> = err.into();
+      assert_eq!(napi_err.status, "PARSE_ERROR");
+    }
+
+    let err: PreprocessorError = DxfError::Parse {
+      line: 8,
+      message: "invalid group code `x`".to_string(),
+    }
+    .into();
+    assert_eq!(
+      err.to_string(),
+      "Parse error at line 8: invalid group code `x`"
+    );
+  }
+
+  #[test]
+  fn test_read_file_and_catch_internal() {
+    assert_eq!(
+      read_file("/nonexistent/drawing.svg"),
+      Err(PreprocessorError::FileNotFound {
+        path: "/nonexistent/drawing.svg".to_string()
+      })
+    );
+    let result: Result<(), _> = catch_internal(|| panic!("boom"));
+    assert_eq!(
+      result,
+      Err(PreprocessorError::Internal {
+        message: "boom".to_string()
+      })
+    );
+  }
+}
@@ -0,0 +1,470 @@
+//! Extraction of nestable parts from an SVG document.
+//!
+//! The usvg tree is flattened with all transforms applied, every subpath is turned
+//! into a `CurvePath` and linearized, open paths are joined into closed loops and the
+//! loops are nested into parts with holes. Each part keeps its curves next to the
+//! polygon; usvg has already converted arcs to cubic curves.
+//!
+//! Text is converted to outlines with the fonts of `ExtractPartsOptions::fonts`. The
+//! outlines are engraving geometry: they move with the part that contains them but
+//! are kept out of the part polygon, so they take no part in NFP generation.
+
+use deepnest_types::types::{CurvePart, CurvePath, Point, Polygon, Segment};
+use geometry_utils::curve::linearize_path;
+use geometry_utils::linearizer::Linearizer;
+use geometry_utils::GeometryUtils;
+use usvg::tiny_skia_path::{self, PathSegment, Transform};
+use usvg::{Group, Node, Tree};
+
+use crate::contours::{
+  clean_ring, join_open, nest_rings, unmatched_endpoints, CurvePartData, CurvePiece,
+};
+use crate::error::PreprocessorError;
+use crate::fonts::{usvg_options, FontOptions};
+use crate::points_on_path::linearizer;
+use crate::units::{detect_units, SvgSource, SvgUnitReport};
+
+/// Default distance within which the ends of open paths are joined.
+pub const DEFAULT_JOIN_TOLERANCE: f64 = 1e-4;
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct ExtractPartsOptions {
+  /// maximum deviation of linearized curves, see `Linearizer`
+  pub tolerance: Option<f64>,
+  /// minimum length of the segments of linearized curves, 0 by default
+  pub min_segment_length: Option<f64>,
+  /// distance within which open paths are joined, defaults to 1e-4 user units
+  pub join_tolerance: Option<f64>,
+  /// DPI for px and unitless lengths, see `detect_units`
+  pub dpi: Option<f64>,
+  /// application that wrote the document, detected if unset
+  pub source: Option<SvgSource>,
+  /// fonts for text; without them text is dropped
+  pub fonts: Option<FontOptions>,
+}
+
+/// A text outline, to be engraved or marked instead of cut.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgEngraving {
+  pub points: Vec<Point>,
+  pub closed: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgPart {
+  /// id of the element the outer contour was taken from, if it has one
+  pub id: Option<String>,
+  /// outer contour in user units with all transforms applied, holes as `children`
+  pub polygon: Polygon,
+  /// the contours of `polygon` before linearization, as SVG path data
+  pub curve: CurvePartData,
+  /// text outlines on the part, in the same coordinates as `polygon`
+  pub engravings: Vec<SvgEngraving>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct ExtractPartsResult {
+  pub parts: Vec<SvgPart>,
+  /// open paths that could not be joined into closed loops
+  pub open_paths: Vec<Vec<Point>>,
+  /// the ends of `open_paths`, where the drawing has gaps
+  pub unmatched_endpoints: Vec<Point>,
+  /// text outlines that do not lie on any part
+  pub engravings: Vec<SvgEngraving>,
+  /// units of the document; `scale_to_mm` converts the coordinates to millimeters
+  pub units: SvgUnitReport,
+}
+
+/// A subpath and its linearization, with the id of its element.
+struct Subpath {
+  id: String,
+  piece: CurvePiece,
+  /// the subpath is a text outline
+  engraving: bool,
+}
+
+/// Extracts the closed contours of `svg_data` as parts with holes.
+pub fn extract_parts(
+  svg_data: &str,
+  options: Option<ExtractPartsOptions>,
+) -> Result<ExtractPartsResult, PreprocessorError> {
+  extract(svg_data, options).map(|(result, _)| result)
+}
+
+/// Extracts the parts of `svg_data` like `extract_parts`, keeping their line and
+/// cubic segments. The parts come in the same order as `ExtractPartsResult::parts`.
+pub fn extract_curve_parts(
+  svg_data: &str,
+  options: Option<ExtractPartsOptions>,
+) -> Result<Vec<CurvePart>, PreprocessorError> {
+  extract(svg_data, options).map(|(_, curves)| curves)
+}
+
+fn extract(
+  svg_data: &str,
+  options: Option<ExtractPartsOptions>,
+) -> Result<(ExtractPartsResult, Vec<CurvePart>), PreprocessorError> {
+  let options = options.unwrap_or_default();
+  let join_tolerance = options.join_tolerance.unwrap_or(DEFAULT_JOIN_TOLERANCE);
+  let units = detect_units(svg_data, options.dpi, options.source)?;
+  let config = usvg_options(units.dpi, options.fonts.as_ref())?;
+  let tree = Tree::from_str(svg_data, &config)?;
+
+  let linearizer = linearizer(options.tolerance, options.min_segment_length);
+  let mut subpaths = vec![];
+  collect_subpaths(
+    tree.root(),
+    Transform::identity(),
+    &linearizer,
+    false,
+    &mut subpaths,
+  );
+
+  let mut rings = vec![];
+  let mut ids = vec![];
+  let mut open = vec![];
+  let mut engravings = vec![];
+  for subpath in subpaths {
+    if subpath.engraving {
+      engravings.push(SvgEngraving {
+        points: subpath.piece.points,
+        closed: subpath.piece.path.closed,
+      });
+    } else if subpath.piece.path.closed {
+      rings.push(subpath.piece);
+      ids.push(Some(subpath.id).filter(|id| !id.is_empty()));
+    } else if subpath.piece.points.len() > 1 {
+      open.push(subpath.piece);
+    }
+  }
+  let (joined, open_paths) = join_open(open, join_tolerance);
+  ids.extend(joined.iter().map(|_| None));
+  rings.extend(joined);
+  let open_paths: Vec<Vec<Point>> = open_paths.into_iter().map(|piece| piece.points).collect();
+
+  let mut cleaned = vec![];
+  let mut paths = vec![];
+  let mut ring_ids = vec![];
+  for (piece, id) in rings.into_iter().zip(ids) {
+    let ring = clean_ring(piece.points, join_tolerance);
+    if ring.len() >= 3 {
+      cleaned.push(ring);
+      paths.push(piece.path);
+      ring_ids.push(id);
+    }
+  }
+
+  let mut parts = vec![];
+  let mut curves = vec![];
+  for part in nest_rings(cleaned) {
+    let curve = part.curve_part(&paths);
+    parts.push(SvgPart {
+      id: ring_ids[part.outer.0].clone(),
+      polygon: part.polygon,
+      curve: CurvePartData::from(&curve),
+      engravings: vec![],
+    });
+    curves.push(curve);
+  }
+  let mut loose = vec![];
+  for engraving in engravings {
+    match part_containing(&parts, &engraving.points) {
+      Some(index) => parts[index].engravings.push(engraving),
+      None => loose.push(engraving),
+    }
+  }
+  let result = ExtractPartsResult {
+    parts,
+    unmatched_endpoints: unmatched_endpoints(&open_paths),
+    open_paths,
+    engravings: loose,
+    units,
+  };
+  Ok((result, curves))
+}
+
+/// Index of the smallest part whose material contains the first decidable point of
+/// `points`, i.e. which contains it in its outer contour and in none of its holes.
+fn part_containing(parts: &[SvgPart], points: &[Point]) -> Option<usize> {
+  let inside = |ring: &Vec<Point>| {
+    let ring = Polygon {
+      points: ring.clone(),
+      children: None,
+      offsetx: None,
+      offsety: None,
+    };
+    points
+      .iter()
+      .find_map(|p| GeometryUtils::point_in_polygon(*p, ring.clone(), None))
+      .unwrap_or(false)
+  };
+  parts
+    .iter()
+    .enumerate()
+    .filter(|(_, part)| {
+      inside(&part.polygon.points) && !part.polygon.children.iter().flatten().any(&inside)
+    })
+    .min_by(|(_, a), (_, b)| {
+      let area = |part: &SvgPart| GeometryUtils::polygon_area(part.polygon.points.clone()).abs();
+      area(a).total_cmp(&area(b))
+    })
+    .map(|(index, _)| index)
+}
+
+/// Collects and linearizes the visible subpaths below `group`. The group transforms
+/// are composed here instead of using `abs_transform`, which applies `<use>`
+/// transforms twice.
+fn collect_subpaths(
+  group: &Group,
+  ts: Transform,
+  linearizer: &Linearizer,
+  engraving: bool,
+  subpaths: &mut Vec<Subpath>,
+) {
+  for node in group.children() {
+    match node {
+      Node::Group(child) => collect_subpaths(
+        child,
+        ts.pre_concat(child.transform()),
+        linearizer,
+        engraving,
+        subpaths,
+      ),
+      Node::Path(path) => {
+        if !path.is_visible() {
+          continue;
+        }
+        let Some(data) = path.data().clone().transform(ts) else {
+          continue;
+        };
+        for curve in curve_paths(&data) {
+          subpaths.push(Subpath {
+            id: path.id().to_string(),
+            piece: CurvePiece {
+              points: linearize_path(&curve, linearizer),
+              path: curve,
+            },
+            engraving,
+          });
+        }
+      }
+      Node::Text(text) => {
+        let flattened = text.flattened();
+        collect_subpaths(
+          flattened,
+          ts.pre_concat(flattened.transform()),
+          linearizer,
+          true,
+          subpaths,
+        )
+      }
+      // Raster images have no outline to cut.
+      Node::Image(_) => {}
+    }
+  }
+}
+
+/// Splits usvg path data into subpaths. Quadratic curves are raised to cubic ones.
+fn curve_paths(data: &tiny_skia_path::Path) -> Vec<CurvePath> {
+  let point = |p: tiny_skia_path::Point| Point {
+    x: p.x as f64,
+    y: p.y as f64,
+  };
+  let mut paths = vec![];
+  let mut current: Option<CurvePath> = None;
+  // Where a subpath starts if it does not begin with a move, e.g. after a close.
+  let mut origin = Point { x: 0.0, y: 0.0 };
+  let mut last = origin;
+  for segment in data.segments() {
+    let segment = match segment {
+      PathSegment::MoveTo(p) => {
+        paths.extend(current.take());
+        origin = point(p);
+        last = origin;
+        current = Some(CurvePath {
+          start: origin,
+          segments: vec![],
+          closed: false,
+        });
+        continue;
+      }
+      PathSegment::Close => {
+        if let Some(mut path) = current.take() {
+          path.closed = true;
+          paths.push(path);
+        }
+        last = origin;
+        continue;
+      }
+      PathSegment::LineTo(p) => Segment::Line { to: point(p) },
+      PathSegment::QuadTo(control, p) => {
+        let (control, to) = (point(control), point(p));
+        let raise = |end: Point| Point {
+          x: end.x + (control.x - end.x) * 2.0 / 3.0,
+          y: end.y + (control.y - end.y) * 2.0 / 3.0,
+        };
+        Segment::Cubic {
+          control1: raise(last),
+          control2: raise(to),
+          to,
+        }
+      }
+      PathSegment::CubicTo(control1, control2, p) => Segment::Cubic {
+        control1: point(control1),
+        control2: point(control2),
+        to: point(p),
+      },
+    };
+    last = segment.to();
+    current
+      .get_or_insert_with(|| CurvePath {
+        start: origin,
+        segments: vec![],
+        closed: false,
+      })
+      .segments
+      .push(segment);
+  }
+  paths.extend(current);
+  paths
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_extract_parts_with_holes_and_joined_paths() {
+    let svg = r#"<svg width="100" height="100" xmlns="http://www.w3.org/2000/svg">
+<g transform="translate(10 0)">
+<path id="frame" d="M 0 0 H 40 V 40 H 0 Z M 10 10 V 30 H 30 V 10 Z" fill-rule="evenodd"/>
+</g>
+<path d="M 60 0 L 80 0 L 80 20"/>
+<path d="M 60 20 L 60 0"/>
+<path d="M 80 20 L 60 20"/>
+<path d="M 0 60 L 20 60"/>
+</svg>"#;
+    let result = extract_parts(svg, None).unwrap();
+    assert_eq!(result.parts.len(), 2);
+
+    let frame = &result.parts[0];
+    assert_eq!(frame.id.as_deref(), Some("frame"));
+    let holes = frame.polygon.children.as_ref().unwrap();
+    assert_eq!(holes.len(), 1);
+    // The transform of the group is applied, and the outer contour runs counter-clockwise.
+    assert!(frame
+      .polygon
+      .points
+      .iter()
+      .all(|p| p.x >= 10.0 && p.x <= 50.0));
+    assert!(GeometryUtils::almost_equal(
+      GeometryUtils::polygon_area(frame.polygon.points.clone()),
+      -1600.0,
+      None
+    ));
+    assert!(GeometryUtils::polygon_area(holes[0].clone()) > 0.0);
+
+    // The curves are kept for export, oriented like the polygon.
+    assert_eq!(frame.curve.outer, "M10 0 50 0 50 40 10 40Z");
+    assert_eq!(frame.curve.holes, vec!["M20 10 20 30 40 30 40 10Z"]);
+
+    // The three open pieces form a square, the last line stays open.
+    let square = &result.parts[1];
+    assert_eq!(square.id, None);
+    assert_eq!(square.polygon.points.len(), 4);
+    assert_eq!(result.open_paths.len(), 1);
+    assert_eq!(
+      result.open_paths[0],
+      vec![Point { x: 0.0, y: 60.0 }, Point { x: 20.0, y: 60.0 }]
+    );
+    assert_eq!(result.unmatched_endpoints, result.open_paths[0]);
+  }
+
+  #[test]
+  fn test_extract_curve_parts_keep_cubics() {
+    // A quadratic bump on a square, joined from two open paths, around a circular hole.
+    let svg = r#"<svg width="100" height="100" xmlns="http://www.w3.org/2000/svg">
+<path d="M 0 0 L 40 0 L 40 40 L 0 40"/>
+<path d="M 0 0 Q -20 20 0 40"/>
+<circle cx="20" cy="20" r="10"/>
+</svg>"#;
+    let parts = extract_curve_parts(svg, None).unwrap();
+    assert_eq!(parts.len(), 1);
+    let part = &parts[0];
+    assert_eq!(part.holes.len(), 1);
+    let cubics = |path: &CurvePath| {
+      path
+        .segments
+        .iter()
+        .filter(|segment| matches!(segment, Segment::Cubic { .. }))
+        .count()
+    };
+    assert!(part.outer.closed);
+    assert_eq!(cubics(&part.outer), 1);
+    assert_eq!(cubics(&part.holes[0]), 4);
+    // The curves have the orientation of the polygon.
+    let linearizer = Linearizer::new(0.01);
+    let outer = linearize_path(&part.outer, &linearizer);
+    let hole = linearize_path(&part.holes[0], &linearizer);
+    assert!(GeometryUtils::polygon_area(outer.clone()) < 0.0);
+    assert!(GeometryUtils::polygon_area(hole) > 0.0);
+    assert!(outer.iter().any(|p| p.x < -9.0));
+  }
+
+  #[test]
+  fn test_extract_parts_invalid_svg() {
+    assert!(matches!(
+      extract_parts("<svg", None),
+      Err(PreprocessorError::Parse { .. })
+    ));
+  }
+
+  #[test]
+  fn test_extract_parts_text_as_engraving() {
+    // A subset of Tuffy with only "A", "B" and "C".
+    let font_file = concat!(env!("CARGO_MANIFEST_DIR"), "/__test__/fonts/Tuffy-ABC.ttf");
+    let svg = r#"<svg width="300" height="100" xmlns="http://www.w3.org/2000/svg">
+<rect id="plate" width="100" height="100"/>
+<text x="10" y="60" font-family="Tuffy" font-size="40">AB</text>
+<text x="200" y="60" font-family="Tuffy" font-size="40">C</text>
+</svg>"#;
+    let options = ExtractPartsOptions {
+      fonts: Some(FontOptions {
+        font_files: Some(vec![font_file.to_string()]),
+        ..FontOptions::default()
+      }),
+      ..ExtractPartsOptions::default()
+    };
+    let result = extract_parts(svg, Some(options)).unwrap();
+    // The glyphs are not parts of their own and do not become holes of the plate.
+    assert_eq!(result.parts.len(), 1);
+    let plate = &result.parts[0];
+    assert_eq!(plate.id.as_deref(), Some("plate"));
+    assert_eq!(plate.polygon.children, None);
+    // "A" and "B" each have an outer contour and a counter.
+    assert!(plate.engravings.len() >= 4);
+    assert!(plate.engravings.iter().all(|e| e.closed));
+    assert!(!result.engravings.is_empty());
+    assert!(result.engravings[0].points.iter().all(|p| p.x >= 200.0));
+
+    // Without fonts usvg drops the text.
+    let result = extract_parts(svg, None).unwrap();
+    assert!(result.parts[0].engravings.is_empty() && result.engravings.is_empty());
+
+    let options = ExtractPartsOptions {
+      fonts: Some(FontOptions {
+        font_dirs: Some(vec!["/nonexistent/fonts".to_string()]),
+        ..FontOptions::default()
+      }),
+      ..ExtractPartsOptions::default()
+    };
+    assert_eq!(
+      extract_parts(svg, Some(options)).unwrap_err().code(),
+      "FILE_NOT_FOUND"
+    );
+  }
+}
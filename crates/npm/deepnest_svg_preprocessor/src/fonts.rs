@@ -0,0 +1,60 @@
+//! Fonts for converting `<text>` to outlines.
+//!
+//! usvg drops text whose font cannot be found, and its default options come with an
+//! empty font database, so text only survives loading if fonts are given here.
+
+use std::path::Path;
+
+use crate::error::PreprocessorError;
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct FontOptions {
+  /// directories searched recursively for font files
+  pub font_dirs: Option<Vec<String>>,
+  /// single font files (TrueType, OpenType or collections)
+  pub font_files: Option<Vec<String>>,
+  /// also load the fonts installed on the system, defaults to false
+  pub system_fonts: Option<bool>,
+  /// family for text without a `font-family`, defaults to "Times New Roman" as in usvg
+  pub default_family: Option<String>,
+}
+
+/// usvg options with the given DPI and the fonts of `fonts`.
+pub fn usvg_options(
+  dpi: f64,
+  fonts: Option<&FontOptions>,
+) -> Result<usvg::Options<'static>, PreprocessorError> {
+  let mut options = usvg::Options {
+    dpi: dpi as f32,
+    ..usvg::Options::default()
+  };
+  let Some(fonts) = fonts else {
+    return Ok(options);
+  };
+  if let Some(family) = &fonts.default_family {
+    options.font_family = family.clone();
+  }
+  let database = options.fontdb_mut();
+  if fonts.system_fonts.unwrap_or(false) {
+    database.load_system_fonts();
+  }
+  for dir in fonts.font_dirs.iter().flatten() {
+    if !Path::new(dir).is_dir() {
+      return Err(PreprocessorError::FileNotFound { path: dir.clone() });
+    }
+    database.load_fonts_dir(dir);
+  }
+  for file in fonts.font_files.iter().flatten() {
+    if !Path::new(file).exists() {
+      return Err(PreprocessorError::FileNotFound { path: file.clone() });
+    }
+    database
+      .load_font_file(file)
+      .map_err(|err| PreprocessorError::Io {
+        path: file.clone(),
+        message: err.to_string(),
+      })?;
+  }
+  Ok(options)
+}
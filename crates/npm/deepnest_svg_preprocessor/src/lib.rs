@@ -1,162 +1,323 @@
 #![deny(clippy::all)]
+#[cfg(feature = "node")]
 #[macro_use]
 extern crate napi_derive;
+#[cfg(not(feature = "node"))]
+#[macro_use]
+extern crate napi_noop;
+mod contours;
 mod convex_hull;
+mod curve_fit;
+mod dxf;
+mod error;
+mod extract;
+mod fonts;
+mod path_data;
 mod path_data_parser;
 mod points_on_curve;
 mod points_on_path;
 mod simplify_polygon;
+mod svg_export;
 mod svg_flip; // added new module for SVG flip
-use std::fs;
+mod units;
+#[cfg(feature = "node")]
 use std::path::Path;
+#[cfg(feature = "node")]
 use usvg::Tree;
 
+#[cfg(feature = "node")]
+use error::{catch_internal, read_file};
+
 // Export both internal functions for benchmarking and testing
+pub use contours::{join_fragments, CurvePartData, JoinPathsResult};
 pub use convex_hull::compute_convex_hull;
-pub use points_on_curve::Point;
+pub use curve_fit::{catmull_rom, fit_curve};
+pub use dxf::{
+  export_dxf_sheets, import_dxf_curve_parts, import_dxf_file, import_dxf_string, DxfContour,
+  DxfError, DxfExportOptions, DxfImportOptions, DxfImportReport, DxfImportResult, DxfPart,
+  DxfPlacement, DxfSheet, DxfUnitReport,
+};
+pub use error::PreprocessorError;
+pub use extract::{
+  extract_curve_parts, extract_parts, ExtractPartsOptions, ExtractPartsResult, SvgEngraving,
+  SvgPart,
+};
+pub use fonts::FontOptions;
+pub use geometry_utils::linearizer::Linearizer;
+pub use path_data::PathData;
+pub use path_data_parser::ArcMode;
+pub use path_data_parser::Segment as PathSegment;
+pub use path_data_parser::{PathCommand, PathDataError, PathParser};
+pub use points_on_curve::{curve_to_bezier, Point};
 pub use points_on_path::points_on_path;
 pub use points_on_path::points_on_path_with_closed_info;
-pub use simplify_polygon::simplify_polygon;
+pub use simplify_polygon::{simplify_part, simplify_polygon, simplify_polygon_conservative};
 
-// Re-export SVG flip API
-pub use svg_flip::{flip_svg_file, flip_svg_string, FlipDirection, FlipSVGResult};
+pub use svg_export::{
+  parse_nest_svg, write_nest_svg, SvgExportError, SvgNestExportOptions, SvgNestPlacement,
+  SvgNestSheet,
+};
 
-// Thread-local cache for reusing the panic handler
-thread_local! {
-    static PANIC_MSG: std::cell::RefCell<String> = std::cell::RefCell::new(String::with_capacity(128));
-}
+pub use units::{detect_units, SvgSource, SvgUnitReport};
 
+// Re-export SVG flip API
+pub use svg_flip::{flip_svg_file, flip_svg_string, FlipDirection};
+
+/// Linearizes SVG path data into point sets. Errors are thrown with the codes of
+/// `PreprocessorError`, e.g. `PATH_DATA_ERROR` with the byte offset in the message.
+/// A command with missing parameters is an error wherever it is in the path.
+/// Arcs are approximated with cubic curves unless `arcs` is `ArcMode.Exact`.
+/// `tolerance` is the maximum distance between a curve and its chords, see `Linearizer`.
+/// It defaults to 0.3. It used to be a flatness threshold with a default of 0.15, so
+/// curves now give different points, usually fewer.
+#[cfg(feature = "node")]
 #[napi]
 pub fn points_on_svg_path(
   path: String,
   tolerance: Option<f64>,
   distance: Option<f64>,
-) -> napi::Result<Vec<Vec<points_on_curve::Point>>> {
-  // Wrap the call with panic handling to avoid crashing Node.js
-  // We use thread-local storage for panic messages to avoid allocations in the hot path
-  PANIC_MSG.with(|msg_cell| {
-    let msg = &mut *msg_cell.borrow_mut();
-    msg.clear();
-
-    let result =
-      std::panic::catch_unwind(|| points_on_path::points_on_path(path, tolerance, distance));
-
-    match result {
-      Ok(path_result) => path_result,
-      Err(err) => {
-        if let Some(s) = err.downcast_ref::<String>() {
-          *msg = s.clone();
-        } else if let Some(s) = err.downcast_ref::<&str>() {
-          *msg = s.to_string();
-        } else {
-          *msg = "Unknown internal error".to_string();
-        }
-        Err(napi::Error::from_reason(format!(
-          "Internal error processing SVG path: {}",
-          msg
-        )))
-      }
-    }
-  })
+  arcs: Option<ArcMode>,
+  min_segment_length: Option<f64>,
+) -> napi::Result<Vec<Vec<points_on_curve::Point>>, &'static str> {
+  let linearizer = points_on_path::linearizer(tolerance, min_segment_length);
+  // A panic must not take down the Node.js process.
+  Ok(catch_internal(|| {
+    points_on_path::points_on_path(path, &linearizer, distance, arcs)
+  })?)
 }
 
+#[cfg(feature = "node")]
 #[napi]
 pub fn points_on_svg_path_with_closed_info(
   path: String,
   tolerance: Option<f64>,
   distance: Option<f64>,
-) -> napi::Result<points_on_path::PathResult> {
-  // Wrap the call with panic handling to avoid crashing Node.js
-  PANIC_MSG.with(|msg_cell| {
-    let msg = &mut *msg_cell.borrow_mut();
-    msg.clear();
-
-    let result = std::panic::catch_unwind(|| {
-      points_on_path::points_on_path_with_closed_info(path, tolerance, distance)
-    });
-
-    match result {
-      Ok(path_result) => path_result,
-      Err(err) => {
-        if let Some(s) = err.downcast_ref::<String>() {
-          *msg = s.clone();
-        } else if let Some(s) = err.downcast_ref::<&str>() {
-          *msg = s.to_string();
-        } else {
-          *msg = "Unknown internal error".to_string();
-        }
-        Err(napi::Error::from_reason(format!(
-          "Internal error processing SVG path: {}",
-          msg
-        )))
-      }
-    }
-  })
+  arcs: Option<ArcMode>,
+  min_segment_length: Option<f64>,
+) -> napi::Result<points_on_path::PathResult, &'static str> {
+  let linearizer = points_on_path::linearizer(tolerance, min_segment_length);
+  Ok(catch_internal(|| {
+    points_on_path::points_on_path_with_closed_info(path, &linearizer, distance, arcs)
+  })?)
 }
 
-#[napi(object)]
+#[cfg_attr(feature = "node", napi(object))]
 pub struct LoadSVGResult {
-  pub success: bool,
   pub result: String,
+  /// units of the document and the scale of `result`
+  pub units: SvgUnitReport,
 }
 
+/// Normalizes an SVG document with usvg. px and unitless lengths are converted with
+/// `dpi_scale`, or else with the DPI of `source` or of the detected application.
+/// Text is converted to outlines if its font is among `fonts`, and dropped otherwise.
+#[cfg(feature = "node")]
 #[napi]
-pub fn load_svg_string(svg_data: String, dpi_scale: Option<f64>) -> LoadSVGResult {
-  let mut config = usvg::Options::default();
-  config.dpi = dpi_scale.unwrap_or(72.0) as f64 as f32;
-  match Tree::from_str(&svg_data, &config) {
-    Ok(tree) => {
-      let output = tree.to_string(&usvg::WriteOptions::default());
-      LoadSVGResult {
-        success: true,
-        result: output,
-      }
-    }
-    Err(err) => LoadSVGResult {
-      success: false,
-      result: format!("Fehler beim Parsen der SVG: {}", err),
-    },
-  }
+pub fn load_svg_string(
+  svg_data: String,
+  dpi_scale: Option<f64>,
+  source: Option<SvgSource>,
+  fonts: Option<FontOptions>,
+) -> napi::Result<LoadSVGResult, &'static str> {
+  Ok(load_svg(&svg_data, dpi_scale, source, fonts.as_ref())?)
 }
 
+/// Normalizes an SVG or SVGZ file, see `load_svg_string`.
+#[cfg(feature = "node")]
 #[napi]
-pub fn load_svg_file(svg_path: String, dpi_scale: Option<f64>) -> LoadSVGResult {
-  let mut config = usvg::Options::default();
-  config.dpi = dpi_scale.unwrap_or(72.0) as f64 as f32;
-  // Konvertieren Sie den String in ein Path
-  let path = Path::new(&svg_path);
-
-  // Prüfen, ob die Datei existiert
-  if !path.exists() {
-    return LoadSVGResult {
-      success: false,
-      result: format!("Datei nicht gefunden: {}", svg_path),
-    };
-  }
-
-  // SVG-Datei einlesen
-  let svg_data = match fs::read(path) {
-    Ok(data) => data,
-    Err(err) => {
-      return LoadSVGResult {
-        success: false,
-        result: format!("Fehler beim Einlesen der Datei: {}", err),
-      };
-    }
+pub fn load_svg_file(
+  svg_path: String,
+  dpi_scale: Option<f64>,
+  source: Option<SvgSource>,
+  fonts: Option<FontOptions>,
+) -> napi::Result<LoadSVGResult, &'static str> {
+  let svg_data = read_file(&svg_path)?;
+  let io_error = |message: String| PreprocessorError::Io {
+    path: svg_path.clone(),
+    message,
   };
+  // Compressed files are inflated first, so the units can be read from the text.
+  let svg_data = if svg_data.starts_with(&[0x1f, 0x8b]) {
+    usvg::decompress_svgz(&svg_data).map_err(|err| io_error(err.to_string()))?
+  } else {
+    svg_data
+  };
+  let svg_data = String::from_utf8(svg_data).map_err(|err| io_error(err.to_string()))?;
+  Ok(load_svg(&svg_data, dpi_scale, source, fonts.as_ref())?)
+}
 
-  // SVG in usvg::Tree parsen
-  match Tree::from_data(&svg_data, &usvg::Options::default()) {
-    Ok(tree) => {
-      let output = tree.to_string(&usvg::WriteOptions::default());
-      LoadSVGResult {
-        success: true,
-        result: output,
-      }
-    }
-    Err(err) => LoadSVGResult {
-      success: false,
-      result: format!("Fehler beim Parsen der SVG: {}", err),
-    },
+#[cfg(feature = "node")]
+fn load_svg(
+  svg_data: &str,
+  dpi_scale: Option<f64>,
+  source: Option<SvgSource>,
+  fonts: Option<&FontOptions>,
+) -> Result<LoadSVGResult, PreprocessorError> {
+  let units = units::detect_units(svg_data, dpi_scale, source)?;
+  let config = fonts::usvg_options(units.dpi, fonts)?;
+  let tree = Tree::from_str(svg_data, &config)?;
+  Ok(LoadSVGResult {
+    result: tree.to_string(&usvg::WriteOptions::default()),
+    units,
+  })
+}
+
+/// Extracts the closed contours of an SVG document as parts with holes, with all
+/// transforms applied. Open paths that could not be closed are returned separately.
+/// Text outlines are returned as engravings of the part they lie on.
+#[cfg(feature = "node")]
+#[napi(js_name = "extractParts")]
+pub fn extract_svg_parts(
+  svg_data: String,
+  options: Option<ExtractPartsOptions>,
+) -> napi::Result<ExtractPartsResult, &'static str> {
+  Ok(extract::extract_parts(&svg_data, options)?)
+}
+
+/// Stitches open path fragments into closed loops, joining ends within `tolerance`
+/// (defaults to 1e-4) and reversing fragments as needed. The ends of the chains that
+/// stay open are reported so the drawing can be repaired.
+#[cfg_attr(feature = "node", napi)]
+pub fn join_path_fragments(
+  fragments: Vec<Vec<deepnest_types::types::Point>>,
+  tolerance: Option<f64>,
+) -> JoinPathsResult {
+  contours::join_fragments(
+    fragments,
+    tolerance.unwrap_or(extract::DEFAULT_JOIN_TOLERANCE),
+  )
+}
+
+/// Replaces a dense polyline, such as a scanned outline, with as few lines, circular
+/// arcs and cubic curves as possible within `tolerance`. A closed path starts at its
+/// first corner. Fewer than two distinct points throw `INVALID_ARGUMENT`.
+#[cfg(feature = "node")]
+#[napi]
+pub fn fit_curve_to_points(
+  points: Vec<deepnest_types::types::Point>,
+  tolerance: f64,
+  closed: Option<bool>,
+) -> napi::Result<PathData, &'static str> {
+  let path = curve_fit::fit_curve(&points, tolerance, closed.unwrap_or(false))?;
+  Ok(PathData::from(&path))
+}
+
+/// The Catmull-Rom spline through `points` as cubic curves. `tightness` defaults to 0,
+/// and 1 gives straight lines. Fewer than three points throw `INVALID_ARGUMENT`.
+#[cfg(feature = "node")]
+#[napi]
+pub fn catmull_rom_curve(
+  points: Vec<deepnest_types::types::Point>,
+  tightness: Option<f64>,
+) -> napi::Result<PathData, &'static str> {
+  let path = curve_fit::catmull_rom(&points, tightness.unwrap_or(0.0))?;
+  Ok(PathData::from(&path))
+}
+
+/// Imports the closed contours of an ASCII DXF document as polygons with holes.
+#[cfg(feature = "node")]
+#[napi]
+pub fn load_dxf_string(
+  dxf_data: String,
+  options: Option<DxfImportOptions>,
+) -> napi::Result<DxfImportResult, &'static str> {
+  Ok(dxf::import_dxf_string(&dxf_data, options).map_err(PreprocessorError::from)?)
+}
+
+/// Imports the closed contours of a DXF file as polygons with holes.
+#[cfg(feature = "node")]
+#[napi]
+pub fn load_dxf_file(
+  dxf_path: String,
+  options: Option<DxfImportOptions>,
+) -> napi::Result<DxfImportResult, &'static str> {
+  if !Path::new(&dxf_path).exists() {
+    return Err(PreprocessorError::FileNotFound { path: dxf_path }.into());
   }
+  dxf::import_dxf_file(Path::new(&dxf_path), options).map_err(|err| {
+    match err {
+      DxfError::Io(message) => PreprocessorError::Io {
+        path: dxf_path.clone(),
+        message,
+      },
+      err => err.into(),
+    }
+    .into()
+  })
+}
+
+/// Writes each nested sheet as a DXF document, with one layer per part.
+#[cfg_attr(feature = "node", napi)]
+pub fn export_dxf(
+  parts: Vec<DxfPart>,
+  sheets: Vec<DxfSheet>,
+  options: Option<DxfExportOptions>,
+) -> Vec<String> {
+  dxf::export_dxf_sheets(&parts, &sheets, options)
+}
+
+/// Writes the nested sheets as SVG, one document per sheet or a single combined one.
+/// The parts are referenced by the ids of their elements in `svg_data`.
+#[cfg(feature = "node")]
+#[napi]
+pub fn export_nest_svg(
+  svg_data: String,
+  sheets: Vec<SvgNestSheet>,
+  options: Option<SvgNestExportOptions>,
+) -> napi::Result<Vec<String>, &'static str> {
+  Ok(svg_export::write_nest_svg(&svg_data, &sheets, options).map_err(PreprocessorError::from)?)
+}
+
+/// Reads the sheets and placements back from an SVG written by `exportNestSvg`.
+#[cfg(feature = "node")]
+#[napi]
+pub fn read_nest_svg(svg_data: String) -> napi::Result<Vec<SvgNestSheet>, &'static str> {
+  Ok(svg_export::parse_nest_svg(&svg_data).map_err(PreprocessorError::from)?)
+}
+
+/// Mirrors an SVG document left to right or top to bottom within its page.
+#[cfg(feature = "node")]
+#[napi]
+pub fn flip_svg(svg_data: String, direction: FlipDirection) -> napi::Result<String, &'static str> {
+  Ok(svg_flip::flip_svg_string(svg_data, direction)?)
+}
+
+/// Mirrors an SVG document about the line through (`x1`, `y1`) and (`x2`, `y2`).
+/// The size and viewBox of the result enclose the mirrored page.
+#[cfg(feature = "node")]
+#[napi]
+pub fn mirror_svg(
+  svg_data: String,
+  x1: f64,
+  y1: f64,
+  x2: f64,
+  y2: f64,
+) -> napi::Result<String, &'static str> {
+  Ok(svg_flip::mirror_svg(&svg_data, x1, y1, x2, y2)?)
+}
+
+/// Rotates an SVG document by `degrees` (clockwise on screen) around (`cx`, `cy`),
+/// which defaults to the origin. The size and viewBox of the result enclose the
+/// rotated page.
+#[cfg(feature = "node")]
+#[napi]
+pub fn rotate_svg(
+  svg_data: String,
+  degrees: f64,
+  cx: Option<f64>,
+  cy: Option<f64>,
+) -> napi::Result<String, &'static str> {
+  Ok(svg_flip::rotate_svg(
+    &svg_data,
+    degrees,
+    cx.unwrap_or(0.0),
+    cy.unwrap_or(0.0),
+  )?)
+}
+
+/// Scales an SVG document relative to the origin; `sy` defaults to `sx`.
+#[cfg(feature = "node")]
+#[napi]
+pub fn scale_svg(svg_data: String, sx: f64, sy: Option<f64>) -> napi::Result<String, &'static str> {
+  Ok(svg_flip::scale_svg(&svg_data, sx, sy.unwrap_or(sx))?)
 }
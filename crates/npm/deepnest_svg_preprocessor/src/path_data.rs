@@ -0,0 +1,778 @@
+//! SVG path data that keeps its commands, for rewriting paths without linearizing them.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use deepnest_types::types::{
+  sin_cos_degrees, CurvePath, Point, Rect, Segment as CurveSegment, Transform,
+};
+
+use crate::error::PreprocessorError;
+use crate::path_data_parser::{absolutize, parse_path, Segment};
+
+/// Parsed SVG path data. Every operation returns a new path and keeps curves and
+/// arcs as they are, so the result is as compact as the input.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi)]
+pub struct PathData {
+  segments: Vec<Segment>,
+}
+
+impl FromStr for PathData {
+  type Err = PreprocessorError;
+
+  fn from_str(d: &str) -> Result<PathData, PreprocessorError> {
+    Ok(PathData {
+      segments: parse_path(d)?,
+    })
+  }
+}
+
+/// Absolute path data of a `CurvePath`, with arcs as `A` and curves as `C` commands.
+impl From<&CurvePath> for PathData {
+  fn from(path: &CurvePath) -> PathData {
+    let mut segments = vec![command('M', vec![path.start.x, path.start.y])];
+    for segment in path.segments.iter() {
+      segments.push(match *segment {
+        CurveSegment::Line { to } => command('L', vec![to.x, to.y]),
+        CurveSegment::Arc {
+          to,
+          rx,
+          ry,
+          x_axis_rotation,
+          large_arc,
+          sweep,
+        } => command(
+          'A',
+          vec![
+            rx,
+            ry,
+            x_axis_rotation,
+            f64::from(u8::from(large_arc)),
+            f64::from(u8::from(sweep)),
+            to.x,
+            to.y,
+          ],
+        ),
+        CurveSegment::Cubic {
+          control1,
+          control2,
+          to,
+        } => command(
+          'C',
+          vec![control1.x, control1.y, control2.x, control2.y, to.x, to.y],
+        ),
+      });
+    }
+    if path.closed {
+      segments.push(command('Z', vec![]));
+    }
+    PathData { segments }
+  }
+}
+
+impl fmt::Display for PathData {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.serialize())
+  }
+}
+
+#[cfg_attr(feature = "node", napi)]
+impl PathData {
+  /// Parses path data, see `FromStr` for use from Rust.
+  #[cfg(feature = "node")]
+  #[napi(factory, js_name = "parse")]
+  pub fn parse_js(d: String) -> napi::Result<PathData, &'static str> {
+    Ok(d.parse::<PathData>()?)
+  }
+
+  /// The commands of the path.
+  pub fn segments(&self) -> &[Segment] {
+    &self.segments
+  }
+
+  /// Writes the path with as few characters as possible: repeated commands are
+  /// implicit, and separators are left out where the next number cannot be joined
+  /// with the previous one. Numbers are written exactly, so parsing the result gives
+  /// the same path.
+  #[napi(js_name = "toString")]
+  pub fn serialize(&self) -> String {
+    let mut d = String::new();
+    // The command that bare numbers continue.
+    let mut implicit: Option<char> = None;
+    // The previous number, if it was the last thing written.
+    let mut previous: Option<String> = None;
+    for segment in self.segments.iter() {
+      if implicit != Some(segment.key) {
+        d.push(segment.key);
+        previous = None;
+      }
+      for value in segment.data.iter() {
+        let number = format_number(*value);
+        if let Some(previous) = &previous {
+          let joins =
+            number.starts_with('-') || (number.starts_with('.') && previous.contains(['.', 'e']));
+          if !joins {
+            d.push(' ');
+          }
+        }
+        d.push_str(&number);
+        previous = Some(number);
+      }
+      implicit = match segment.key {
+        'M' => Some('L'),
+        'm' => Some('l'),
+        'Z' | 'z' => None,
+        key => Some(key),
+      };
+    }
+    d
+  }
+
+  /// The path with absolute commands only.
+  #[napi]
+  pub fn to_absolute(&self) -> PathData {
+    PathData {
+      segments: absolutize(self.segments.clone()),
+    }
+  }
+
+  /// The path with relative commands only.
+  #[napi]
+  pub fn to_relative(&self) -> PathData {
+    let mut cursor = Cursor::default();
+    let segments = absolutize(self.segments.clone())
+      .into_iter()
+      .map(|segment| {
+        let (x, y) = cursor.current;
+        let mut data = segment.data.clone();
+        match segment.key {
+          'H' => data[0] -= x,
+          'V' => data[0] -= y,
+          'A' => {
+            data[5] -= x;
+            data[6] -= y;
+          }
+          _ => {
+            for pair in data.chunks_exact_mut(2) {
+              pair[0] -= x;
+              pair[1] -= y;
+            }
+          }
+        }
+        cursor.advance(&segment);
+        Segment {
+          key: segment.key.to_ascii_lowercase(),
+          data,
+        }
+      })
+      .collect();
+    PathData { segments }
+  }
+
+  /// Applies the affine transform `[a c e; b d f]`, as in the SVG `matrix()`.
+  /// Arcs stay arcs, with the radii and rotation of the transformed ellipse.
+  /// The result is absolute.
+  #[napi]
+  pub fn transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> PathData {
+    transform_path(self, &Transform { a, b, c, d, e, f })
+  }
+
+  /// Rotates the path by `degrees` (clockwise on screen) around (`cx`, `cy`), which
+  /// defaults to the origin.
+  #[napi]
+  pub fn rotate(&self, degrees: f64, cx: Option<f64>, cy: Option<f64>) -> PathData {
+    transform_path(
+      self,
+      &Transform::rotate(degrees, cx.unwrap_or(0.0), cy.unwrap_or(0.0)),
+    )
+  }
+
+  /// The path traversed in the opposite direction. Every subpath is reversed in
+  /// place, and closed subpaths keep their start point. The result is absolute.
+  #[napi]
+  pub fn reverse(&self) -> PathData {
+    let mut segments = vec![];
+    for subpath in subpaths(&explicit(&self.segments)) {
+      let start = subpath.start;
+      let closed = subpath.closed;
+      let end = subpath.end;
+      if closed {
+        segments.push(command('M', vec![start.0, start.1]));
+        if end != start {
+          segments.push(command('L', vec![end.0, end.1]));
+        }
+      } else {
+        segments.push(command('M', vec![end.0, end.1]));
+      }
+      let first = segments.len();
+      for (segment, from) in subpath.drawn.iter().rev() {
+        segments.push(reversed(segment, *from));
+      }
+      // The closing line draws the last reversed line again.
+      if closed
+        && segments.len() > first
+        && matches!(segments[segments.len() - 1].key, 'L' | 'H' | 'V')
+      {
+        segments.pop();
+      }
+      if closed {
+        segments.push(command('Z', vec![]));
+      }
+    }
+    PathData { segments }
+  }
+
+  /// The exact bounds of the path, including the extrema of curves and arcs.
+  #[napi]
+  pub fn bounds(&self) -> Rect {
+    let mut bounds = Bounds::default();
+    let mut cursor = Cursor::default();
+    for segment in explicit(&self.segments) {
+      let from = cursor.current;
+      cursor.advance(&segment);
+      let to = cursor.current;
+      bounds.include(to);
+      let data = &segment.data;
+      match segment.key {
+        'C' => {
+          for axis in 0..2 {
+            let values = [from, (data[0], data[1]), (data[2], data[3]), to].map(|p| pick(p, axis));
+            for t in cubic_extrema(values) {
+              bounds.include_axis(axis, cubic_at(values, t));
+            }
+          }
+        }
+        'Q' => {
+          for axis in 0..2 {
+            let values = [from, (data[0], data[1]), to].map(|p| pick(p, axis));
+            let denominator = values[0] - 2.0 * values[1] + values[2];
+            if denominator != 0.0 {
+              let t = (values[0] - values[1]) / denominator;
+              if t > 0.0 && t < 1.0 {
+                let u = 1.0 - t;
+                bounds.include_axis(
+                  axis,
+                  u * u * values[0] + 2.0 * u * t * values[1] + t * t * values[2],
+                );
+              }
+            }
+          }
+        }
+        'A' => arc_extrema(from, data, &mut bounds),
+        _ => {}
+      }
+    }
+    bounds.rect()
+  }
+}
+
+/// Current point and start of the current subpath while walking absolute segments.
+#[derive(Default)]
+struct Cursor {
+  current: (f64, f64),
+  start: (f64, f64),
+}
+
+impl Cursor {
+  fn advance(&mut self, segment: &Segment) {
+    let data = &segment.data;
+    self.current = match segment.key {
+      'H' => (data[0], self.current.1),
+      'V' => (self.current.0, data[0]),
+      'Z' => self.start,
+      'A' => (data[5], data[6]),
+      _ => (data[data.len() - 2], data[data.len() - 1]),
+    };
+    if segment.key == 'M' {
+      self.start = self.current;
+    }
+  }
+}
+
+/// Minimal decimal representation of a number that parses back to the same value.
+fn format_number(value: f64) -> String {
+  // Avoids writing negative zero.
+  let number = (value + 0.0).to_string();
+  if let Some(fraction) = number.strip_prefix("0.") {
+    format!(".{}", fraction)
+  } else if let Some(fraction) = number.strip_prefix("-0.") {
+    format!("-.{}", fraction)
+  } else {
+    number
+  }
+}
+
+fn command(key: char, data: Vec<f64>) -> Segment {
+  Segment { key, data }
+}
+
+/// Absolute segments with the control points of S and T written out, as C and Q.
+fn explicit(segments: &[Segment]) -> Vec<Segment> {
+  let mut cursor = Cursor::default();
+  // The last control point of the previous curve and the kind of the curve.
+  let mut control: Option<(char, (f64, f64))> = None;
+  let mut out = vec![];
+  for segment in absolutize(segments.to_vec()) {
+    let (x, y) = cursor.current;
+    let reflect = |kind: char| match control {
+      Some((previous, (cx, cy))) if previous == kind => (2.0 * x - cx, 2.0 * y - cy),
+      _ => (x, y),
+    };
+    let data = &segment.data;
+    let segment = match segment.key {
+      'S' => {
+        let (cx, cy) = reflect('C');
+        command('C', vec![cx, cy, data[0], data[1], data[2], data[3]])
+      }
+      'T' => {
+        let (cx, cy) = reflect('Q');
+        command('Q', vec![cx, cy, data[0], data[1]])
+      }
+      _ => segment,
+    };
+    control = match segment.key {
+      'C' => Some(('C', (segment.data[2], segment.data[3]))),
+      'Q' => Some(('Q', (segment.data[0], segment.data[1]))),
+      _ => None,
+    };
+    cursor.advance(&segment);
+    out.push(segment);
+  }
+  out
+}
+
+struct Subpath {
+  start: (f64, f64),
+  /// drawing segments with their start points
+  drawn: Vec<(Segment, (f64, f64))>,
+  /// end of the last drawing segment
+  end: (f64, f64),
+  closed: bool,
+}
+
+/// Splits absolute segments into subpaths. A subpath that follows a Z without a
+/// move starts at the start of the closed one.
+fn subpaths(segments: &[Segment]) -> Vec<Subpath> {
+  let mut cursor = Cursor::default();
+  let mut out: Vec<Subpath> = vec![];
+  for segment in segments {
+    let from = cursor.current;
+    cursor.advance(segment);
+    let open = out.last().is_some_and(|subpath| !subpath.closed);
+    match segment.key {
+      'M' => out.push(Subpath {
+        start: cursor.current,
+        drawn: vec![],
+        end: cursor.current,
+        closed: false,
+      }),
+      'Z' if open => {
+        if let Some(subpath) = out.last_mut() {
+          subpath.closed = true;
+        }
+      }
+      'Z' => {}
+      _ => {
+        if !open {
+          out.push(Subpath {
+            start: from,
+            drawn: vec![],
+            end: from,
+            closed: false,
+          });
+        }
+        if let Some(subpath) = out.last_mut() {
+          subpath.drawn.push((segment.clone(), from));
+          subpath.end = cursor.current;
+        }
+      }
+    }
+  }
+  out
+}
+
+/// An absolute, explicit segment drawn backwards, ending at `to`.
+fn reversed(segment: &Segment, to: (f64, f64)) -> Segment {
+  let data = &segment.data;
+  match segment.key {
+    'H' => command('H', vec![to.0]),
+    'V' => command('V', vec![to.1]),
+    'C' => command('C', vec![data[2], data[3], data[0], data[1], to.0, to.1]),
+    'Q' => command('Q', vec![data[0], data[1], to.0, to.1]),
+    'A' => command(
+      'A',
+      vec![
+        data[0],
+        data[1],
+        data[2],
+        data[3],
+        1.0 - data[4],
+        to.0,
+        to.1,
+      ],
+    ),
+    _ => command('L', vec![to.0, to.1]),
+  }
+}
+
+/// The path mapped by `transform`. Arcs stay arcs, with the radii and rotation of the
+/// transformed ellipse. The result is absolute.
+fn transform_path(path: &PathData, transform: &Transform) -> PathData {
+  let apply = |x: f64, y: f64| {
+    let p = transform.apply(Point { x, y });
+    (p.x, p.y)
+  };
+  // H and V only survive transforms that keep the axes apart.
+  let keeps_axes = transform.b == 0.0 && transform.c == 0.0;
+  let mut cursor = Cursor::default();
+  let segments = absolutize(path.segments.clone())
+    .into_iter()
+    .map(|segment| {
+      let (x, y) = cursor.current;
+      let transformed = match segment.key {
+        'H' if keeps_axes => Segment {
+          key: 'H',
+          data: vec![transform.a * segment.data[0] + transform.e],
+        },
+        'V' if keeps_axes => Segment {
+          key: 'V',
+          data: vec![transform.d * segment.data[0] + transform.f],
+        },
+        'H' | 'V' => {
+          let (x, y) = if segment.key == 'H' {
+            apply(segment.data[0], y)
+          } else {
+            apply(x, segment.data[0])
+          };
+          Segment {
+            key: 'L',
+            data: vec![x, y],
+          }
+        }
+        'A' => {
+          let (rx, ry, rotation) = transform_ellipse(
+            segment.data[0],
+            segment.data[1],
+            segment.data[2],
+            [transform.a, transform.b, transform.c, transform.d],
+          );
+          let sweep = if transform.determinant() < 0.0 {
+            1.0 - segment.data[4]
+          } else {
+            segment.data[4]
+          };
+          let (x, y) = apply(segment.data[5], segment.data[6]);
+          Segment {
+            key: 'A',
+            data: vec![rx, ry, rotation, segment.data[3], sweep, x, y],
+          }
+        }
+        _ => {
+          let mut data = segment.data.clone();
+          for pair in data.chunks_exact_mut(2) {
+            (pair[0], pair[1]) = apply(pair[0], pair[1]);
+          }
+          Segment {
+            key: segment.key,
+            data,
+          }
+        }
+      };
+      cursor.advance(&segment);
+      transformed
+    })
+    .collect();
+  PathData { segments }
+}
+
+/// Radii and rotation (in degrees) of the ellipse (`rx`, `ry`, `rotation`) mapped by
+/// the linear transform `[a c; b d]`.
+fn transform_ellipse(rx: f64, ry: f64, rotation: f64, [a, b, c, d]: [f64; 4]) -> (f64, f64, f64) {
+  let (sin, cos) = sin_cos_degrees(rotation);
+  // The ellipse is the unit circle mapped by m = [a c; b d] * R(rotation) * diag(rx, ry).
+  let m = [
+    (a * cos + c * sin) * rx,
+    (b * cos + d * sin) * rx,
+    (-a * sin + c * cos) * ry,
+    (-b * sin + d * cos) * ry,
+  ];
+  // Singular values of m = R(phi) * diag(s1, s2) * R(theta); the unit circle is
+  // invariant under R(theta), so the ellipse has the radii s1 and |s2| and rotation phi.
+  let e = (m[0] + m[3]) / 2.0;
+  let f = (m[0] - m[3]) / 2.0;
+  let g = (m[1] + m[2]) / 2.0;
+  let h = (m[1] - m[2]) / 2.0;
+  let q = e.hypot(h);
+  let r = f.hypot(g);
+  let phi = (g.atan2(f) + h.atan2(e)) / 2.0;
+  let mut rotation = phi.to_degrees();
+  // Rounding noise would turn an axis-aligned ellipse slightly.
+  if (rotation - rotation.round()).abs() < 1e-9 {
+    rotation = rotation.round();
+  }
+  let (rx, ry) = (q + r, (q - r).abs());
+  // The same ellipse with the rotation in [0, 90).
+  let rotation = rotation.rem_euclid(180.0);
+  if rotation >= 90.0 {
+    (ry, rx, rotation - 90.0)
+  } else {
+    (rx, ry, rotation)
+  }
+}
+
+fn pick(point: (f64, f64), axis: usize) -> f64 {
+  if axis == 0 {
+    point.0
+  } else {
+    point.1
+  }
+}
+
+fn cubic_at([p0, p1, p2, p3]: [f64; 4], t: f64) -> f64 {
+  let u = 1.0 - t;
+  u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+}
+
+/// Parameters in (0, 1) where the derivative of a cubic Bézier coordinate is zero.
+fn cubic_extrema([p0, p1, p2, p3]: [f64; 4]) -> Vec<f64> {
+  // The derivative over 3 is (a - 2b + c) t² + 2 (b - a) t + a.
+  let (a, b, c) = (p1 - p0, p2 - p1, p3 - p2);
+  let qa = a - 2.0 * b + c;
+  let qb = 2.0 * (b - a);
+  let roots = if qa.abs() < 1e-12 {
+    if qb == 0.0 {
+      vec![]
+    } else {
+      vec![-a / qb]
+    }
+  } else {
+    let discriminant = qb * qb - 4.0 * qa * a;
+    if discriminant < 0.0 {
+      vec![]
+    } else {
+      let root = discriminant.sqrt();
+      vec![(-qb + root) / (2.0 * qa), (-qb - root) / (2.0 * qa)]
+    }
+  };
+  roots.into_iter().filter(|t| *t > 0.0 && *t < 1.0).collect()
+}
+
+/// Includes the extrema of the arc from `from` with the parameters of an `A` command,
+/// using the center parameterization of the SVG specification (appendix B.2.4).
+fn arc_extrema(from: (f64, f64), data: &[f64], bounds: &mut Bounds) {
+  let (mut rx, mut ry) = (data[0].abs(), data[1].abs());
+  let to = (data[5], data[6]);
+  // Degenerate arcs are straight lines.
+  if rx == 0.0 || ry == 0.0 || from == to {
+    return;
+  }
+  let (large_arc, sweep) = (data[3] != 0.0, data[4] != 0.0);
+  let (sin, cos) = sin_cos_degrees(data[2]);
+  let dx = (from.0 - to.0) / 2.0;
+  let dy = (from.1 - to.1) / 2.0;
+  let x1 = cos * dx + sin * dy;
+  let y1 = -sin * dx + cos * dy;
+  let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+  if lambda > 1.0 {
+    rx *= lambda.sqrt();
+    ry *= lambda.sqrt();
+  }
+  let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+  let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+  let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+  let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+  let cx1 = coefficient * rx * y1 / ry;
+  let cy1 = -coefficient * ry * x1 / rx;
+  let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0;
+  let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0;
+  let theta1 = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+  let theta2 = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+  let mut extent = theta2 - theta1;
+  if sweep && extent < 0.0 {
+    extent += 2.0 * PI;
+  } else if !sweep && extent > 0.0 {
+    extent -= 2.0 * PI;
+  }
+
+  let on_arc = |t: f64| {
+    if extent >= 0.0 {
+      (t - theta1).rem_euclid(2.0 * PI) <= extent
+    } else {
+      (theta1 - t).rem_euclid(2.0 * PI) <= -extent
+    }
+  };
+  let x_extremum = (-ry * sin).atan2(rx * cos);
+  let y_extremum = (ry * cos).atan2(rx * sin);
+  for t in [x_extremum, x_extremum + PI, y_extremum, y_extremum + PI] {
+    if on_arc(t) {
+      let (t_sin, t_cos) = t.sin_cos();
+      bounds.include((
+        cx + rx * t_cos * cos - ry * t_sin * sin,
+        cy + rx * t_cos * sin + ry * t_sin * cos,
+      ));
+    }
+  }
+}
+
+struct Bounds {
+  min: [f64; 2],
+  max: [f64; 2],
+}
+
+impl Default for Bounds {
+  fn default() -> Self {
+    Bounds {
+      min: [f64::INFINITY; 2],
+      max: [f64::NEG_INFINITY; 2],
+    }
+  }
+}
+
+impl Bounds {
+  fn include(&mut self, point: (f64, f64)) {
+    self.include_axis(0, point.0);
+    self.include_axis(1, point.1);
+  }
+
+  fn include_axis(&mut self, axis: usize, value: f64) {
+    self.min[axis] = self.min[axis].min(value);
+    self.max[axis] = self.max[axis].max(value);
+  }
+
+  fn rect(&self) -> Rect {
+    Rect {
+      x: self.min[0],
+      y: self.min[1],
+      width: self.max[0] - self.min[0],
+      height: self.max[1] - self.min[1],
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn path(d: &str) -> PathData {
+    d.parse().unwrap()
+  }
+
+  fn assert_rect(rect: Rect, expected: [f64; 4]) {
+    let actual = [rect.x, rect.y, rect.width, rect.height];
+    for (a, e) in actual.iter().zip(expected) {
+      assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+    }
+  }
+
+  #[test]
+  fn test_serialize_round_trip() {
+    let compact = path("M 10,20 L 30,40 L 50,60 Z m 0.5 -0.5 l -1.25 0.75 h 2 z");
+    assert_eq!(compact.to_string(), "M10 20 30 40 50 60Zm.5-.5-1.25.75h2z");
+    assert_eq!(path(&compact.to_string()), compact);
+
+    let curves = path("M0 0 C 1 2 3 4 5 6 S 7 8 9 10 Q 1e-7 2 3 4 T 5 6 A 5 5 30 1 0 10 10");
+    assert_eq!(path(&curves.to_string()), curves);
+    assert!(matches!(
+      "M 5".parse::<PathData>(),
+      Err(PreprocessorError::PathData { .. })
+    ));
+  }
+
+  #[test]
+  fn test_from_curve_path() {
+    let slot = CurvePath {
+      start: Point { x: 0.0, y: 0.0 },
+      segments: vec![
+        CurveSegment::Line {
+          to: Point { x: 2.0, y: 0.0 },
+        },
+        CurveSegment::Arc {
+          to: Point { x: 2.0, y: 1.0 },
+          rx: 0.5,
+          ry: 0.5,
+          x_axis_rotation: 0.0,
+          large_arc: false,
+          sweep: true,
+        },
+        CurveSegment::Cubic {
+          control1: Point { x: 1.5, y: 1.5 },
+          control2: Point { x: 0.5, y: 1.5 },
+          to: Point { x: 0.0, y: 1.0 },
+        },
+      ],
+      closed: true,
+    };
+    assert_eq!(
+      PathData::from(&slot).to_string(),
+      "M0 0 2 0A.5.5 0 0 1 2 1C1.5 1.5.5 1.5 0 1Z"
+    );
+  }
+
+  #[test]
+  fn test_absolute_and_relative() {
+    let absolute = path("M10 10 L20 10 V20 H10 Z M30 30 A5 5 0 0 1 40 30");
+    let relative = absolute.to_relative();
+    assert_eq!(
+      relative.to_string(),
+      "m10 10 10 0v10h-10zm20 20a5 5 0 0 1 10 0"
+    );
+    assert_eq!(relative.to_absolute(), absolute);
+  }
+
+  #[test]
+  fn test_transform() {
+    // Scaling keeps H and V, and stretches circular arcs into ellipses.
+    let scaled = path("M0 0 H10 V5 A5 5 0 0 1 0 5 Z").transform(2.0, 0.0, 0.0, 1.0, 1.0, 0.0);
+    assert_eq!(scaled.to_string(), "M1 0H21V5A10 5 0 0 1 1 5Z");
+    // Mirroring flips the direction of arcs.
+    let mirrored = path("M0 0 A5 10 0 0 1 10 0").transform(-1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    assert_eq!(mirrored.to_string(), "M0 0A5 10 0 0 0-10 0");
+    // Rotating turns the axes of ellipses.
+    let rotated = path("M0 0 H10 A10 5 0 0 1 0 0").rotate(90.0, None, None);
+    assert_eq!(rotated.to_string(), "M0 0 0 10A5 10 0 0 1 0 0");
+    let rotated = path("M0 0 A10 5 30 0 1 20 0").rotate(90.0, Some(10.0), Some(0.0));
+    assert_eq!(rotated.to_string(), "M10-10A5 10 30 0 1 10 10");
+    assert_rect(
+      path("M0 0 A10 5 0 0 1 20 0")
+        .rotate(90.0, None, None)
+        .bounds(),
+      [0.0, 0.0, 5.0, 20.0],
+    );
+  }
+
+  #[test]
+  fn test_reverse() {
+    let square = path("M0 0 L10 0 L10 10 Z");
+    assert_eq!(square.reverse().to_string(), "M0 0 10 10 10 0Z");
+    assert_eq!(square.reverse().reverse(), square.to_absolute());
+
+    let open = path("M0 0 C0 10 10 10 10 0 h5 s5 5 10 0");
+    assert_eq!(
+      open.reverse().to_string(),
+      "M25 0C20 5 15 0 15 0H10C10 10 0 10 0 0"
+    );
+  }
+
+  #[test]
+  fn test_exact_bounds() {
+    assert_rect(
+      path("M0 0 C0 10 10 10 10 0").bounds(),
+      [0.0, 0.0, 10.0, 7.5],
+    );
+    assert_rect(path("M0 0 Q5 10 10 0").bounds(), [0.0, 0.0, 10.0, 5.0]);
+    // Sweep 1 runs clockwise on screen, over the top of the circle.
+    assert_rect(
+      path("M0 0 A5 5 0 0 1 10 0").bounds(),
+      [0.0, -5.0, 10.0, 5.0],
+    );
+    assert_rect(path("M0 0 A5 5 0 1 0 10 0").bounds(), [0.0, 0.0, 10.0, 5.0]);
+    // Radii too small for the endpoints are scaled up.
+    assert_rect(
+      path("M0 0 A1 1 0 0 0 0 10").bounds(),
+      [-5.0, 0.0, 5.0, 10.0],
+    );
+    assert_rect(path("M1 1").bounds(), [1.0, 1.0, 0.0, 0.0]);
+  }
+}
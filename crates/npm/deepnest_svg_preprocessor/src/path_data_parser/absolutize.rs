@@ -1,233 +1,168 @@
-use super::Segment;
+use super::{PathCommand, Segment};
 
 pub fn absolutize(segments: Vec<Segment>) -> Vec<Segment> {
-    let mut cx = 0.0;
-    let mut cy = 0.0;
-    let mut subx = 0.0;
-    let mut suby = 0.0;
-    let mut out: Vec<Segment> = Vec::new();
+  let mut absolutizer = Absolutizer::default();
+  segments
+    .iter()
+    .filter_map(|seg| absolutizer.push(PathCommand::new(seg.key, 0, &seg.data)))
+    .map(Segment::from)
+    .collect()
+}
 
-    for seg in segments {
-        match seg.key {
-            'M' => {
-                out.push(Segment {
-                    key: 'M',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 2 {
-                    cx = seg.data[0];
-                    cy = seg.data[1];
-                    subx = cx;
-                    suby = cy;
-                }
-            }
-            'm' => {
-                if seg.data.len() >= 2 {
-                    cx += seg.data[0];
-                    cy += seg.data[1];
-                    out.push(Segment {
-                        key: 'M',
-                        data: vec![cx, cy],
-                    });
-                    subx = cx;
-                    suby = cy;
-                }
-            }
-            'L' => {
-                out.push(Segment {
-                    key: 'L',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 2 {
-                    cx = seg.data[0];
-                    cy = seg.data[1];
-                }
-            }
-            'l' => {
-                if seg.data.len() >= 2 {
-                    cx += seg.data[0];
-                    cy += seg.data[1];
-                    out.push(Segment {
-                        key: 'L',
-                        data: vec![cx, cy],
-                    });
-                }
-            }
-            'C' => {
-                out.push(Segment {
-                    key: 'C',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 6 {
-                    cx = seg.data[4];
-                    cy = seg.data[5];
-                }
-            }
-            'c' => {
-                let newdata: Vec<f64> = seg
-                    .data
-                    .iter()
-                    .enumerate()
-                    .map(|(i, &d)| if i % 2 == 0 { d + cx } else { d + cy })
-                    .collect();
-                out.push(Segment {
-                    key: 'C',
-                    data: newdata.clone(),
-                });
-                if newdata.len() >= 6 {
-                    cx = newdata[4];
-                    cy = newdata[5];
-                }
-            }
-            'Q' => {
-                out.push(Segment {
-                    key: 'Q',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 4 {
-                    cx = seg.data[2];
-                    cy = seg.data[3];
-                }
-            }
-            'q' => {
-                let newdata: Vec<f64> = seg
-                    .data
-                    .iter()
-                    .enumerate()
-                    .map(|(i, &d)| if i % 2 == 0 { d + cx } else { d + cy })
-                    .collect();
-                out.push(Segment {
-                    key: 'Q',
-                    data: newdata.clone(),
-                });
-                if newdata.len() >= 4 {
-                    cx = newdata[2];
-                    cy = newdata[3];
-                }
-            }
-            'A' => {
-                out.push(Segment {
-                    key: 'A',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 7 {
-                    cx = seg.data[5];
-                    cy = seg.data[6];
-                }
-            }
-            'a' => {
-                if seg.data.len() >= 7 {
-                    cx += seg.data[5];
-                    cy += seg.data[6];
-                    let newdata = vec![
-                        seg.data[0],
-                        seg.data[1],
-                        seg.data[2],
-                        seg.data[3],
-                        seg.data[4],
-                        cx,
-                        cy,
-                    ];
-                    out.push(Segment {
-                        key: 'A',
-                        data: newdata,
-                    });
-                }
-            }
-            'H' => {
-                out.push(Segment {
-                    key: 'H',
-                    data: seg.data.clone(),
-                });
-                if !seg.data.is_empty() {
-                    cx = seg.data[0];
-                }
-            }
-            'h' => {
-                if !seg.data.is_empty() {
-                    cx += seg.data[0];
-                    out.push(Segment {
-                        key: 'H',
-                        data: vec![cx],
-                    });
-                }
-            }
-            'V' => {
-                out.push(Segment {
-                    key: 'V',
-                    data: seg.data.clone(),
-                });
-                if !seg.data.is_empty() {
-                    cy = seg.data[0];
-                }
-            }
-            'v' => {
-                if !seg.data.is_empty() {
-                    cy += seg.data[0];
-                    out.push(Segment {
-                        key: 'V',
-                        data: vec![cy],
-                    });
-                }
-            }
-            'S' => {
-                out.push(Segment {
-                    key: 'S',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 4 {
-                    cx = seg.data[2];
-                    cy = seg.data[3];
-                }
-            }
-            's' => {
-                let newdata: Vec<f64> = seg
-                    .data
-                    .iter()
-                    .enumerate()
-                    .map(|(i, &d)| if i % 2 == 0 { d + cx } else { d + cy })
-                    .collect();
-                out.push(Segment {
-                    key: 'S',
-                    data: newdata.clone(),
-                });
-                if newdata.len() >= 4 {
-                    cx = newdata[2];
-                    cy = newdata[3];
-                }
-            }
-            'T' => {
-                out.push(Segment {
-                    key: 'T',
-                    data: seg.data.clone(),
-                });
-                if seg.data.len() >= 2 {
-                    cx = seg.data[0];
-                    cy = seg.data[1];
-                }
-            }
-            't' => {
-                if seg.data.len() >= 2 {
-                    cx += seg.data[0];
-                    cy += seg.data[1];
-                    out.push(Segment {
-                        key: 'T',
-                        data: vec![cx, cy],
-                    });
-                }
-            }
-            'Z' | 'z' => {
-                out.push(Segment {
-                    key: 'Z',
-                    data: Vec::new(),
-                });
-                cx = subx;
-                cy = suby;
-            }
-            _ => {
-                // Unbekannter Befehl: entweder ignorieren oder unverändert übernehmen.
-            }
-        }
-    }
+/// Der Zustand von `absolutize`, um Segmente einzeln umzuwandeln, z.B. beim Streamen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Absolutizer {
+  cx: f64,
+  cy: f64,
+  subx: f64,
+  suby: f64,
+}
+
+impl Absolutizer {
+  /// Liefert den absoluten Befehl zu `command`, mit dessen Offset, oder `None`
+  /// für unbekannte oder unvollständige relative Befehle.
+  pub fn push(&mut self, command: PathCommand) -> Option<PathCommand> {
+    let Absolutizer {
+      mut cx,
+      mut cy,
+      mut subx,
+      mut suby,
+    } = *self;
+    let data = command.params();
+    let absolute = |key: char, data: &[f64]| Some(PathCommand::new(key, command.offset, data));
+    let out = match command.key {
+      'M' => {
+        if data.len() >= 2 {
+          cx = data[0];
+          cy = data[1];
+          subx = cx;
+          suby = cy;
+        }
+        absolute('M', data)
+      }
+      'm' => {
+        if data.len() >= 2 {
+          cx += data[0];
+          cy += data[1];
+          subx = cx;
+          suby = cy;
+          absolute('M', &[cx, cy])
+        } else {
+          None
+        }
+      }
+      'L' => {
+        if data.len() >= 2 {
+          cx = data[0];
+          cy = data[1];
+        }
+        absolute('L', data)
+      }
+      'l' => {
+        if data.len() >= 2 {
+          cx += data[0];
+          cy += data[1];
+          absolute('L', &[cx, cy])
+        } else {
+          None
+        }
+      }
+      'C' => {
+        if data.len() >= 6 {
+          cx = data[4];
+          cy = data[5];
+        }
+        absolute('C', data)
+      }
+      'Q' | 'S' => {
+        if data.len() >= 4 {
+          cx = data[2];
+          cy = data[3];
+        }
+        absolute(command.key, data)
+      }
+      'c' | 'q' | 's' => {
+        let mut newdata = [0.0; 7];
+        for (i, &d) in data.iter().enumerate() {
+          newdata[i] = if i % 2 == 0 { d + cx } else { d + cy };
+        }
+        let newdata = &newdata[..data.len()];
+        let end = if command.key == 'c' { 6 } else { 4 };
+        if newdata.len() >= end {
+          cx = newdata[end - 2];
+          cy = newdata[end - 1];
+        }
+        absolute(command.key.to_ascii_uppercase(), newdata)
+      }
+      'A' => {
+        if data.len() >= 7 {
+          cx = data[5];
+          cy = data[6];
+        }
+        absolute('A', data)
+      }
+      'a' => {
+        if data.len() >= 7 {
+          cx += data[5];
+          cy += data[6];
+          absolute('A', &[data[0], data[1], data[2], data[3], data[4], cx, cy])
+        } else {
+          None
+        }
+      }
+      'H' => {
+        if !data.is_empty() {
+          cx = data[0];
+        }
+        absolute('H', data)
+      }
+      'h' => {
+        if !data.is_empty() {
+          cx += data[0];
+          absolute('H', &[cx])
+        } else {
+          None
+        }
+      }
+      'V' => {
+        if !data.is_empty() {
+          cy = data[0];
+        }
+        absolute('V', data)
+      }
+      'v' => {
+        if !data.is_empty() {
+          cy += data[0];
+          absolute('V', &[cy])
+        } else {
+          None
+        }
+      }
+      'T' => {
+        if data.len() >= 2 {
+          cx = data[0];
+          cy = data[1];
+        }
+        absolute('T', data)
+      }
+      't' => {
+        if data.len() >= 2 {
+          cx += data[0];
+          cy += data[1];
+          absolute('T', &[cx, cy])
+        } else {
+          None
+        }
+      }
+      'Z' | 'z' => {
+        cx = subx;
+        cy = suby;
+        absolute('Z', &[])
+      }
+      // Unbekannter Befehl: wird ignoriert.
+      _ => None,
+    };
+    *self = Absolutizer { cx, cy, subx, suby };
     out
-}
\ No newline at end of file
+  }
+}
@@ -1,8 +1,8 @@
 mod absolutize;
-mod parser;
 mod normalize;
+mod parser;
 
-pub use absolutize::absolutize;
+pub use absolutize::{absolutize, Absolutizer};
+pub use normalize::{ArcMode, Normalizer};
 #[allow(unused_imports)]
-pub use parser::{parse_path, serialize, Segment};
-pub use normalize::normalize;
\ No newline at end of file
+pub use parser::{parse_path, serialize, PathCommand, PathDataError, PathParser, Segment};
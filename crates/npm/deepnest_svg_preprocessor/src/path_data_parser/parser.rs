@@ -1,301 +1,343 @@
-//use regex::Regex;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum TokenType {
-    Command = 0,
-    Number,
-    EOD,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+  pub key: char,
+  pub data: Vec<f64>,
 }
 
-#[derive(Debug, Clone)]
-struct Token {
-    kind: TokenType,
-    text: String,
+/// Invalid path data, with the byte offset of the offending command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathDataError {
+  pub offset: usize,
+  pub message: String,
 }
 
-#[derive(Debug, Clone)]
-pub struct Segment {
-    pub key: char,
-    pub data: Vec<f64>,
+impl std::fmt::Display for PathDataError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} at offset {}", self.message, self.offset)
+  }
 }
 
 fn params_count_for(c: char) -> Option<usize> {
-    match c {
-        'A' | 'a' => Some(7),
-        'C' | 'c' => Some(6),
-        'H' | 'h' => Some(1),
-        'L' | 'l' => Some(2),
-        'M' | 'm' => Some(2),
-        'Q' | 'q' => Some(4),
-        'S' | 's' => Some(4),
-        'T' | 't' => Some(2),
-        'V' | 'v' => Some(1),
-        'Z' | 'z' => Some(0),
-        _ => None,
+  match c {
+    'A' | 'a' => Some(7),
+    'C' | 'c' => Some(6),
+    'H' | 'h' => Some(1),
+    'L' | 'l' => Some(2),
+    'M' | 'm' => Some(2),
+    'Q' | 'q' => Some(4),
+    'S' | 's' => Some(4),
+    'T' | 't' => Some(2),
+    'V' | 'v' => Some(1),
+    'Z' | 'z' => Some(0),
+    _ => None,
+  }
+}
+
+/// A segment as produced by `PathParser`, with its parameters stored inline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathCommand {
+  pub key: char,
+  /// byte offset of the command, or of its first parameter for implicit repeats
+  pub offset: usize,
+  params: [f64; 7],
+  len: usize,
+}
+
+impl PathCommand {
+  /// A command with the given parameters; only the first seven are kept, the
+  /// most any command takes.
+  pub fn new(key: char, offset: usize, params: &[f64]) -> Self {
+    let len = params.len().min(7);
+    let mut inline = [0.0; 7];
+    inline[..len].copy_from_slice(&params[..len]);
+    PathCommand {
+      key,
+      offset,
+      params: inline,
+      len,
     }
+  }
+
+  pub fn params(&self) -> &[f64] {
+    &self.params[..self.len]
+  }
 }
 
-fn tokenize(d: &str) -> Vec<Token> {
-    // Estimate token count (typical SVG paths have approximately 1 token per 2-3 characters)
-    let estimated_tokens = d.len() / 3;
-    let mut tokens: Vec<Token> = Vec::with_capacity(estimated_tokens);
-    
-    // Avoid string allocation with replacing by handling commas directly in the parsing
-    let mut i = 0;
-    let chars: Vec<char> = d.chars().collect();
-    
-    while i < chars.len() {
-        // Skip whitespace and commas
-        while i < chars.len() && (chars[i].is_whitespace() || chars[i] == ',') {
+impl From<PathCommand> for Segment {
+  fn from(command: PathCommand) -> Self {
+    Segment {
+      key: command.key,
+      data: command.params().to_vec(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+  Command(char),
+  Number(f64),
+}
+
+/// Streaming parser for SVG path data. It yields the segments as they are read,
+/// without allocating, so even multi-megabyte paths are parsed in constant memory.
+///
+/// Like the parser of the original JS library it is forgiving: unknown characters
+/// are skipped, path data not starting with a moveto gets an implicit `M0,0`, and
+/// commands with missing parameters are reported as errors and skipped.
+pub struct PathParser<'a> {
+  data: &'a [u8],
+  pos: usize,
+  /// the command repeated by parameters without a command letter
+  mode: Option<char>,
+  /// anything was yielded, either a segment or an error
+  yielded: bool,
+  done: bool,
+}
+
+impl<'a> PathParser<'a> {
+  pub fn new(d: &'a str) -> Self {
+    PathParser {
+      data: d.as_bytes(),
+      pos: 0,
+      mode: None,
+      yielded: false,
+      done: false,
+    }
+  }
+
+  /// The next token from `pos` with its byte offset and the position after it,
+  /// or `None` at the end of the data.
+  fn token(&self, mut pos: usize) -> Option<(Token, usize, usize)> {
+    let data = self.data;
+    while pos < data.len() {
+      let c = data[pos];
+      if c.is_ascii_whitespace() || c == b',' {
+        pos += 1;
+        continue;
+      }
+      if params_count_for(c as char).is_some() {
+        return Some((Token::Command(c as char), pos, pos + 1));
+      }
+      if c == b'-' || c == b'+' || c == b'.' || c.is_ascii_digit() {
+        let start = pos;
+        let digits = |mut i: usize| {
+          while i < data.len() && data[i].is_ascii_digit() {
             i += 1;
+          }
+          i
+        };
+        let mut i = start;
+        if c == b'-' || c == b'+' {
+          i += 1;
         }
-        
-        if i >= chars.len() {
-            break;
+        i = digits(i);
+        if i < data.len() && data[i] == b'.' {
+          i = digits(i + 1);
         }
-        
-        // Check for commands
-        let c = chars[i];
-        if matches!(c, 'a' | 'A' | 'c' | 'C' | 'h' | 'H' | 'l' | 'L' | 'm' | 'M' | 
-                     'q' | 'Q' | 's' | 'S' | 't' | 'T' | 'v' | 'V' | 'z' | 'Z') {
-            tokens.push(Token {
-                kind: TokenType::Command,
-                text: c.to_string(),
-            });
+        if i < data.len() && (data[i] == b'e' || data[i] == b'E') {
+          i += 1;
+          if i < data.len() && (data[i] == b'+' || data[i] == b'-') {
             i += 1;
-            continue;
+          }
+          i = digits(i);
         }
-        
-        // Check for numbers
-        if c == '-' || c == '+' || c == '.' || c.is_digit(10) {
-            let start = i;
-            
-            // Handle sign
-            if c == '-' || c == '+' {
-                i += 1;
-            }
-            
-            // Handle digits before decimal
-            while i < chars.len() && chars[i].is_digit(10) {
-                i += 1;
-            }
-            
-            // Handle decimal point and digits after
-            if i < chars.len() && chars[i] == '.' {
-                i += 1;
-                while i < chars.len() && chars[i].is_digit(10) {
-                    i += 1;
-                }
-            }
-            
-            // Handle exponent notation
-            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
-                i += 1;
-                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
-                    i += 1;
-                }
-                while i < chars.len() && chars[i].is_digit(10) {
-                    i += 1;
-                }
-            }
-            
-            if i > start {
-                let num_str = chars[start..i].iter().collect::<String>();
-                if let Ok(num) = f64::from_str(&num_str) {
-                    tokens.push(Token {
-                        kind: TokenType::Number,
-                        text: num.to_string(), // Use normalized representation
-                    });
-                } else {
-                    // Skip invalid number
-                    i = start + 1;
-                }
-            } else {
-                // Skip single character that isn't a valid number start
-                i += 1;
-            }
-            continue;
+        // The scanned bytes are ASCII, so they are valid UTF-8.
+        let text = std::str::from_utf8(&data[start..i]).unwrap_or_default();
+        if let Ok(value) = f64::from_str(text) {
+          return Some((Token::Number(value), start, i));
         }
-        
-        // Skip unknown character
-        i += 1;
+        // Skip a sign or dot that does not start a number.
+        pos = start + 1;
+        continue;
+      }
+      // Skip unknown characters.
+      pos += 1;
     }
-    
-    tokens.push(Token {
-        kind: TokenType::EOD,
-        text: String::new(),
-    });
-    
-    tokens
+    None
+  }
 }
 
-pub fn parse_path(d: &str) -> Result<Vec<Segment>, String> {
-    let tokens = tokenize(d);
-    if tokens.is_empty() {
-        return Err("Invalid path data: Cannot tokenize".to_string());
-    }
-    
-    let mut segments: Vec<Segment> = Vec::new();
-    let mut index = 0;
-    // None entspricht dem "BOD" (Beginning Of Data)
-    let mut mode: Option<char> = None;
-
-    while index < tokens.len() {
-        let token = &tokens[index];
-        if token.kind == TokenType::EOD {
-            break;
-        }
+fn missing_parameters(offset: usize, command: char) -> PathDataError {
+  PathDataError {
+    offset,
+    message: format!("Missing parameters for command {}", command),
+  }
+}
 
-        let params_count: usize;
-        let current_mode: char;
+impl Iterator for PathParser<'_> {
+  type Item = Result<PathCommand, PathDataError>;
 
-        if mode.is_none() {
-            // Beim ersten Token muss es ein "M" oder "m" sein.
-            if token.text == "M" || token.text == "m" {
-                mode = Some(token.text.chars().next().unwrap());
-                index += 1;
-                current_mode = mode.unwrap();
-                params_count = params_count_for(current_mode)
-                    .ok_or_else(|| format!("Unbekannter Befehl: {}", current_mode))?;
-            } else {
-                // Falls nicht, wird "M0,0" vorangestellt und neu geparst.
-                let new_d = format!("M0,0{}", d);
-                return parse_path(&new_d);
-            }
-        } else {
-            // Bereits im Pfad: Wenn das aktuelle Token eine Zahl ist, wird der vorherige Befehl fortgeführt.
-            if token.kind == TokenType::Number {
-                current_mode = mode.unwrap();
-                params_count = params_count_for(current_mode)
-                    .ok_or_else(|| format!("Unbekannter Befehl: {}", current_mode))?;
-            } else {
-                // Andernfalls handelt es sich um einen neuen Befehl.
-                mode = Some(token.text.chars().next().unwrap());
-                current_mode = mode.unwrap();
-                index += 1;
-                params_count = params_count_for(current_mode)
-                    .ok_or_else(|| format!("Unbekannter Befehl: {}", current_mode))?;
-            }
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.done {
+      let Some((token, offset, end)) = self.token(self.pos) else {
+        self.done = true;
+        if !self.yielded {
+          self.yielded = true;
+          return Some(Err(PathDataError {
+            offset: 0,
+            message: "No valid segments found in path".to_string(),
+          }));
         }
+        return None;
+      };
 
-        // Überprüfe, ob genügend Tokens für die Parameter vorhanden sind.
-        if index + params_count > tokens.len() {
-            // Handle truncated paths gracefully - try to use what we have so far
-            break;
+      let key = match (token, self.mode) {
+        (Token::Command(c), Some(_)) | (Token::Command(c @ ('M' | 'm')), None) => {
+          self.pos = end;
+          c
         }
-
-        let mut params = Vec::new();
-        for i in index..index + params_count {
-            if i >= tokens.len() {
-                break; // Avoid going out of bounds
-            }
-            
-            let num_token = &tokens[i];
-            if num_token.kind == TokenType::Number {
-                let value: f64 = match num_token.text.parse() {
-                    Ok(val) => val,
-                    Err(_) => {
-                        // Try to sanitize the number - replace comma with period
-                        let sanitized = num_token.text.replace(',', ".");
-                        match sanitized.parse() {
-                            Ok(val) => val,
-                            Err(_) => return Err(format!(
-                                "Parameter ist keine Zahl: {},{}",
-                                current_mode, num_token.text
-                            ))
-                        }
-                    }
-                };
-                params.push(value);
-            } else {
-                // Not a number - try to be forgiving and continue
-                break;
-            }
+        // Path data must start with a moveto; as in the JS library, "M0,0" is assumed.
+        (_, None) => {
+          self.mode = Some('L');
+          self.yielded = true;
+          return Some(Ok(PathCommand {
+            key: 'M',
+            offset: 0,
+            params: [0.0; 7],
+            len: 2,
+          }));
         }
+        (Token::Number(_), Some(mode)) => mode,
+      };
+      self.mode = Some(key);
+      let count = params_count_for(key).unwrap_or(0);
+      if count == 0 && matches!(token, Token::Number(_)) {
+        // Parameters after a closepath have no command to repeat.
+        self.pos = end;
+        continue;
+      }
 
-        // Only add the segment if we got enough parameters
-        // For Z/z commands we don't need any parameters
-        if params.len() == params_count || (current_mode == 'Z' || current_mode == 'z') {
-            let params_len = params.len();
-            segments.push(Segment {
-                key: current_mode,
-                data: params,
-            });
-            index += params_len;
-        } else {
-            // If we didn't get enough parameters, we'll skip this command
-            index += params.len();
+      let mut params = [0.0; 7];
+      let mut len = 0;
+      while len < count {
+        match self.token(self.pos) {
+          Some((Token::Number(value), _, end)) => {
+            params[len] = value;
+            len += 1;
+            self.pos = end;
+          }
+          next => {
+            // Truncated path data ends the path, other commands continue it.
+            self.done = next.is_none();
+            self.yielded = true;
+            return Some(Err(missing_parameters(offset, key)));
+          }
         }
+      }
 
-        // Nach einem "M"/"m" wird der Modus zu "L"/"l" geändert.
-        if current_mode == 'M' {
-            mode = Some('L');
-        } else if current_mode == 'm' {
-            mode = Some('l');
-        }
+      // Nach einem "M"/"m" wird der Modus zu "L"/"l" geändert.
+      if key == 'M' {
+        self.mode = Some('L');
+      } else if key == 'm' {
+        self.mode = Some('l');
+      }
+      self.yielded = true;
+      return Some(Ok(PathCommand {
+        key,
+        offset,
+        params,
+        len,
+      }));
     }
-    
-    if segments.is_empty() {
-        return Err("No valid segments found in path".to_string());
+    None
+  }
+}
+
+/// Parses the whole path data. Errors are only returned if no segment could be
+/// read at all, see `PathParser`.
+pub fn parse_path(d: &str) -> Result<Vec<Segment>, PathDataError> {
+  let mut segments: Vec<Segment> = Vec::new();
+  let mut error = None;
+  for command in PathParser::new(d) {
+    match command {
+      Ok(command) => segments.push(command.into()),
+      Err(err) => {
+        error.get_or_insert(err);
+      }
     }
-    
-    Ok(segments)
+  }
+  match error {
+    Some(err) if segments.is_empty() => Err(err),
+    _ => Ok(segments),
+  }
 }
 
 #[allow(dead_code)]
 pub fn serialize(segments: &[Segment]) -> String {
-    let mut tokens: Vec<String> = Vec::new();
+  let mut tokens: Vec<String> = Vec::new();
 
-    for segment in segments {
-        tokens.push(segment.key.to_string());
-        match segment.key {
-            'C' | 'c' => {
-                if segment.data.len() >= 6 {
-                    tokens.push(segment.data[0].to_string());
-                    tokens.push(format!("{},", segment.data[1]));
-                    tokens.push(segment.data[2].to_string());
-                    tokens.push(format!("{},", segment.data[3]));
-                    tokens.push(segment.data[4].to_string());
-                    tokens.push(segment.data[5].to_string());
-                }
-            }
-            'S' | 's' | 'Q' | 'q' => {
-                if segment.data.len() >= 4 {
-                    tokens.push(segment.data[0].to_string());
-                    tokens.push(format!("{},", segment.data[1]));
-                    tokens.push(segment.data[2].to_string());
-                    tokens.push(segment.data[3].to_string());
-                }
-            }
-            _ => {
-                for d in &segment.data {
-                    tokens.push(d.to_string());
-                }
-            }
+  for segment in segments {
+    tokens.push(segment.key.to_string());
+    match segment.key {
+      'C' | 'c' => {
+        if segment.data.len() >= 6 {
+          tokens.push(segment.data[0].to_string());
+          tokens.push(format!("{},", segment.data[1]));
+          tokens.push(segment.data[2].to_string());
+          tokens.push(format!("{},", segment.data[3]));
+          tokens.push(segment.data[4].to_string());
+          tokens.push(segment.data[5].to_string());
+        }
+      }
+      'S' | 's' | 'Q' | 'q' => {
+        if segment.data.len() >= 4 {
+          tokens.push(segment.data[0].to_string());
+          tokens.push(format!("{},", segment.data[1]));
+          tokens.push(segment.data[2].to_string());
+          tokens.push(segment.data[3].to_string());
         }
+      }
+      _ => {
+        for d in &segment.data {
+          tokens.push(d.to_string());
+        }
+      }
     }
-    tokens.join(" ")
+  }
+  tokens.join(" ")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+  use super::*;
 
-    #[test]
-    fn test_tokenize() {
-        let input = "M10,20 L30,40";
-        let tokens = tokenize(input);
-        // Prüft, ob das letzte Token EOD ist.
-        assert_eq!(tokens.last().unwrap().kind, TokenType::EOD);
-    }
+  #[test]
+  fn test_path_parser() {
+    let commands: Vec<_> = PathParser::new("M10,20 L30-40 50e1.5z").collect();
+    let keys: Vec<char> = commands.iter().map(|c| c.as_ref().unwrap().key).collect();
+    assert_eq!(keys, vec!['M', 'L', 'L', 'z']);
+    let repeated = commands[2].as_ref().unwrap();
+    assert_eq!(repeated.offset, 14);
+    assert_eq!(repeated.params(), &[500.0, 0.5]);
 
-    #[test]
-    fn test_parse_and_serialize() {
-        let path_data = "M10,20 L30,40 50,60";
-        let segments = parse_path(path_data).expect("Parsing failed");
-        let serialized = serialize(&segments);
-        // Einfacher Test, der sicherstellt, dass etwas zurückgegeben wird.
-        assert!(!serialized.is_empty());
-    }
+    // Incomplete commands are skipped, the path continues with the next command.
+    let commands: Vec<_> = PathParser::new("M0 0 C1 2 L3 4 Q5").collect();
+    assert_eq!(commands.len(), 4);
+    assert_eq!(commands[1].as_ref().unwrap_err().offset, 5);
+    assert_eq!(commands[2].as_ref().unwrap().params(), &[3.0, 4.0]);
+    assert_eq!(
+      commands[3].as_ref().unwrap_err().message,
+      "Missing parameters for command Q"
+    );
+  }
+
+  #[test]
+  fn test_parse_and_serialize() {
+    let path_data = "M10,20 L30,40 50,60";
+    let segments = parse_path(path_data).expect("Parsing failed");
+    let serialized = serialize(&segments);
+    // Einfacher Test, der sicherstellt, dass etwas zurückgegeben wird.
+    assert!(!serialized.is_empty());
+  }
+
+  #[test]
+  fn test_parse_error_offset() {
+    let err = parse_path("  M 5").unwrap_err();
+    assert_eq!(err.offset, 2);
+    assert_eq!(err.message, "Missing parameters for command M");
+    let err = parse_path("x").unwrap_err();
+    assert_eq!(err.message, "No valid segments found in path");
+  }
 }
@@ -1,4 +1,5 @@
 use super::Point;
+use crate::error::PreprocessorError;
 
 /// Klont einen Punkt. Da `Point` ein Copy-Typ ist, genügt hier die einfache Rückgabe.
 fn clone_point(p: Point) -> Point {
@@ -8,48 +9,66 @@ fn clone_point(p: Point) -> Point {
 /// Wandelt einen Kurvenverlauf in eine Folge von kubischen Bézierkurven um.
 /// - `points_in`: Eingangspunkte der Kurve (muss mindestens drei Punkte enthalten).
 /// - `curve_tightness`: Bestimmt die Straffheit der Kurve (Standard: 0.0)
-#[allow(unused)]
-pub fn curve_to_bezier(points_in: &[Point], curve_tightness: f64) -> Vec<Point> {
-    let len = points_in.len();
-    if len < 3 {
-        panic!("A curve must have at least three points.");
+///
+/// Ergebnis: der Startpunkt, gefolgt von je zwei Kontrollpunkten und dem Endpunkt jeder Kurve.
+/// Wie in roughjs sind drei Punkte eine einzige Kurve mit `points_in[1]` und
+/// `points_in[2]` als Kontrollpunkten, die nicht durch `points_in[1]` verläuft.
+pub fn curve_to_bezier(points_in: &[Point], curve_tightness: f64) -> Result<Vec<Point>, PreprocessorError> {
+    check_length(points_in)?;
+    if points_in.len() == 3 {
+        Ok(vec![
+            clone_point(points_in[0]),
+            clone_point(points_in[1]),
+            clone_point(points_in[2]),
+            clone_point(points_in[2]),
+        ])
+    } else {
+        catmull_rom_to_bezier(points_in, curve_tightness)
     }
-    let mut out: Vec<Point> = Vec::new();
+}
 
-    if len == 3 {
-        out.push(clone_point(points_in[0]));
-        out.push(clone_point(points_in[1]));
-        out.push(clone_point(points_in[2]));
-        out.push(clone_point(points_in[2]));
-    } else {
-        // Erzeuge ein neues Punkte-Array, in dem das erste Element verdoppelt wird
-        let mut points: Vec<Point> = Vec::new();
-        points.push(points_in[0]);
-        points.push(points_in[0]);
-        for i in 1..len {
+/// Wie `curve_to_bezier`, aber auch bei drei Punkten der Catmull-Rom-Spline durch alle
+/// Punkte, mit je einer Kurve zwischen zwei benachbarten Punkten.
+pub fn catmull_rom_to_bezier(points_in: &[Point], curve_tightness: f64) -> Result<Vec<Point>, PreprocessorError> {
+    check_length(points_in)?;
+    let len = points_in.len();
+    let mut out: Vec<Point> = Vec::new();
+    // Erzeuge ein neues Punkte-Array, in dem das erste und das letzte Element verdoppelt werden
+    let mut points: Vec<Point> = Vec::new();
+    points.push(points_in[0]);
+    points.push(points_in[0]);
+    for i in 1..len {
+        points.push(points_in[i]);
+        if i == len - 1 {
             points.push(points_in[i]);
-            if i == len - 1 {
-                points.push(points_in[i]);
-            }
-        }
-        let s = 1.0 - curve_tightness;
-        out.push(clone_point(points[0]));
-        // Für i von 1 bis (points.len() - 3), da die Bedingung (i + 2) < points.len() erfüllt sein muss
-        for i in 1..(points.len() - 2) {
-            let cached = points[i];
-            let b1: Point = [
-                cached.x + (s * points[i + 1].x - s * points[i - 1].x) / 6.0,
-                cached.y + (s * points[i + 1].y - s * points[i - 1].y) / 6.0,
-            ].into();
-            let b2: Point = [
-                points[i + 1].x + (s * points[i].x - s * points[i + 2].x) / 6.0,
-                points[i + 1].y + (s * points[i].y - s * points[i + 2].y) / 6.0,
-            ].into();
-            let b3 = points[i + 1];
-            out.push(b1);
-            out.push(b2);
-            out.push(b3);
         }
     }
-    out
-}
\ No newline at end of file
+    let s = 1.0 - curve_tightness;
+    out.push(clone_point(points[0]));
+    // Für i von 1 bis (points.len() - 3), da die Bedingung (i + 2) < points.len() erfüllt sein muss
+    for i in 1..(points.len() - 2) {
+        let cached = points[i];
+        let b1: Point = [
+            cached.x + (s * points[i + 1].x - s * points[i - 1].x) / 6.0,
+            cached.y + (s * points[i + 1].y - s * points[i - 1].y) / 6.0,
+        ].into();
+        let b2: Point = [
+            points[i + 1].x + (s * points[i].x - s * points[i + 2].x) / 6.0,
+            points[i + 1].y + (s * points[i].y - s * points[i + 2].y) / 6.0,
+        ].into();
+        let b3 = points[i + 1];
+        out.push(b1);
+        out.push(b2);
+        out.push(b3);
+    }
+    Ok(out)
+}
+
+fn check_length(points_in: &[Point]) -> Result<(), PreprocessorError> {
+    if points_in.len() < 3 {
+        return Err(PreprocessorError::InvalidArgument {
+            message: "a curve must have at least three points".to_string(),
+        });
+    }
+    Ok(())
+}
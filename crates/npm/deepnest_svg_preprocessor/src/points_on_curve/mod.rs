@@ -4,4 +4,4 @@ mod curve;
 mod curve_to_bezier;
 
 pub use curve::{points_on_bezier_curves, simplify, simplify_points, Point};
-pub use curve_to_bezier::curve_to_bezier;
\ No newline at end of file
+pub use curve_to_bezier::{catmull_rom_to_bezier, curve_to_bezier};
\ No newline at end of file
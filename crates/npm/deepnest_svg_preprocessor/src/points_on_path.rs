@@ -1,10 +1,12 @@
-use crate::path_data_parser::{absolutize, normalize, parse_path};
-use crate::points_on_curve::{points_on_bezier_curves, simplify, Point};
-use napi::bindgen_prelude::*;
+use geometry_utils::linearizer::Linearizer;
+
+use crate::error::PreprocessorError;
+use crate::path_data_parser::{Absolutizer, ArcMode, Normalizer, PathCommand, PathParser};
+use crate::points_on_curve::{simplify, Point};
 
 /// Information about a processed path including whether it's closed
 #[derive(Debug, Clone)]
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub struct PathResult {
   /// Sets of points that approximate the path
   pub points: Vec<Vec<Point>>,
@@ -12,30 +14,28 @@ pub struct PathResult {
   pub closed: Vec<bool>,
 }
 
+/// The linearizer for the `tolerance` and `min_segment_length` options of the API,
+/// with the defaults of `Linearizer`.
+pub(crate) fn linearizer(tolerance: Option<f64>, min_segment_length: Option<f64>) -> Linearizer {
+  let default = Linearizer::default();
+  Linearizer {
+    max_deviation: tolerance.unwrap_or(default.max_deviation),
+    min_segment_length: min_segment_length.unwrap_or(default.min_segment_length),
+  }
+}
+
 /// Liefert für den übergebenen Pfad (als String) eine Liste von Punkt‑Sätzen zurück,
-/// die den Pfad approximieren. Dabei können eine optionale Toleranz (für die
-/// Bézier‑Kurven Approximation) sowie ein Epsilon (für eine anschließende Vereinfachung)
-/// angegeben werden.
+/// die den Pfad approximieren. Kurven werden mit `linearizer` linearisiert, optional
+/// kann ein Epsilon (für eine anschließende Vereinfachung) angegeben werden. Mit
+/// `ArcMode::Exact` werden Bögen direkt linearisiert statt über Bézierkurven.
 pub fn points_on_path(
   path: String,
-  tolerance: Option<f64>,
+  linearizer: &Linearizer,
   distance: Option<f64>,
-) -> Result<Vec<Vec<Point>>> {
-  // Parse, absolutiere und normalisiere den Pfad.
-  let segments = match parse_path(&path) {
-    Ok(segs) => segs,
-    Err(e) => return Err(Error::new(Status::InvalidArg, format!("Path parsing error: {}", e))),
-  };
-  
-  // Apply absolutization and normalization in sequence
-  let abs_segments = absolutize(segments);
-  let normalized = match normalize(abs_segments) {
-    Ok(norm) => norm,
-    Err(e) => return Err(Error::new(Status::GenericFailure, format!("Path normalization error: {}", e))),
-  };
-
+  arcs: Option<ArcMode>,
+) -> Result<Vec<Vec<Point>>, PreprocessorError> {
   // Pre-allocate vectors with capacity to avoid frequent reallocations
-  let mut sets: Vec<Vec<Point>> = Vec::with_capacity(normalized.len() / 4 + 1);
+  let mut sets: Vec<Vec<Point>> = Vec::new();
   let mut current_points: Vec<Point> = Vec::with_capacity(32);
   let mut start: Point = [0.0, 0.0].into();
   let mut pending_curve: Vec<Point> = Vec::with_capacity(16);
@@ -45,11 +45,7 @@ pub fn points_on_path(
     |pending_curve: &mut Vec<Point>, current_points: &mut Vec<Point>| {
       if pending_curve.len() >= 4 {
         // Hängt die Punkte, die durch die Bézierkurvenapproximation geliefert werden, an.
-        // Reserve space for new points to prevent reallocations
-        let new_points = points_on_bezier_curves(pending_curve, tolerance, None);
-        // Avoid unnecessary copying by extending with new_points directly
-        current_points.reserve(current_points.len() + new_points.len());
-        current_points.extend_from_slice(&new_points);
+        append_cubics(current_points, pending_curve, linearizer);
       }
       pending_curve.clear();
     };
@@ -67,19 +63,20 @@ pub fn points_on_path(
   };
 
   // Iteriere über alle normalisierten Segmente.
-  for seg in &normalized {
+  // Der Pfad wird gestreamt, die Segmente werden nicht gesammelt.
+  for_each_normalized_segment(&path, arcs.unwrap_or(ArcMode::Cubic), |seg| {
     match seg.key {
       'M' => {
         append_pending_points(&mut pending_curve, &mut current_points, &mut sets);
-        if seg.data.len() >= 2 {
-          start = [seg.data[0], seg.data[1]].into();
+        if seg.params().len() >= 2 {
+          start = [seg.params()[0], seg.params()[1]].into();
           current_points.push(start);
         }
       }
       'L' => {
         append_pending_curve(&mut pending_curve, &mut current_points);
-        if seg.data.len() >= 2 {
-          current_points.push([seg.data[0], seg.data[1]].into());
+        if seg.params().len() >= 2 {
+          current_points.push([seg.params()[0], seg.params()[1]].into());
         }
       }
       'C' => {
@@ -88,10 +85,16 @@ pub fn points_on_path(
           pending_curve.push(last_point);
         }
         // Füge die 3 Kontrollpunkte hinzu.
-        if seg.data.len() >= 6 {
-          pending_curve.push([seg.data[0], seg.data[1]].into());
-          pending_curve.push([seg.data[2], seg.data[3]].into());
-          pending_curve.push([seg.data[4], seg.data[5]].into());
+        if seg.params().len() >= 6 {
+          pending_curve.push([seg.params()[0], seg.params()[1]].into());
+          pending_curve.push([seg.params()[2], seg.params()[3]].into());
+          pending_curve.push([seg.params()[4], seg.params()[5]].into());
+        }
+      }
+      'A' => {
+        append_pending_curve(&mut pending_curve, &mut current_points);
+        if seg.params().len() >= 7 {
+          append_arc(&mut current_points, start, seg.params(), linearizer);
         }
       }
       'Z' => {
@@ -102,7 +105,7 @@ pub fn points_on_path(
         // Andere Befehle werden ignoriert.
       }
     }
-  }
+  })?;
   
   append_pending_points(&mut pending_curve, &mut current_points, &mut sets);
 
@@ -127,25 +130,13 @@ pub fn points_on_path(
 
 pub fn points_on_path_with_closed_info(
   path: String,
-  tolerance: Option<f64>,
+  linearizer: &Linearizer,
   distance: Option<f64>,
-) -> Result<PathResult> {
-  // Parse, absolutiere und normalisiere den Pfad.
-  let segments = match parse_path(&path) {
-    Ok(segs) => segs,
-    Err(e) => return Err(Error::new(Status::InvalidArg, format!("Path parsing error: {}", e))),
-  };
-  
-  // Apply absolutization and normalization in sequence
-  let abs_segments = absolutize(segments);
-  let normalized = match normalize(abs_segments) {
-    Ok(norm) => norm,
-    Err(e) => return Err(Error::new(Status::GenericFailure, format!("Path normalization error: {}", e))),
-  };
-
+  arcs: Option<ArcMode>,
+) -> Result<PathResult, PreprocessorError> {
   // Pre-allocate vectors with capacity to avoid frequent reallocations
-  let mut sets: Vec<Vec<Point>> = Vec::with_capacity(normalized.len() / 4 + 1);
-  let mut closed_info: Vec<bool> = Vec::with_capacity(normalized.len() / 4 + 1);
+  let mut sets: Vec<Vec<Point>> = Vec::new();
+  let mut closed_info: Vec<bool> = Vec::new();
   let mut current_points: Vec<Point> = Vec::with_capacity(32);
   let mut start: Point = [0.0, 0.0].into();
   let mut is_current_path_closed = false;
@@ -156,11 +147,7 @@ pub fn points_on_path_with_closed_info(
     |pending_curve: &mut Vec<Point>, current_points: &mut Vec<Point>| {
       if pending_curve.len() >= 4 {
         // Hängt die Punkte, die durch die Bézierkurvenapproximation geliefert werden, an.
-        // Reserve space for new points to prevent reallocations
-        let new_points = points_on_bezier_curves(pending_curve, tolerance, None);
-        // Avoid unnecessary copying by extending with new_points directly
-        current_points.reserve(current_points.len() + new_points.len());
-        current_points.extend_from_slice(&new_points);
+        append_cubics(current_points, pending_curve, linearizer);
       }
       pending_curve.clear();
     };
@@ -185,20 +172,21 @@ pub fn points_on_path_with_closed_info(
   };
 
   // Iteriere über alle normalisierten Segmente.
-  for seg in &normalized {
+  // Der Pfad wird gestreamt, die Segmente werden nicht gesammelt.
+  for_each_normalized_segment(&path, arcs.unwrap_or(ArcMode::Cubic), |seg| {
     match seg.key {
       'M' => {
         append_pending_points(&mut pending_curve, &mut current_points, &mut sets, &mut closed_info, is_current_path_closed);
         is_current_path_closed = false; // Reset for new path
-        if seg.data.len() >= 2 {
-          start = [seg.data[0], seg.data[1]].into();
+        if seg.params().len() >= 2 {
+          start = [seg.params()[0], seg.params()[1]].into();
           current_points.push(start);
         }
       }
       'L' => {
         append_pending_curve(&mut pending_curve, &mut current_points);
-        if seg.data.len() >= 2 {
-          current_points.push([seg.data[0], seg.data[1]].into());
+        if seg.params().len() >= 2 {
+          current_points.push([seg.params()[0], seg.params()[1]].into());
         }
       }
       'C' => {
@@ -207,10 +195,16 @@ pub fn points_on_path_with_closed_info(
           pending_curve.push(last_point);
         }
         // Füge die 3 Kontrollpunkte hinzu.
-        if seg.data.len() >= 6 {
-          pending_curve.push([seg.data[0], seg.data[1]].into());
-          pending_curve.push([seg.data[2], seg.data[3]].into());
-          pending_curve.push([seg.data[4], seg.data[5]].into());
+        if seg.params().len() >= 6 {
+          pending_curve.push([seg.params()[0], seg.params()[1]].into());
+          pending_curve.push([seg.params()[2], seg.params()[3]].into());
+          pending_curve.push([seg.params()[4], seg.params()[5]].into());
+        }
+      }
+      'A' => {
+        append_pending_curve(&mut pending_curve, &mut current_points);
+        if seg.params().len() >= 7 {
+          append_arc(&mut current_points, start, seg.params(), linearizer);
         }
       }
       'Z' => {
@@ -222,7 +216,7 @@ pub fn points_on_path_with_closed_info(
         // Andere Befehle werden ignoriert.
       }
     }
-  }
+  })?;
   
   append_pending_points(&mut pending_curve, &mut current_points, &mut sets, &mut closed_info, is_current_path_closed);
 
@@ -253,6 +247,76 @@ pub fn points_on_path_with_closed_info(
   })
 }
 
+/// Parses, absolutizes and normalizes `path` command by command and passes the
+/// normalized commands to `f`, without allocating per command. The first invalid
+/// command is returned as an error with its offset, the commands before it have
+/// already been passed to `f`.
+fn for_each_normalized_segment(
+  path: &str,
+  arcs: ArcMode,
+  mut f: impl FnMut(&PathCommand),
+) -> Result<(), PreprocessorError> {
+  let mut absolutizer = Absolutizer::default();
+  let mut normalizer = Normalizer::new(arcs);
+  let mut normalized = Vec::with_capacity(4);
+  for command in PathParser::new(path) {
+    let Some(absolute) = absolutizer.push(command?) else {
+      continue;
+    };
+    normalizer
+      .push(absolute, &mut normalized)
+      .map_err(|message| PreprocessorError::PathData {
+        offset: Some(absolute.offset),
+        message,
+      })?;
+    for segment in normalized.drain(..) {
+      f(&segment);
+    }
+  }
+  Ok(())
+}
+
+/// Appends a chain of cubic curves, given as their start point followed by three
+/// points per curve. The start point is already in `points`.
+fn append_cubics(points: &mut Vec<Point>, curve: &[Point], linearizer: &Linearizer) {
+  let mut out = vec![];
+  let mut from = to_geometry(curve[0]);
+  for controls in curve[1..].chunks_exact(3) {
+    let to = to_geometry(controls[2]);
+    linearizer.cubic(from, to_geometry(controls[0]), to_geometry(controls[1]), to, &mut out);
+    from = to;
+  }
+  points.extend(out.into_iter().map(from_geometry));
+}
+
+/// Appends the points of an absolute `A` segment starting at the last point.
+fn append_arc(points: &mut Vec<Point>, start: Point, data: &[f64], linearizer: &Linearizer) {
+  let from = points.last().copied().unwrap_or(start);
+  let mut out = vec![];
+  linearizer.arc(
+    to_geometry(from),
+    deepnest_types::types::Point {
+      x: data[5],
+      y: data[6],
+    },
+    data[0],
+    data[1],
+    data[2],
+    data[3] != 0.0,
+    data[4] != 0.0,
+    &mut out,
+  );
+  points.extend(out.into_iter().map(from_geometry));
+}
+
+fn to_geometry(p: Point) -> deepnest_types::types::Point {
+  deepnest_types::types::Point { x: p.x, y: p.y }
+}
+
+fn from_geometry(p: deepnest_types::types::Point) -> Point {
+  Point { x: p.x, y: p.y }
+}
+
 /// Checks if two points are equal within a small epsilon to account for floating point precision
 #[inline]
 fn is_point_equal(p1: Point, p2: Point) -> bool {
@@ -267,7 +331,7 @@ mod tests {
     #[test]
     fn test_points_on_path() {
         let path = "M532.094,806.71c6.595,91.184 45.177,175.149 106.414,244.044c-18.85,3.17 -38.222,4.822 -57.982,4.822c-189.194,-0 -342.795,-151.386 -342.795,-337.85c0,-186.465 153.601,-337.851 342.795,-337.851c57.886,0 112.441,14.172 160.283,39.185c-75.737,49.42 -135.471,115.393 -171.506,191.444c-11.119,-4.197 -23.167,-6.494 -35.747,-6.494c-55.939,0 -101.355,45.416 -101.355,101.355c0,55.452 44.627,100.562 99.893,101.345Z";
-        let result = points_on_path(path.to_string(), Some(0.5), None).unwrap();
+        let result = points_on_path(path.to_string(), &Linearizer::new(0.5), None, None).unwrap();
         // Ensure we receive at least one set of points.
         assert!(!result.is_empty());
         // Ensure that the first set contains more than one point.
@@ -279,23 +343,58 @@ mod tests {
     fn test_closed_path_detection() {
         // Test with explicit Z command
         let closed_path = "M10,10 L20,10 L20,20 L10,20 Z";
-        let result = points_on_path_with_closed_info(closed_path.to_string(), None, None).unwrap();
+        let result = points_on_path_with_closed_info(closed_path.to_string(), &Linearizer::default(), None, None).unwrap();
         assert!(!result.points.is_empty());
         assert!(result.closed[0], "Path with Z command should be detected as closed");
         println!("Points: {:?}", result);
         
         // Test geometrically closed path (first point equals last point)
         let implicitly_closed = "M10,10 L20,10 L20,20 L10,20 L10,10";
-        let result = points_on_path_with_closed_info(implicitly_closed.to_string(), None, None).unwrap();
+        let result = points_on_path_with_closed_info(implicitly_closed.to_string(), &Linearizer::default(), None, None).unwrap();
         assert!(!result.points.is_empty());
         assert!(result.closed[0], "Path with first=last point should be detected as closed");
         println!("Points: {:?}", result);
         
         // Test open path
         let open_path = "M10,10 L20,10 L20,20 L10,20";
-        let result = points_on_path_with_closed_info(open_path.to_string(), None, None).unwrap();
+        let result = points_on_path_with_closed_info(open_path.to_string(), &Linearizer::default(), None, None).unwrap();
         assert!(!result.points.is_empty());
         assert!(!result.closed[0], "Open path should be detected as not closed");
         println!("Points: {:?}", result);
     }
+
+    #[test]
+    fn test_exact_arcs() {
+        let circle = "M0 10 A10 10 0 0 1 20 10 A10 10 0 0 1 0 10 Z";
+        let radius = |p: &Point| (p.x - 10.0).hypot(p.y - 10.0);
+        let exact = points_on_path(circle.to_string(), &Linearizer::new(0.01), None, Some(ArcMode::Exact)).unwrap();
+        assert_eq!(exact.len(), 1);
+        assert!(exact[0].iter().all(|p| (radius(p) - 10.0).abs() < 1e-9));
+        // The chords stay within the tolerance of the circle.
+        for pair in exact[0].windows(2) {
+            let mid = Point { x: (pair[0].x + pair[1].x) / 2.0, y: (pair[0].y + pair[1].y) / 2.0 };
+            assert!(10.0 - radius(&mid) <= 0.01 + 1e-9);
+        }
+        // Cubic curves only approximate the circle.
+        let cubic = points_on_path(circle.to_string(), &Linearizer::new(0.01), None, None).unwrap();
+        assert!(cubic[0].iter().any(|p| (radius(p) - 10.0).abs() > 1e-6));
+
+        // Sweep 1 runs clockwise on screen, over the top.
+        let result = points_on_path_with_closed_info("M0 0 A5 5 0 0 1 10 0".to_string(), &Linearizer::new(0.01), None, Some(ArcMode::Exact)).unwrap();
+        assert!(!result.closed[0]);
+        assert!(result.points[0].iter().all(|p| p.y <= 1e-9));
+        assert!(result.points[0].iter().any(|p| p.y < -4.9));
+    }
+
+    #[test]
+    fn test_invalid_command_after_valid_ones() {
+        let err = points_on_path("M0 0 L10 0 L10 10 C1 2 L0 10 Z".to_string(), &Linearizer::default(), None, None).unwrap_err();
+        assert_eq!(
+            err,
+            PreprocessorError::PathData {
+                offset: Some(18),
+                message: "Missing parameters for command C".to_string(),
+            }
+        );
+    }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 use simplifyRS::{simplify, Point as SimplifyPoint};
+#[cfg(feature = "node")]
 use napi_derive::napi;
+use deepnest_types::types::Polygon;
+use geometry_utils::simplify::{self, simplify_conservative};
 use crate::points_on_curve::Point;
 
 type SimplifyPoint2D = SimplifyPoint<2, f64>;
@@ -16,7 +19,7 @@ impl From<SimplifyPoint2D> for Point {
     }
 }
 
-#[napi]
+#[cfg_attr(feature = "node", napi)]
 pub fn simplify_polygon(points: Vec<Point>, tolerance: f64, high_quality: bool) -> Vec<Point> {
     // Convert from our Point type to the library's SimplifyPoint type
     let simplify_points: Vec<SimplifyPoint2D> = points.into_iter().map(Into::into).collect();
@@ -27,3 +30,29 @@ pub fn simplify_polygon(points: Vec<Point>, tolerance: f64, high_quality: bool)
     // Convert back to our Point type
     simplified.into_iter().map(Into::into).collect()
 }
+
+/// Simplifies a part without moving any edge into its material: the outer contour only
+/// grows and the holes only shrink, by at most `tolerance`, so parts nested with the
+/// result never overlap the original parts.
+#[cfg_attr(feature = "node", napi)]
+pub fn simplify_polygon_conservative(polygon: Polygon, tolerance: f64) -> Polygon {
+    Polygon {
+        points: simplify_conservative(&polygon.points, tolerance, true),
+        children: polygon.children.map(|holes| {
+            holes
+                .iter()
+                .map(|hole| simplify_conservative(hole, tolerance, false))
+                .collect()
+        }),
+        offsetx: polygon.offsetx,
+        offsety: polygon.offsety,
+    }
+}
+
+/// Simplifies the outer contour and the holes of a part together, such that removed
+/// vertices stay within `tolerance`, no contour crosses itself or another one and
+/// the holes stay inside the outer contour.
+#[cfg_attr(feature = "node", napi)]
+pub fn simplify_part(polygon: Polygon, tolerance: f64) -> Polygon {
+    simplify::simplify_part(&polygon, tolerance)
+}
@@ -0,0 +1,444 @@
+//! SVG export of nested sheets.
+//!
+//! The parts are taken from the usvg tree of the source document by their id and
+//! written once into `<defs>`, with their absolute transforms applied. Every
+//! placement is a `<use>` of that definition with the placement as `transform`, and
+//! carries `data-deepnest-*` attributes so the layout can be read back with
+//! `parse_nest_svg`.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use usvg::tiny_skia_path::PathSegment;
+use usvg::{roxmltree, FillRule, Group, Node, Paint, Tree};
+
+/// Default gap between the sheets of a combined document.
+pub const DEFAULT_SHEET_SPACING: f64 = 10.0;
+
+const SHEET_ATTR: &str = "data-deepnest-sheet";
+const WIDTH_ATTR: &str = "data-deepnest-width";
+const HEIGHT_ATTR: &str = "data-deepnest-height";
+const PART_ATTR: &str = "data-deepnest-part";
+const X_ATTR: &str = "data-deepnest-x";
+const Y_ATTR: &str = "data-deepnest-y";
+const ROTATION_ATTR: &str = "data-deepnest-rotation";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgExportError {
+  Parse(String),
+  MissingPart(String),
+  InvalidAttribute { name: String, value: String },
+}
+
+impl fmt::Display for SvgExportError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SvgExportError::Parse(message) => write!(f, "Error parsing SVG: {}", message),
+      SvgExportError::MissingPart(id) => write!(f, "No element with id \"{}\" in the SVG", id),
+      SvgExportError::InvalidAttribute { name, value } => {
+        write!(f, "Invalid value \"{}\" for attribute {}", value, name)
+      }
+    }
+  }
+}
+
+impl std::error::Error for SvgExportError {}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgNestPlacement {
+  /// id of the part element in the source SVG
+  pub part_id: String,
+  pub x: f64,
+  pub y: f64,
+  /// rotation in degrees around the origin of the source document, applied before
+  /// the translation
+  pub rotation: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgNestSheet {
+  /// index written to `data-deepnest-sheet`, defaults to the position in the list
+  pub index: Option<u32>,
+  pub width: f64,
+  pub height: f64,
+  pub placements: Vec<SvgNestPlacement>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgNestExportOptions {
+  /// write all sheets into a single document, stacked from top to bottom
+  pub combined: Option<bool>,
+  /// gap between the sheets of a combined document, defaults to `DEFAULT_SHEET_SPACING`
+  pub sheet_spacing: Option<f64>,
+}
+
+/// Writes the sheets of a nest as SVG, one document per sheet or a single combined
+/// document. The parts are looked up by id in `svg_data`.
+pub fn write_nest_svg(
+  svg_data: &str,
+  sheets: &[SvgNestSheet],
+  options: Option<SvgNestExportOptions>,
+) -> Result<Vec<String>, SvgExportError> {
+  let options = options.unwrap_or_default();
+  let tree = Tree::from_str(svg_data, &usvg::Options::default())
+    .map_err(|err| SvgExportError::Parse(err.to_string()))?;
+
+  if options.combined.unwrap_or(false) {
+    let spacing = options.sheet_spacing.unwrap_or(DEFAULT_SHEET_SPACING);
+    let width = sheets.iter().map(|sheet| sheet.width).fold(0.0, f64::max);
+    let height = sheets.iter().map(|sheet| sheet.height).sum::<f64>()
+      + spacing * sheets.len().saturating_sub(1) as f64;
+    let mut body = String::new();
+    let mut offset = 0.0;
+    for (index, sheet) in sheets.iter().enumerate() {
+      write_sheet(&mut body, sheet, index, offset);
+      offset += sheet.height + spacing;
+    }
+    Ok(vec![document(&tree, sheets, width, height, &body)?])
+  } else {
+    sheets
+      .iter()
+      .enumerate()
+      .map(|(index, sheet)| {
+        let mut body = String::new();
+        write_sheet(&mut body, sheet, index, 0.0);
+        document(
+          &tree,
+          std::slice::from_ref(sheet),
+          sheet.width,
+          sheet.height,
+          &body,
+        )
+      })
+      .collect()
+  }
+}
+
+/// Reads the sheets and placements back from an SVG written by `write_nest_svg`.
+/// Sheets are returned in the order of their index.
+pub fn parse_nest_svg(svg_data: &str) -> Result<Vec<SvgNestSheet>, SvgExportError> {
+  let document =
+    roxmltree::Document::parse(svg_data).map_err(|err| SvgExportError::Parse(err.to_string()))?;
+  let mut sheets: BTreeMap<u32, SvgNestSheet> = BTreeMap::new();
+  for node in document.descendants().filter(|node| node.is_element()) {
+    let Some(index) = node.attribute(SHEET_ATTR) else {
+      continue;
+    };
+    let index = parse_attribute(SHEET_ATTR, index)?;
+    let sheet = sheets.entry(index).or_insert_with(|| SvgNestSheet {
+      index: Some(index),
+      width: 0.0,
+      height: 0.0,
+      placements: vec![],
+    });
+    if let Some(part_id) = node.attribute(PART_ATTR) {
+      let number = |name: &str| parse_attribute(name, node.attribute(name).unwrap_or("0"));
+      sheet.placements.push(SvgNestPlacement {
+        part_id: part_id.to_string(),
+        x: number(X_ATTR)?,
+        y: number(Y_ATTR)?,
+        rotation: number(ROTATION_ATTR)?,
+      });
+    } else {
+      if let Some(width) = node.attribute(WIDTH_ATTR) {
+        sheet.width = parse_attribute(WIDTH_ATTR, width)?;
+      }
+      if let Some(height) = node.attribute(HEIGHT_ATTR) {
+        sheet.height = parse_attribute(HEIGHT_ATTR, height)?;
+      }
+    }
+  }
+  Ok(sheets.into_values().collect())
+}
+
+fn parse_attribute<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, SvgExportError> {
+  value
+    .trim()
+    .parse()
+    .map_err(|_| SvgExportError::InvalidAttribute {
+      name: name.to_string(),
+      value: value.to_string(),
+    })
+}
+
+/// Wraps the sheets in an SVG document with the definitions of the placed parts.
+fn document(
+  tree: &Tree,
+  sheets: &[SvgNestSheet],
+  width: f64,
+  height: f64,
+  body: &str,
+) -> Result<String, SvgExportError> {
+  let mut out = String::new();
+  // Writing to a String never fails.
+  let _ = write!(
+    out,
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+     width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n<defs>\n",
+    w = width,
+    h = height
+  );
+  let mut written: Vec<&str> = vec![];
+  for placement in sheets.iter().flat_map(|sheet| sheet.placements.iter()) {
+    if written.contains(&placement.part_id.as_str()) {
+      continue;
+    }
+    let node = tree
+      .node_by_id(&placement.part_id)
+      .ok_or_else(|| SvgExportError::MissingPart(placement.part_id.clone()))?;
+    let _ = writeln!(out, "<g id=\"{}\">", escape(&placement.part_id));
+    write_node(&mut out, node);
+    out.push_str("</g>\n");
+    written.push(&placement.part_id);
+  }
+  out.push_str("</defs>\n");
+  out.push_str(body);
+  out.push_str("</svg>\n");
+  Ok(out)
+}
+
+fn write_sheet(out: &mut String, sheet: &SvgNestSheet, position: usize, offset: f64) {
+  let index = sheet.index.unwrap_or(position as u32);
+  let _ = write!(
+    out,
+    "<g {}=\"{}\" {}=\"{}\" {}=\"{}\"",
+    SHEET_ATTR, index, WIDTH_ATTR, sheet.width, HEIGHT_ATTR, sheet.height
+  );
+  if offset != 0.0 {
+    let _ = write!(out, " transform=\"translate(0 {})\"", offset);
+  }
+  let _ = writeln!(
+    out,
+    ">\n<rect width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#000000\"/>",
+    sheet.width, sheet.height
+  );
+  for placement in sheet.placements.iter() {
+    let id = escape(&placement.part_id);
+    let _ = writeln!(
+      out,
+      "<use href=\"#{id}\" xlink:href=\"#{id}\" transform=\"translate({x} {y}) rotate({r})\" \
+       {}=\"{id}\" {}=\"{index}\" {}=\"{x}\" {}=\"{y}\" {}=\"{r}\"/>",
+      PART_ATTR,
+      SHEET_ATTR,
+      X_ATTR,
+      Y_ATTR,
+      ROTATION_ATTR,
+      id = id,
+      index = index,
+      x = placement.x,
+      y = placement.y,
+      r = placement.rotation
+    );
+  }
+  out.push_str("</g>\n");
+}
+
+/// Writes a node with its absolute transform applied, so the definition does not
+/// depend on the groups around the node in the source document.
+fn write_node(out: &mut String, node: &Node) {
+  match node {
+    Node::Group(group) => write_group(out, group),
+    Node::Path(path) => {
+      if !path.is_visible() {
+        return;
+      }
+      let ts = path.abs_transform();
+      let Some(data) = path.data().clone().transform(ts) else {
+        return;
+      };
+      let _ = write!(out, "<path d=\"{}\"", path_data(&data));
+      match path.fill() {
+        Some(fill) => {
+          let _ = write!(out, " fill=\"{}\"", color(fill.paint()));
+          if fill.opacity().get() != 1.0 {
+            let _ = write!(out, " fill-opacity=\"{}\"", fill.opacity().get());
+          }
+          if fill.rule() == FillRule::EvenOdd {
+            out.push_str(" fill-rule=\"evenodd\"");
+          }
+        }
+        None => out.push_str(" fill=\"none\""),
+      }
+      if let Some(stroke) = path.stroke() {
+        // The stroke width scales with the geometry of the transform.
+        let scale = (ts.sx * ts.sy - ts.kx * ts.ky).abs().sqrt();
+        let _ = write!(
+          out,
+          " stroke=\"{}\" stroke-width=\"{}\"",
+          color(stroke.paint()),
+          stroke.width().get() * scale
+        );
+        if stroke.opacity().get() != 1.0 {
+          let _ = write!(out, " stroke-opacity=\"{}\"", stroke.opacity().get());
+        }
+      }
+      out.push_str("/>\n");
+    }
+    Node::Text(text) => write_group(out, text.flattened()),
+    // Raster images have no outline to cut.
+    Node::Image(_) => {}
+  }
+}
+
+fn write_group(out: &mut String, group: &Group) {
+  if group.opacity().get() != 1.0 {
+    let _ = writeln!(out, "<g opacity=\"{}\">", group.opacity().get());
+  } else {
+    out.push_str("<g>\n");
+  }
+  for child in group.children() {
+    write_node(out, child);
+  }
+  out.push_str("</g>\n");
+}
+
+pub(crate) fn path_data(path: &usvg::tiny_skia_path::Path) -> String {
+  let mut d = String::new();
+  for segment in path.segments() {
+    let _ = match segment {
+      PathSegment::MoveTo(p) => write!(d, "M {} {} ", p.x, p.y),
+      PathSegment::LineTo(p) => write!(d, "L {} {} ", p.x, p.y),
+      PathSegment::QuadTo(c, p) => write!(d, "Q {} {} {} {} ", c.x, c.y, p.x, p.y),
+      PathSegment::CubicTo(c1, c2, p) => {
+        write!(d, "C {} {} {} {} {} {} ", c1.x, c1.y, c2.x, c2.y, p.x, p.y)
+      }
+      PathSegment::Close => write!(d, "Z "),
+    };
+  }
+  d.trim_end().to_string()
+}
+
+/// Gradients and patterns are written as black, only the outline matters for cutting.
+fn color(paint: &Paint) -> String {
+  match paint {
+    Paint::Color(c) => format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue),
+    _ => "#000000".to_string(),
+  }
+}
+
+fn escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use usvg::Transform;
+
+  const SOURCE_SVG: &str = r##"<svg width="100" height="100" xmlns="http://www.w3.org/2000/svg">
+<g transform="translate(5 5)">
+<rect id="part1" width="10" height="20" fill="#ff0000"/>
+</g>
+<circle id="part2" cx="5" cy="5" r="5" fill="none" stroke="#0000ff"/>
+</svg>"##;
+
+  /// Bounds of the first path filled red, which is a placement of `part1`.
+  /// The group transforms are composed here, since `abs_transform` of paths inside
+  /// `<use>` elements applies the transform of the `<use>` twice in usvg 0.45.
+  fn red_bounds(group: &Group, ts: Transform) -> Option<usvg::Rect> {
+    group.children().iter().find_map(|node| match node {
+      Node::Group(child) => red_bounds(child, ts.pre_concat(child.transform())),
+      Node::Path(path) => match path.fill().map(|fill| fill.paint()) {
+        Some(Paint::Color(c)) if c.red == 255 => {
+          path.data().clone().transform(ts).map(|p| p.bounds())
+        }
+        _ => None,
+      },
+      _ => None,
+    })
+  }
+
+  fn sheets() -> Vec<SvgNestSheet> {
+    vec![
+      SvgNestSheet {
+        index: None,
+        width: 100.0,
+        height: 50.0,
+        placements: vec![
+          SvgNestPlacement {
+            part_id: "part1".to_string(),
+            x: 30.0,
+            y: -5.0,
+            rotation: 90.0,
+          },
+          SvgNestPlacement {
+            part_id: "part2".to_string(),
+            x: 50.0,
+            y: 0.0,
+            rotation: 0.0,
+          },
+        ],
+      },
+      SvgNestSheet {
+        index: None,
+        width: 80.0,
+        height: 40.0,
+        placements: vec![SvgNestPlacement {
+          part_id: "part1".to_string(),
+          x: 0.0,
+          y: 0.0,
+          rotation: 0.0,
+        }],
+      },
+    ]
+  }
+
+  #[test]
+  fn test_write_nest_svg_per_sheet() {
+    let documents = write_nest_svg(SOURCE_SVG, &sheets(), None).unwrap();
+    assert_eq!(documents.len(), 2);
+
+    // The placed rectangle (5..15, 5..25 in the source) is rotated and moved.
+    let tree = Tree::from_str(&documents[0], &usvg::Options::default()).unwrap();
+    let bounds = red_bounds(tree.root(), Transform::identity()).expect("placed part");
+    assert!((bounds.left() - 5.0).abs() < 1e-3, "{:?}", bounds);
+    assert!((bounds.top() - 0.0).abs() < 1e-3, "{:?}", bounds);
+    assert!((bounds.width() - 20.0).abs() < 1e-3, "{:?}", bounds);
+
+    let read = parse_nest_svg(&documents[1]).unwrap();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].index, Some(1));
+    assert_eq!(read[0].placements, sheets()[1].placements);
+  }
+
+  #[test]
+  fn test_write_nest_svg_combined_round_trip() {
+    let options = SvgNestExportOptions {
+      combined: Some(true),
+      sheet_spacing: Some(5.0),
+    };
+    let documents = write_nest_svg(SOURCE_SVG, &sheets(), Some(options)).unwrap();
+    assert_eq!(documents.len(), 1);
+    assert!(documents[0].contains("height=\"95\""));
+    // Each part is defined once.
+    assert_eq!(documents[0].matches("<g id=\"part1\">").count(), 1);
+
+    let read = parse_nest_svg(&documents[0]).unwrap();
+    let expected: Vec<SvgNestSheet> = sheets()
+      .into_iter()
+      .enumerate()
+      .map(|(index, sheet)| SvgNestSheet {
+        index: Some(index as u32),
+        ..sheet
+      })
+      .collect();
+    assert_eq!(read, expected);
+  }
+
+  #[test]
+  fn test_write_nest_svg_missing_part() {
+    let mut sheets = sheets();
+    sheets[1].placements[0].part_id = "unknown".to_string();
+    assert_eq!(
+      write_nest_svg(SOURCE_SVG, &sheets, None),
+      Err(SvgExportError::MissingPart("unknown".to_string()))
+    );
+  }
+}
@@ -1,111 +1,236 @@
-use std::fs;
-use std::path::Path;
-use usvg::Tree;
+//! Mirroring, rotation and scaling of whole SVG documents.
+//!
+//! The document is normalized with usvg and its content is wrapped in a group with
+//! the transform. The size and viewBox of the result are the bounds of the
+//! transformed page, so coordinates keep their meaning: a point `p` of the input is
+//! drawn at `transform(p)` in the output.
 
-#[derive(Debug)]
+use std::fmt::Write;
+
+use deepnest_types::types::{Point, Transform};
+use usvg::{roxmltree, Tree};
+
+use crate::error::{read_file, PreprocessorError};
+
+#[cfg_attr(feature = "node", napi)]
+#[cfg_attr(not(feature = "node"), derive(Clone, Copy))]
+#[derive(Debug, PartialEq, Eq)]
 pub enum FlipDirection {
-    Horizontal,
-    Vertical,
+  /// mirrors left to right, about the vertical center line of the page
+  Horizontal,
+  /// mirrors top to bottom, about the horizontal center line of the page
+  Vertical,
 }
 
-#[derive(Debug)]
-pub struct FlipSVGResult {
-    pub success: bool,
-    pub result: String,
+/// Mirrors the document about the line through (`x1`, `y1`) and (`x2`, `y2`).
+pub fn mirror_svg(
+  svg_data: &str,
+  x1: f64,
+  y1: f64,
+  x2: f64,
+  y2: f64,
+) -> Result<String, PreprocessorError> {
+  let transform =
+    Transform::mirror(x1, y1, x2, y2).ok_or_else(|| PreprocessorError::InvalidArgument {
+      message: "the mirror line needs two distinct points".to_string(),
+    })?;
+  transform_svg(svg_data, transform)
 }
 
-pub fn flip_svg_string(svg_data: String, direction: FlipDirection) -> FlipSVGResult {
-    match Tree::from_str(&svg_data, &usvg::Options::default()) {
-        Ok(tree) => {
-            let size = tree.size();
-            let width = size.width();
-            let height = size.height();
-            let transform_str = match direction {
-                FlipDirection::Horizontal => format!("matrix(-1,0,0,1,{},0)", width),
-                FlipDirection::Vertical => format!("matrix(1,0,0,-1,0,{})", height),
-            };
-            let original = tree.to_string(&usvg::WriteOptions::default());
-            let output = wrap_svg_with_flip(&original, &transform_str);
-            FlipSVGResult { success: true, result: output }
-        },
-        Err(err) => FlipSVGResult {
-            success: false,
-            result: format!("Error parsing SVG: {}", err),
-        },
-    }
+/// Rotates the document by `degrees` around (`cx`, `cy`).
+#[cfg_attr(not(feature = "node"), allow(dead_code))]
+pub fn rotate_svg(
+  svg_data: &str,
+  degrees: f64,
+  cx: f64,
+  cy: f64,
+) -> Result<String, PreprocessorError> {
+  transform_svg(svg_data, Transform::rotate(degrees, cx, cy))
 }
 
-pub fn flip_svg_file(svg_path: String, direction: FlipDirection) -> FlipSVGResult {
-    let path = Path::new(&svg_path);
-    if !path.exists() {
-        return FlipSVGResult {
-            success: false,
-            result: format!("File not found: {}", svg_path),
-        };
-    }
-    let svg_data = match fs::read_to_string(path) {
-        Ok(data) => data,
-        Err(err) => {
-            return FlipSVGResult {
-                success: false,
-                result: format!("Error reading file: {}", err),
-            };
-        }
-    };
-    match Tree::from_str(&svg_data, &usvg::Options::default()) {
-        Ok(tree) => {
-            let size = tree.size();
-            let width = size.width();
-            let height = size.height();
-            let transform_str = match direction {
-                FlipDirection::Horizontal => format!("matrix(-1,0,0,1,{},0)", width),
-                FlipDirection::Vertical => format!("matrix(1,0,0,-1,0,{})", height),
-            };
-            let original = tree.to_string(&usvg::WriteOptions::default());
-            let output = wrap_svg_with_flip(&original, &transform_str);
-            FlipSVGResult { success: true, result: output }
-        },
-        Err(err) => FlipSVGResult {
-            success: false,
-            result: format!("Error parsing SVG: {}", err),
-        },
-    }
+/// Scales the document by `sx` and `sy` relative to the origin.
+#[cfg_attr(not(feature = "node"), allow(dead_code))]
+pub fn scale_svg(svg_data: &str, sx: f64, sy: f64) -> Result<String, PreprocessorError> {
+  transform_svg(svg_data, Transform::scale(sx, sy))
+}
+
+pub fn flip_svg_string(
+  svg_data: String,
+  direction: FlipDirection,
+) -> Result<String, PreprocessorError> {
+  let tree = Tree::from_str(&svg_data, &usvg::Options::default())?;
+  let width = tree.size().width() as f64;
+  let height = tree.size().height() as f64;
+  match direction {
+    FlipDirection::Horizontal => mirror_svg(&svg_data, width / 2.0, 0.0, width / 2.0, height),
+    FlipDirection::Vertical => mirror_svg(&svg_data, 0.0, height / 2.0, width, height / 2.0),
+  }
+}
+
+pub fn flip_svg_file(
+  svg_path: String,
+  direction: FlipDirection,
+) -> Result<String, PreprocessorError> {
+  let svg_data = String::from_utf8(read_file(&svg_path)?).map_err(|err| PreprocessorError::Io {
+    path: svg_path.clone(),
+    message: err.to_string(),
+  })?;
+  flip_svg_string(svg_data, direction)
 }
 
-fn wrap_svg_with_flip(svg: &str, transform: &str) -> String {
-    if let Some(svg_tag_end) = svg.find('>') {
-        let (start, rest) = svg.split_at(svg_tag_end + 1);
-        if let Some(svg_close) = rest.rfind("</svg>") {
-            let (inner, end) = rest.split_at(svg_close);
-            return format!("{}<g transform=\"{}\">{}</g>{}", start, transform, inner, end);
-        }
-    }
-    svg.to_string()
+fn transform_svg(svg_data: &str, transform: Transform) -> Result<String, PreprocessorError> {
+  if !transform.is_invertible() {
+    return Err(PreprocessorError::InvalidArgument {
+      message: "the transform collapses the document".to_string(),
+    });
+  }
+  let tree = Tree::from_str(svg_data, &usvg::Options::default())?;
+  let width = tree.size().width() as f64;
+  let height = tree.size().height() as f64;
+  let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+    .map(|(x, y)| transform.apply(Point { x, y }));
+  let min_x = corners.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+  let min_y = corners.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+  let max_x = corners
+    .iter()
+    .map(|p| p.x)
+    .fold(f64::NEG_INFINITY, f64::max);
+  let max_y = corners
+    .iter()
+    .map(|p| p.y)
+    .fold(f64::NEG_INFINITY, f64::max);
+
+  // The content of the normalized document is taken by the ranges of its nodes, so
+  // XML declarations, comments and attributes of the root element cannot end up in
+  // the wrong place.
+  let normalized = tree.to_string(&usvg::WriteOptions::default());
+  let document = roxmltree::Document::parse(&normalized)?;
+  let root = document.root_element();
+  let body = match (root.first_child(), root.last_child()) {
+    (Some(first), Some(last)) => &normalized[first.range().start..last.range().end],
+    _ => "",
+  };
+
+  let mut out = String::from("<svg");
+  // Writing to a String never fails.
+  for namespace in root.namespaces() {
+    let _ = match namespace.name() {
+      Some(prefix) => write!(out, " xmlns:{}=\"{}\"", prefix, namespace.uri()),
+      None => write!(out, " xmlns=\"{}\"", namespace.uri()),
+    };
+  }
+  let _ = write!(
+    out,
+    " width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n<g transform=\"matrix({} {} {} {} {} {})\">\n{}\n</g>\n</svg>\n",
+    max_x - min_x,
+    max_y - min_y,
+    min_x,
+    min_y,
+    max_x - min_x,
+    max_y - min_y,
+    transform.a,
+    transform.b,
+    transform.c,
+    transform.d,
+    transform.e,
+    transform.f,
+    body
+  );
+  Ok(out)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+  use super::*;
 
-    // A basic SVG for testing.
-    const SIMPLE_SVG: &str = r#"<svg width="100" height="200" xmlns="http://www.w3.org/2000/svg">
+  // A basic SVG for testing.
+  const SIMPLE_SVG: &str = r#"<svg width="100" height="200" xmlns="http://www.w3.org/2000/svg">
 <rect width="100" height="200" fill="red"/>
 </svg>"#;
 
-    #[test]
-    fn test_flip_svg_string_horizontal() {
-        let result = flip_svg_string(SIMPLE_SVG.to_string(), FlipDirection::Horizontal);
-        assert!(result.success, "Flip operation should succeed");
-        // Check if the output contains the transformation wrapper.
-        assert!(result.result.contains("<g transform=\"matrix(-1,0,0,1,"), "Output should include horizontal flip matrix");
-        println!("{}", result.result);
-    }
-
-    #[test]
-    fn test_flip_svg_string_vertical() {
-        let result = flip_svg_string(SIMPLE_SVG.to_string(), FlipDirection::Vertical);
-        assert!(result.success, "Flip operation should succeed");
-        assert!(result.result.contains("<g transform=\"matrix(1,0,0,-1,0,"), "Output should include vertical flip matrix");
-        println!("{}", result.result);
-    }
+  /// A document with an XML declaration and a comment before the root element.
+  const PROLOG_SVG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Generator: test > 1 -->
+<svg width="100" height="200" xmlns="http://www.w3.org/2000/svg">
+<rect width="10" height="20" fill="red"/>
+</svg>"#;
+
+  /// Size of the page and bounds of the content after reloading `svg`.
+  fn page_and_bounds(svg: &str) -> ((f32, f32), (f32, f32, f32, f32)) {
+    let tree = Tree::from_str(svg, &usvg::Options::default()).unwrap();
+    let bounds = tree.root().abs_bounding_box();
+    (
+      (tree.size().width(), tree.size().height()),
+      (bounds.left(), bounds.top(), bounds.right(), bounds.bottom()),
+    )
+  }
+
+  fn assert_close(actual: (f32, f32, f32, f32), expected: (f32, f32, f32, f32)) {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+    assert!(
+      close(actual.0, expected.0)
+        && close(actual.1, expected.1)
+        && close(actual.2, expected.2)
+        && close(actual.3, expected.3),
+      "{:?} != {:?}",
+      actual,
+      expected
+    );
+  }
+
+  #[test]
+  fn test_flip_svg_string_horizontal() {
+    let result = flip_svg_string(SIMPLE_SVG.to_string(), FlipDirection::Horizontal)
+      .expect("Flip operation should succeed");
+    assert!(
+      result.contains("<g transform=\"matrix(-1 0 0 1 100 0)\">"),
+      "Output should include horizontal flip matrix"
+    );
+  }
+
+  #[test]
+  fn test_flip_svg_string_vertical() {
+    let result = flip_svg_string(PROLOG_SVG.to_string(), FlipDirection::Vertical)
+      .expect("Flip operation should succeed");
+    assert!(
+      result.contains("<g transform=\"matrix(1 0 0 -1 0 200)\">"),
+      "Output should include vertical flip matrix"
+    );
+    let (size, bounds) = page_and_bounds(&result);
+    assert_eq!(size, (100.0, 200.0));
+    assert_close(bounds, (0.0, 180.0, 10.0, 200.0));
+  }
+
+  #[test]
+  fn test_mirror_and_rotate_update_view_box() {
+    // Mirroring about the diagonal swaps width and height.
+    let result = mirror_svg(PROLOG_SVG, 0.0, 0.0, 1.0, 1.0).unwrap();
+    let (size, bounds) = page_and_bounds(&result);
+    assert_eq!(size, (200.0, 100.0));
+    assert_close(bounds, (0.0, 0.0, 20.0, 10.0));
+
+    // A quarter turn around the origin moves the page to negative x.
+    let result = rotate_svg(PROLOG_SVG, 90.0, 0.0, 0.0).unwrap();
+    assert!(result.contains("viewBox=\"-200 0 200 100\""));
+    let (size, bounds) = page_and_bounds(&result);
+    assert_eq!(size, (200.0, 100.0));
+    assert_close(bounds, (180.0, 0.0, 200.0, 10.0));
+
+    let result = scale_svg(SIMPLE_SVG, 2.0, 0.5).unwrap();
+    assert_eq!(page_and_bounds(&result).0, (200.0, 100.0));
+    assert!(matches!(
+      scale_svg(SIMPLE_SVG, 0.0, 1.0),
+      Err(PreprocessorError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+      mirror_svg(SIMPLE_SVG, 1.0, 1.0, 1.0, 1.0),
+      Err(PreprocessorError::InvalidArgument { .. })
+    ));
+  }
+
+  #[test]
+  fn test_flip_svg_file_not_found() {
+    let err = flip_svg_file("missing.svg".to_string(), FlipDirection::Vertical).unwrap_err();
+    assert_eq!(err.code(), "FILE_NOT_FOUND");
+  }
 }
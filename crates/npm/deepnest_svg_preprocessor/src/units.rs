@@ -0,0 +1,211 @@
+//! Unit detection for imported SVG documents.
+//!
+//! usvg converts absolute lengths (mm, cm, in, pt, pc) to user units at the DPI of
+//! its options and maps the viewBox onto the document size, so one unit of the
+//! normalized output is one pixel at that DPI. The DPI therefore has to match the
+//! application that wrote the file, since px and unitless lengths mean different
+//! physical sizes in Inkscape, Illustrator and CorelDraw.
+
+use usvg::roxmltree;
+
+/// DPI used when neither a DPI nor the source application is known, as in Deepnest.
+pub const DEFAULT_DPI: f64 = 72.0;
+/// Inkscape 0.92 and later use the CSS resolution.
+pub const INKSCAPE_DPI: f64 = 96.0;
+pub const ILLUSTRATOR_DPI: f64 = 72.0;
+pub const CORELDRAW_DPI: f64 = 96.0;
+
+const MM_PER_INCH: f64 = 25.4;
+
+/// Application that wrote an SVG document
+#[cfg_attr(feature = "node", napi)]
+#[cfg_attr(not(feature = "node"), derive(Clone, Copy))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum SvgSource {
+  Inkscape,
+  Illustrator,
+  CorelDraw,
+}
+
+impl SvgSource {
+  pub fn dpi(self) -> f64 {
+    match self {
+      SvgSource::Inkscape => INKSCAPE_DPI,
+      SvgSource::Illustrator => ILLUSTRATOR_DPI,
+      SvgSource::CorelDraw => CORELDRAW_DPI,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      SvgSource::Inkscape => "inkscape",
+      SvgSource::Illustrator => "illustrator",
+      SvgSource::CorelDraw => "coreldraw",
+    }
+  }
+}
+
+/// The units of a document and the scale of its normalized output.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "node", napi(object))]
+pub struct SvgUnitReport {
+  /// unit of the `width` attribute: "mm", "cm", "in", "pt", "pc" or "px" (also for
+  /// unitless and missing widths)
+  pub unit: String,
+  /// detected application: "inkscape", "illustrator", "coreldraw" or "unknown"
+  pub source: String,
+  /// DPI used for px and unitless lengths
+  pub dpi: f64,
+  /// document width in millimeters, from `width` or the viewBox
+  pub width_mm: Option<f64>,
+  /// document height in millimeters, from `height` or the viewBox
+  pub height_mm: Option<f64>,
+  /// millimeters per unit of the normalized output
+  pub scale_to_mm: f64,
+}
+
+/// Detects the application that wrote the document from its namespaces and the
+/// generator comment.
+pub fn detect_source(document: &roxmltree::Document) -> Option<SvgSource> {
+  let root = document.root_element();
+  let namespaces: Vec<&str> = root.namespaces().map(|ns| ns.uri()).collect();
+  let has_namespace = |fragment: &str| namespaces.iter().any(|uri| uri.contains(fragment));
+  if has_namespace("inkscape.org") || has_namespace("sodipodi") {
+    return Some(SvgSource::Inkscape);
+  }
+  if has_namespace("corel.com") {
+    return Some(SvgSource::CorelDraw);
+  }
+  if has_namespace("ns.adobe.com") {
+    return Some(SvgSource::Illustrator);
+  }
+  document
+    .root()
+    .children()
+    .filter(|node| node.is_comment())
+    .find_map(|node| {
+      let text = node.text().unwrap_or_default();
+      if text.contains("Adobe Illustrator") {
+        Some(SvgSource::Illustrator)
+      } else if text.contains("CorelDRAW") {
+        Some(SvgSource::CorelDraw)
+      } else if text.contains("Inkscape") {
+        Some(SvgSource::Inkscape)
+      } else {
+        None
+      }
+    })
+}
+
+/// Detects the units of `svg_data`.
+///
+/// An explicit `dpi` wins over the DPI of `source`, which wins over the DPI of the
+/// detected application; `DEFAULT_DPI` is used if all are unknown.
+pub fn detect_units(
+  svg_data: &str,
+  dpi: Option<f64>,
+  source: Option<SvgSource>,
+) -> Result<SvgUnitReport, roxmltree::Error> {
+  let document = roxmltree::Document::parse(svg_data)?;
+  let root = document.root_element();
+  let detected = detect_source(&document);
+  let dpi = dpi
+    .or(source.map(SvgSource::dpi))
+    .or(detected.map(SvgSource::dpi))
+    .unwrap_or(DEFAULT_DPI);
+  let px_to_mm = MM_PER_INCH / dpi;
+
+  let width = root.attribute("width").and_then(parse_length);
+  let height = root.attribute("height").and_then(parse_length);
+  let view_box: Option<Vec<f64>> = root
+    .attribute("viewBox")
+    .map(|value| {
+      value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+    })
+    .filter(|numbers: &Vec<f64>| numbers.len() == 4);
+  let size_mm = |length: Option<(f64, &str)>, view_box_size: Option<f64>| match length {
+    Some((value, unit)) => unit_to_mm(unit, px_to_mm).map(|scale| value * scale),
+    None => view_box_size.map(|size| size * px_to_mm),
+  };
+
+  Ok(SvgUnitReport {
+    unit: match width {
+      Some((_, unit)) if !unit.is_empty() => unit.to_string(),
+      _ => "px".to_string(),
+    },
+    source: detected.map_or("unknown", SvgSource::name).to_string(),
+    dpi,
+    width_mm: size_mm(width, view_box.as_ref().map(|v| v[2])),
+    height_mm: size_mm(height, view_box.as_ref().map(|v| v[3])),
+    scale_to_mm: px_to_mm,
+  })
+}
+
+/// Splits a length such as "210mm" into its number and unit.
+fn parse_length(value: &str) -> Option<(f64, &str)> {
+  let value = value.trim();
+  let split = value
+    .find(|c: char| c.is_ascii_alphabetic() || c == '%')
+    .unwrap_or(value.len());
+  let number = value[..split].trim().parse().ok()?;
+  Some((number, value[split..].trim()))
+}
+
+/// Millimeters per unit, `None` for relative units such as "%" or "em".
+fn unit_to_mm(unit: &str, px_to_mm: f64) -> Option<f64> {
+  match unit {
+    "mm" => Some(1.0),
+    "cm" => Some(10.0),
+    "in" => Some(MM_PER_INCH),
+    "pt" => Some(MM_PER_INCH / 72.0),
+    "pc" => Some(MM_PER_INCH / 6.0),
+    "px" | "" => Some(px_to_mm),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_detect_units_inkscape_mm() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"
+      xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape"
+      width="210mm" height="297mm" viewBox="0 0 210 297"/>"#;
+    let report = detect_units(svg, None, None).unwrap();
+    assert_eq!(report.source, "inkscape");
+    assert_eq!(report.unit, "mm");
+    assert_eq!(report.dpi, 96.0);
+    assert_eq!(report.width_mm, Some(210.0));
+    assert_eq!(report.height_mm, Some(297.0));
+    // usvg maps the 210 mm onto 210 / 25.4 * 96 pixels.
+    assert!((report.scale_to_mm * 210.0 / 25.4 * 96.0 - 210.0).abs() < 1e-9);
+
+    // An explicit DPI wins over the detected application.
+    let report = detect_units(svg, Some(72.0), Some(SvgSource::CorelDraw)).unwrap();
+    assert_eq!(report.dpi, 72.0);
+  }
+
+  #[test]
+  fn test_detect_units_illustrator_px() {
+    let svg = r#"<?xml version="1.0"?>
+<!-- Generator: Adobe Illustrator 24.0.0, SVG Export Plug-In -->
+<svg xmlns="http://www.w3.org/2000/svg" width="144px" viewBox="0 0 144 72"/>"#;
+    let report = detect_units(svg, None, None).unwrap();
+    assert_eq!(report.source, "illustrator");
+    assert_eq!(report.unit, "px");
+    assert_eq!(report.width_mm, Some(50.8));
+    assert_eq!(report.height_mm, Some(25.4));
+
+    let report = detect_units(r#"<svg width="72pt" height="50%"/>"#, None, None).unwrap();
+    assert_eq!(report.source, "unknown");
+    assert_eq!(report.dpi, DEFAULT_DPI);
+    assert_eq!(report.width_mm, Some(25.4));
+    assert_eq!(report.height_mm, None);
+  }
+}
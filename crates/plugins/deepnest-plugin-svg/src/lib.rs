@@ -1,14 +1,165 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+//! SVG import and export as a Deepnest format plugin.
+//!
+//! Import extracts the closed contours with `deepnest_svg_preprocessor::extract_parts`.
+//! Export writes the parts as paths and places them with `write_nest_svg`, so the
+//! result carries the same placement metadata as the SVG export of the app.
+
+use std::fmt::Write;
+
+use deepnest_plugin_api::{declare_plugin, FormatPlugin, Nest, Part, PluginError};
+use deepnest_svg_preprocessor::{
+  extract_parts, write_nest_svg, SvgExportError, SvgNestExportOptions, SvgNestPlacement,
+  SvgNestSheet,
+};
+use deepnest_types::types::Point;
+
+#[derive(Debug, Default)]
+pub struct SvgPlugin;
+
+impl FormatPlugin for SvgPlugin {
+  fn format_id(&self) -> &str {
+    "svg"
+  }
+
+  fn extensions(&self) -> Vec<String> {
+    vec!["svg".to_string()]
+  }
+
+  fn import(&self, data: &[u8]) -> Result<Vec<Part>, PluginError> {
+    let svg = std::str::from_utf8(data).map_err(|err| PluginError::Format(err.to_string()))?;
+    let result = extract_parts(svg, None).map_err(|err| PluginError::Format(err.to_string()))?;
+    Ok(
+      result
+        .parts
+        .into_iter()
+        .map(|part| Part {
+          id: part.id,
+          polygon: part.polygon,
+        })
+        .collect(),
+    )
+  }
+
+  fn export(&self, nest: &Nest) -> Result<Vec<u8>, PluginError> {
+    let sheets = nest
+      .sheets
+      .iter()
+      .enumerate()
+      .map(|(index, sheet)| SvgNestSheet {
+        index: Some(index as u32),
+        width: sheet.width,
+        height: sheet.height,
+        placements: sheet
+          .placements
+          .iter()
+          .map(|placement| SvgNestPlacement {
+            part_id: part_id(placement.part),
+            x: placement.x,
+            y: placement.y,
+            rotation: placement.rotation,
+          })
+          .collect(),
+      })
+      .collect::<Vec<_>>();
+    let options = SvgNestExportOptions {
+      combined: Some(true),
+      sheet_spacing: None,
+    };
+    let documents =
+      write_nest_svg(&parts_svg(&nest.parts), &sheets, Some(options)).map_err(|err| match err {
+        SvgExportError::MissingPart(id) => {
+          PluginError::Format(format!("a placement refers to the unknown part {}", id))
+        }
+        err => PluginError::Internal(err.to_string()),
+      })?;
+    Ok(documents.concat().into_bytes())
+  }
+}
+
+declare_plugin!(SvgPlugin);
+
+/// Id of a part in the generated source SVG. The ids of the parts themselves are
+/// not used, since they need not be unique or valid XML ids.
+fn part_id(index: usize) -> String {
+  format!("part-{}", index)
+}
+
+/// A document with one path per part, as the source of `write_nest_svg`.
+fn parts_svg(parts: &[Part]) -> String {
+  // The size only has to be valid, parts outside of it are kept.
+  let mut svg =
+    String::from("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1\" height=\"1\">\n");
+  // Writing to a String never fails.
+  for (index, part) in parts.iter().enumerate() {
+    let mut d = String::new();
+    let rings = std::iter::once(&part.polygon.points).chain(part.polygon.children.iter().flatten());
+    for ring in rings {
+      ring_data(&mut d, ring);
+    }
+    let _ = writeln!(
+      svg,
+      "<path id=\"{}\" d=\"{}\" fill-rule=\"evenodd\" fill=\"none\" stroke=\"#000000\"/>",
+      part_id(index),
+      d.trim_start()
+    );
+  }
+  svg.push_str("</svg>\n");
+  svg
+}
+
+fn ring_data(d: &mut String, ring: &[Point]) {
+  for (i, p) in ring.iter().enumerate() {
+    let _ = write!(d, " {} {} {}", if i == 0 { "M" } else { "L" }, p.x, p.y);
+  }
+  if !ring.is_empty() {
+    d.push_str(" Z");
+  }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+  use super::*;
+  use deepnest_plugin_api::{Placement, Sheet};
+  use deepnest_svg_preprocessor::parse_nest_svg;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
+  #[test]
+  fn test_import_and_export() {
+    let svg = br#"<svg width="100" height="100" xmlns="http://www.w3.org/2000/svg">
+<path id="frame" d="M 0 0 H 40 V 40 H 0 Z M 10 10 V 30 H 30 V 10 Z" fill-rule="evenodd"/>
+</svg>"#;
+    let parts = SvgPlugin.import(svg).unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].id.as_deref(), Some("frame"));
+    assert_eq!(parts[0].polygon.children.as_ref().map(Vec::len), Some(1));
+
+    let nest = Nest {
+      parts,
+      sheets: vec![Sheet {
+        width: 100.0,
+        height: 50.0,
+        placements: vec![Placement {
+          part: 0,
+          x: 50.0,
+          y: 5.0,
+          rotation: 90.0,
+        }],
+      }],
+    };
+    let exported = String::from_utf8(SvgPlugin.export(&nest).unwrap()).unwrap();
+    let sheets = parse_nest_svg(&exported).unwrap();
+    assert_eq!(sheets.len(), 1);
+    assert_eq!(sheets[0].placements[0].part_id, "part-0");
+    assert_eq!(sheets[0].placements[0].rotation, 90.0);
+
+    let mut invalid = nest.clone();
+    invalid.sheets[0].placements[0].part = 3;
+    assert!(matches!(
+      SvgPlugin.export(&invalid),
+      Err(PluginError::Format(_))
+    ));
+    assert!(matches!(
+      SvgPlugin.import(b"<svg"),
+      Err(PluginError::Format(_))
+    ));
+  }
 }